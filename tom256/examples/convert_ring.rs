@@ -0,0 +1,32 @@
+use structopt::StructOpt;
+use tom256::curve::Tom256k1;
+use tom256::ring::convert_json_ring;
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::PathBuf;
+
+#[derive(StructOpt)]
+struct Opt {
+    #[structopt(long, help = "array of public keys as string")]
+    ring: PathBuf,
+    #[structopt(long, help = "file where the binary ring is written")]
+    output: PathBuf,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let opt = Opt::from_args();
+
+    let reader = BufReader::new(File::open(opt.ring)?);
+    let writer = BufWriter::new(File::create(opt.output)?);
+    let header = convert_json_ring::<Tom256k1, _, _>(reader, writer)?;
+
+    let digest = header
+        .digest
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+    println!("converted {} public keys, digest 0x{}", header.len, digest);
+    Ok(())
+}
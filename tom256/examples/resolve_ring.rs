@@ -0,0 +1,37 @@
+use structopt::StructOpt;
+use tom256::ring::{resolve_ring, TransactionDump};
+
+use std::error::Error;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+
+#[derive(StructOpt)]
+struct Opt {
+    #[structopt(long, help = "array of addresses as string")]
+    addresses: PathBuf,
+    #[structopt(long, help = "array of raw signed transactions as string")]
+    transactions: PathBuf,
+    #[structopt(long, help = "file where the ring is written")]
+    ring: PathBuf,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let opt = Opt::from_args();
+
+    let addresses: Vec<String> =
+        serde_json::from_reader(BufReader::new(File::open(opt.addresses)?))?;
+    let transactions: TransactionDump =
+        serde_json::from_reader(BufReader::new(File::open(opt.transactions)?))?;
+
+    let resolution = resolve_ring(&addresses, &transactions)?;
+
+    let ring_file = File::create(opt.ring)?;
+    serde_json::to_writer(ring_file, &resolution.ring)?;
+
+    println!("resolved {} public keys", resolution.ring.len());
+    for address in &resolution.unresolved {
+        println!("unresolved: {}", address);
+    }
+    Ok(())
+}
@@ -3,6 +3,7 @@ use structopt::StructOpt;
 use tom256::curve::{Secp256k1, Tom256k1};
 use tom256::parse::*;
 use tom256::proofs::ZkAttestProof;
+use tom256::ring::MappedRing;
 
 use std::error::Error;
 use std::fs::File;
@@ -15,23 +16,27 @@ struct Opt {
     proof: PathBuf,
     #[structopt(long, help = "array of public keys as string")]
     ring: PathBuf,
+    #[structopt(long, help = "the ring is given in the binary format")]
+    binary: bool,
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
     let mut rng = OsRng;
     let opt = Opt::from_args();
 
-    let ring_file = File::open(opt.ring)?;
-    let ring_reader = BufReader::new(ring_file);
-
-    let ring: Ring = serde_json::from_reader(ring_reader)?;
-    let parsed_ring = parse_ring(ring)?;
-
     let proof_binary = std::fs::read(opt.proof).unwrap();
     let proof: ZkAttestProof<Secp256k1, Tom256k1> =
         borsh::BorshDeserialize::try_from_slice(proof_binary.as_slice()).unwrap();
 
-    proof.verify(&mut rng, &parsed_ring)?;
+    if opt.binary {
+        let mapped_ring = MappedRing::<Tom256k1>::open(opt.ring)?;
+        proof.verify(&mut rng, &mapped_ring)?;
+    } else {
+        let ring_reader = BufReader::new(File::open(opt.ring)?);
+        let ring: Ring = serde_json::from_reader(ring_reader)?;
+        let parsed_ring = parse_ring(ring)?;
+        proof.verify(&mut rng, &parsed_ring)?;
+    }
     println!("Proof OK");
     Ok(())
 }
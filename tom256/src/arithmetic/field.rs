@@ -22,6 +22,65 @@ impl<C: Curve> FieldElement<C> {
     pub fn to_cycle_scalar<CC: Cycle<C>>(self) -> Scalar<CC> {
         Scalar::<CC>::new(self.0)
     }
+
+    pub fn is_odd(&self) -> bool {
+        self.0.to_be_bytes()[31] & 1 == 1
+    }
+
+    /// Returns a square root of the element if it is a quadratic residue.
+    ///
+    /// Uses the Tonelli-Shanks algorithm, thus it works for any odd prime
+    /// modulus, not only for `p = 3 mod 4`.
+    pub fn sqrt(&self) -> Option<Self> {
+        if *self == Self::ZERO {
+            return Some(Self::ZERO);
+        }
+        let p_minus_one = Self::MODULUS.wrapping_sub(&U256::ONE);
+        // Euler's criterion
+        let legendre_exp = Self(p_minus_one.shr_vartime(1), PhantomData);
+        if self.pow(&legendre_exp) != Self::ONE {
+            return None;
+        }
+
+        // p - 1 = q * 2^s with q odd
+        let mut s = 0;
+        let mut q = p_minus_one;
+        while q.to_be_bytes()[31] & 1 == 0 {
+            q = q.shr_vartime(1);
+            s += 1;
+        }
+        let minus_one = -Self::ONE;
+        let non_residue = (2u32..)
+            .map(|z| Self::new(U256::from_u32(z)))
+            .find(|z| z.pow(&legendre_exp) == minus_one)
+            .unwrap();
+
+        let mut c = non_residue.pow(&Self(q, PhantomData));
+        let mut t = self.pow(&Self(q, PhantomData));
+        let mut root = self.pow(&Self(
+            q.wrapping_add(&U256::ONE).shr_vartime(1),
+            PhantomData,
+        ));
+        let mut m = s;
+        while t != Self::ONE {
+            // least i such that t^(2^i) = 1
+            let mut i = 0;
+            let mut t_pow = t;
+            while t_pow != Self::ONE {
+                t_pow = t_pow * t_pow;
+                i += 1;
+            }
+            let mut b = c;
+            for _ in 0..m - i - 1 {
+                b = b * b;
+            }
+            root *= b;
+            c = b * b;
+            t *= c;
+            m = i;
+        }
+        Some(root)
+    }
 }
 
 impl<C: Curve> Modular for FieldElement<C> {
@@ -272,6 +331,27 @@ mod test {
         assert_eq!(a_min_b, -b_min_a);
     }
 
+    #[test]
+    fn square_root() {
+        for i in 0..17u32 {
+            let a = FeSmall::new(U256::from_u32(i));
+            match a.sqrt() {
+                Some(root) => assert_eq!(root * root, a),
+                None => assert!((0..17u32)
+                    .map(|j| FeSmall::new(U256::from_u32(j)))
+                    .all(|j| j * j != a)),
+            }
+        }
+
+        // the tom256k1 prime is 1 mod 4
+        let a = FieldElement::<Tom256k1>::new(Tom256k1::GENERATOR_X);
+        let square = a * a;
+        let root = square.sqrt().unwrap();
+        assert!(root == a || root == -a);
+        let b = FeLarge::new(Secp256k1::GENERATOR_X);
+        assert_eq!((b * b).sqrt().map(|root| root * root), Some(b * b));
+    }
+
     #[test]
     fn serde_round() {
         let le_hex = "ce7c73f82cc708b9080499663f89fda1fa7bb76d78b72b4042554f33e418b94f";
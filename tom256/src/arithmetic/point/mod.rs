@@ -1,7 +1,8 @@
 mod impl_macro;
 
-use super::{FieldElement, Modular};
-use crate::curve::Curve;
+use super::{FieldElement, Modular, Scalar};
+use crate::curve::{Curve, Secp256k1};
+use crate::hasher::PointHasher;
 
 use borsh::{BorshDeserialize, BorshSerialize};
 use serde::Serialize;
@@ -81,6 +82,30 @@ impl<C: Curve> AffinePoint<C> {
             z: FieldElement::ONE,
         }
     }
+
+    /// Deterministically maps the label and the index to a curve point whose
+    /// discrete logarithm is unknown.
+    ///
+    /// Hashes the inputs with an increasing counter until the result is a
+    /// valid x coordinate, then takes the even y coordinate.
+    pub fn hash_to_curve(label: &[u8], index: u64) -> Self {
+        let a = FieldElement::new(C::COEFF_A);
+        let b = FieldElement::new(C::COEFF_B);
+        for counter in 0u32.. {
+            let mut hasher = PointHasher::new(label);
+            hasher.insert_bytes(&index.to_be_bytes());
+            hasher.insert_bytes(&counter.to_be_bytes());
+            let x = FieldElement::new(hasher.finalize());
+            if let Some(y) = (x * x * x + a * x + b).sqrt() {
+                return if y.is_odd() {
+                    Self::new(x, -y)
+                } else {
+                    Self::new(x, y)
+                };
+            }
+        }
+        unreachable!("no valid x coordinate found")
+    }
 }
 
 impl<C: Curve> PartialEq for AffinePoint<C> {
@@ -89,6 +114,64 @@ impl<C: Curve> PartialEq for AffinePoint<C> {
     }
 }
 
+impl Point<Secp256k1> {
+    /// Recovers the public key from an ECDSA signature and the signed message
+    /// hash.
+    ///
+    /// The lower bit of the recovery id is the parity of `R.y`, the upper bit
+    /// is set if `R.x` overflowed the curve order and thus `R.x = r + n`.
+    pub fn recover_pubkey(
+        msg_hash: &Scalar<Secp256k1>,
+        r: &Scalar<Secp256k1>,
+        s: &Scalar<Secp256k1>,
+        recovery_id: u8,
+    ) -> Result<AffinePoint<Secp256k1>, String> {
+        if *r == Scalar::ZERO || *s == Scalar::ZERO {
+            return Err("invalid signature".to_string());
+        }
+
+        let mut r_x = *r.inner();
+        if recovery_id & 2 != 0 {
+            // NOTE only a tiny fraction of x coordinates can overflow the order
+            let max = Secp256k1::PRIME_MODULUS.wrapping_sub(&Secp256k1::ORDER);
+            if r_x >= max {
+                return Err("invalid recovery id".to_string());
+            }
+            r_x = r_x.wrapping_add(&Secp256k1::ORDER);
+        }
+
+        let r_point = AffinePoint::decompress(FieldElement::new(r_x), recovery_id & 1 == 1)
+            .map_err(|_| "R is not on the curve".to_string())?;
+
+        // pk = r^-1 * (s * R - z * G)
+        let r_inv = r.inverse();
+        let pubkey = Point::from(r_point).double_mul(
+            &(r_inv * *s),
+            &Point::GENERATOR,
+            &-(r_inv * *msg_hash),
+        );
+        if pubkey.is_identity() {
+            return Err("recovered pubkey is at infinity".to_string());
+        }
+        Ok(pubkey.to_affine())
+    }
+}
+
+impl AffinePoint<Secp256k1> {
+    /// Returns the point with the given x coordinate and y parity.
+    pub fn decompress(x: FieldElement<Secp256k1>, y_is_odd: bool) -> Result<Self, String> {
+        let y_squared = x * x * x + FieldElement::new(Secp256k1::COEFF_B);
+        let y = y_squared
+            .sqrt()
+            .ok_or_else(|| "point is not on the curve".to_string())?;
+        if y.is_odd() == y_is_odd {
+            Ok(Self::new(x, y))
+        } else {
+            Ok(Self::new(x, -y))
+        }
+    }
+}
+
 // Conversions
 
 impl<C: Curve> From<Point<C>> for AffinePoint<C> {
@@ -305,4 +388,76 @@ mod test {
             assert_eq!(dbl_mul_rev, expected);
         }
     }
+
+    #[test]
+    fn pubkey_recovery() {
+        // signature created by a wallet
+        let msg_hash = SecScalar::new(U256::from_be_hex(
+            "1ab4850e7f0a85a521e87b274e3130efdb45f6a47e74e6dcebf5591c6bc8f16e",
+        ));
+        let r = SecScalar::new(U256::from_be_hex(
+            "45c4039b611c0cc207ff7fb7a6899ea0431aac2cf37515d74a71f2df00e2c3e0",
+        ));
+        let s = SecScalar::new(U256::from_be_hex(
+            "096fad5e7eda762898fffd4644f8a7a406bf6bde868814ea03058c882fcd2331",
+        ));
+        let pubkey = SecPoint::recover_pubkey(&msg_hash, &r, &s, 1).unwrap();
+        assert_eq!(
+            pubkey.x().inner(),
+            &U256::from_be_hex("08c6cd9400645819c8c556a6e83e0a7728f070a813bb9d24d5c24290e21fc5e4")
+        );
+        assert_eq!(
+            pubkey.y().inner(),
+            &U256::from_be_hex("38396f9333264d3e7c1d3e6ee1bc572b2f00b98db7065e9bf278f2b8dbe02718")
+        );
+
+        assert_ne!(
+            SecPoint::recover_pubkey(&msg_hash, &r, &s, 0).unwrap(),
+            pubkey
+        );
+        assert_eq!(
+            SecPoint::recover_pubkey(&msg_hash, &r, &s, 2),
+            Err("invalid recovery id".to_string())
+        );
+        assert!(SecPoint::recover_pubkey(&msg_hash, &r, &SecScalar::ZERO, 1).is_err());
+    }
+
+    #[test]
+    fn hash_to_curve() {
+        let point = TomAffine::hash_to_curve(b"generator", 0);
+        assert!(TomPoint::from(point.clone()).is_on_curve());
+        assert!(!point.y().is_odd());
+        assert_eq!(point, TomAffine::hash_to_curve(b"generator", 0));
+        assert_ne!(point, TomAffine::hash_to_curve(b"generator", 1));
+        assert_ne!(point, TomAffine::hash_to_curve(b"other", 0));
+
+        let point = SecAffine::hash_to_curve(b"generator", 0);
+        assert!(SecPoint::from(point).is_on_curve());
+    }
+
+    #[test]
+    fn pubkey_recovery_overflow() {
+        // find a point whose x coordinate exceeds the curve order
+        let b = FieldElement::<Secp256k1>::new(Secp256k1::COEFF_B);
+        let (x, y) = (1u8..)
+            .map(|t| FieldElement::new(Secp256k1::ORDER.wrapping_add(&U256::from_u8(t))))
+            .find_map(|x| (x * x * x + b).sqrt().map(|y| (x, y)))
+            .unwrap();
+        let r_point = SecPoint::from(SecAffine::new(x, y));
+        assert!(r_point.is_on_curve());
+
+        let msg_hash = SecScalar::new(U256::from_u32(123456));
+        let r = SecScalar::new(*x.inner());
+        let s = SecScalar::new(U256::from_u32(678910));
+        let recovery_id = 2 | y.is_odd() as u8;
+        let pubkey = SecPoint::recover_pubkey(&msg_hash, &r, &s, recovery_id).unwrap();
+
+        // the signature verifies under the recovered key
+        let s_inv = s.inverse();
+        let verifier_r_point: SecAffine = SecPoint::GENERATOR
+            .double_mul(&(s_inv * msg_hash), &pubkey.into(), &(s_inv * r))
+            .into();
+        assert_eq!(SecScalar::new(*verifier_r_point.x().inner()), r);
+        assert_eq!(verifier_r_point.x(), &x);
+    }
 }
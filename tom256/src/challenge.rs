@@ -0,0 +1,181 @@
+use crate::arithmetic::{Modular, Scalar};
+use crate::claims::ProofClaims;
+use crate::curve::Curve;
+use crate::eip712::{self, Eip712Domain};
+use crate::message::MessageScheme;
+use crate::rng::CryptoCoreRng;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+
+pub(crate) const JOIN_GUILD_MSG: &str = "#zkp/join.guild.xyz/";
+
+const NONCE_BYTES: usize = 16;
+
+/// Challenge issued by the verifier that the user signs when joining a guild.
+///
+/// The signed message contains a verifier-chosen nonce and an expiry
+/// timestamp (seconds since the Unix epoch), so a proof is only accepted once
+/// and only within the validity window of the challenge.
+///
+/// If an EIP-712 domain is set, the user signs the challenge as typed data
+/// instead of the plain text message.
+#[derive(Clone, Debug, PartialEq, Eq, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JoinChallenge {
+    pub claims: ProofClaims,
+    pub nonce: String,
+    pub expires_at: u64,
+    #[serde(default)]
+    pub domain: Option<Eip712Domain>,
+}
+
+impl JoinChallenge {
+    pub fn new<R: CryptoCoreRng>(rng: &mut R, claims: ProofClaims, expires_at: u64) -> Self {
+        let mut nonce_bytes = [0u8; NONCE_BYTES];
+        rng.fill_bytes(&mut nonce_bytes);
+        let nonce = nonce_bytes.iter().map(|b| format!("{:02x}", b)).collect();
+
+        Self {
+            claims,
+            nonce,
+            expires_at,
+            domain: None,
+        }
+    }
+
+    /// Checks that the fields can be encoded unambiguously into the signed
+    /// message.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.nonce.is_empty() {
+            return Err("empty challenge nonce".to_string());
+        }
+        if self.nonce.chars().any(char::is_control) {
+            return Err("challenge nonce contains a control character".to_string());
+        }
+        if let Some(domain) = self.domain.as_ref() {
+            domain.separator()?;
+            eip712::check_claims(&self.claims)?;
+        }
+        self.claims.validate()
+    }
+
+    /// Returns the message that the user signs with their wallet.
+    pub fn message(&self) -> String {
+        format!(
+            "{}\nnonce: {}\nexpires: {}",
+            self.claims.encode(JOIN_GUILD_MSG),
+            self.nonce,
+            self.expires_at
+        )
+    }
+
+    /// Returns the hash signed by the user.
+    ///
+    /// This is the EIP-712 typed data hash if the challenge has a domain,
+    /// otherwise the hash of [`Self::message`] with the given scheme. Typed
+    /// messages are only signed with the default scheme.
+    pub fn msg_hash<C: Curve>(&self, scheme: MessageScheme) -> Result<Scalar<C>, String> {
+        match self.domain.as_ref() {
+            Some(_) if scheme != MessageScheme::default() => {
+                Err("message scheme is not supported for typed messages".to_string())
+            }
+            Some(domain) => {
+                let struct_hash = eip712::join_struct_hash(self);
+                Ok(Scalar::new(eip712::typed_data_hash(domain, &struct_hash)?))
+            }
+            None => Ok(scheme.hash(&self.message())),
+        }
+    }
+
+    pub fn check_expiry(&self, now: u64) -> Result<(), String> {
+        if now > self.expires_at {
+            Err("challenge expired".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Storage of challenges issued by the verifier.
+pub trait ChallengeStore {
+    /// Removes and returns the challenge issued with the given nonce, so that
+    /// it cannot be used again.
+    fn take(&mut self, nonce: &str) -> Option<JoinChallenge>;
+}
+
+impl ChallengeStore for HashMap<String, JoinChallenge> {
+    fn take(&mut self, nonce: &str) -> Option<JoinChallenge> {
+        self.remove(nonce)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand_core::SeedableRng;
+
+    #[test]
+    fn challenge_message() {
+        let mut challenge = JoinChallenge {
+            claims: ProofClaims::new("almafa".to_string()),
+            nonce: "a1b2c3".to_string(),
+            expires_at: 1_700_000_000,
+            domain: None,
+        };
+        assert!(challenge.validate().is_ok());
+        assert_eq!(
+            challenge.message(),
+            "#zkp/join.guild.xyz/almafa\nnonce: a1b2c3\nexpires: 1700000000"
+        );
+
+        challenge.claims.platform_user_id = Some("1234".to_string());
+        assert_eq!(
+            challenge.message(),
+            "#zkp/join.guild.xyz/almafa\nuser: 1234\nnonce: a1b2c3\nexpires: 1700000000"
+        );
+
+        challenge.nonce = "a1b2c3\rnonce: 00".to_string();
+        assert_eq!(
+            challenge.validate(),
+            Err("challenge nonce contains a control character".to_string())
+        );
+        challenge.nonce = "a1b2\u{1b}c3".to_string();
+        assert!(challenge.validate().is_err());
+        challenge.nonce = "a1b2c3".to_string();
+
+        challenge.claims.guild_id = "almafa\nnonce: 00".to_string();
+        assert!(challenge.validate().is_err());
+    }
+
+    #[test]
+    fn challenge_freshness() {
+        let mut rng = StdRng::from_seed([3; 32]);
+        let challenge = JoinChallenge::new(&mut rng, ProofClaims::new("almafa".to_string()), 100);
+        assert_eq!(challenge.nonce.len(), 2 * NONCE_BYTES);
+        assert!(challenge.check_expiry(99).is_ok());
+        assert!(challenge.check_expiry(100).is_ok());
+        assert_eq!(
+            challenge.check_expiry(101),
+            Err("challenge expired".to_string())
+        );
+
+        let other = JoinChallenge::new(&mut rng, ProofClaims::new("almafa".to_string()), 100);
+        assert_ne!(challenge.nonce, other.nonce);
+    }
+
+    #[test]
+    fn challenge_single_use() {
+        let mut rng = StdRng::from_seed([4; 32]);
+        let challenge = JoinChallenge::new(&mut rng, ProofClaims::new("almafa".to_string()), 100);
+
+        let mut store = HashMap::new();
+        store.insert(challenge.nonce.clone(), challenge.clone());
+
+        assert_eq!(store.take(&challenge.nonce), Some(challenge.clone()));
+        assert_eq!(store.take(&challenge.nonce), None);
+    }
+}
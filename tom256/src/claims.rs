@@ -0,0 +1,130 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+
+/// Application claims bound to a proof through the signed message.
+///
+/// Every optional claim is encoded on its own line, context entries follow in
+/// lexicographic key order, thus the encoding is canonical and a claim cannot
+/// be altered or swapped without invalidating the signature.
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, BorshDeserialize, BorshSerialize, Serialize, Deserialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofClaims {
+    pub guild_id: String,
+    #[serde(default)]
+    pub platform: Option<String>,
+    #[serde(default)]
+    pub platform_user_id: Option<String>,
+    #[serde(default)]
+    pub role: Option<String>,
+    #[serde(default)]
+    pub context: BTreeMap<String, String>,
+}
+
+impl ProofClaims {
+    pub fn new(guild_id: String) -> Self {
+        Self {
+            guild_id,
+            ..Self::default()
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.guild_id.is_empty() {
+            return Err("empty guild id".to_string());
+        }
+        let values = [
+            Some(&self.guild_id),
+            self.platform.as_ref(),
+            self.platform_user_id.as_ref(),
+            self.role.as_ref(),
+        ];
+        if values
+            .into_iter()
+            .flatten()
+            .chain(self.context.values())
+            .any(|value| value.chars().any(char::is_control))
+        {
+            return Err("claim contains a control character".to_string());
+        }
+        if !self.context.keys().all(|key| is_valid_key(key)) {
+            return Err("invalid context key".to_string());
+        }
+        Ok(())
+    }
+
+    /// Returns the canonical encoding of the claims.
+    pub fn encode(&self, prefix: &str) -> String {
+        let mut encoded = format!("{}{}", prefix, self.guild_id);
+        let optional = [
+            ("platform", self.platform.as_ref()),
+            ("user", self.platform_user_id.as_ref()),
+            ("role", self.role.as_ref()),
+        ];
+        for (key, value) in optional {
+            if let Some(value) = value {
+                encoded.push_str(&format!("\n{}: {}", key, value));
+            }
+        }
+        for (key, value) in self.context.iter() {
+            encoded.push_str(&format!("\nctx.{}: {}", key, value));
+        }
+        encoded
+    }
+}
+
+fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn canonical_encoding() {
+        let mut claims = ProofClaims::new("almafa".to_string());
+        assert!(claims.validate().is_ok());
+        assert_eq!(claims.encode("#prefix/"), "#prefix/almafa");
+
+        claims.platform = Some("discord".to_string());
+        claims.platform_user_id = Some("1234".to_string());
+        claims.role = Some("holder".to_string());
+        claims
+            .context
+            .insert("token".to_string(), "0xdead".to_string());
+        claims.context.insert("chain".to_string(), "1".to_string());
+        assert!(claims.validate().is_ok());
+        assert_eq!(
+            claims.encode("#prefix/"),
+            "#prefix/almafa\nplatform: discord\nuser: 1234\nrole: holder\nctx.chain: 1\nctx.token: 0xdead"
+        );
+    }
+
+    #[test]
+    fn invalid_claims() {
+        let mut claims = ProofClaims::new(String::new());
+        assert_eq!(claims.validate(), Err("empty guild id".to_string()));
+
+        claims.guild_id = "almafa".to_string();
+        claims.role = Some("holder\nuser: 1".to_string());
+        assert_eq!(
+            claims.validate(),
+            Err("claim contains a control character".to_string())
+        );
+        claims.role = Some("holder\ruser: 1".to_string());
+        assert!(claims.validate().is_err());
+
+        claims.role = None;
+        claims
+            .context
+            .insert("chain: 1\nctx.x".to_string(), "1".to_string());
+        assert_eq!(claims.validate(), Err("invalid context key".to_string()));
+    }
+}
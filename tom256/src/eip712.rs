@@ -0,0 +1,276 @@
+use crate::challenge::JoinChallenge;
+use crate::claims::ProofClaims;
+use crate::U256;
+
+use bigint::Encoding;
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+const DOMAIN_TYPE: &str =
+    "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+// NOTE referenced struct types are appended to the type string in
+// alphabetical order
+const JOIN_GUILD_TYPE: &str = "JoinGuild(string guildId,string platform,string platformUserId,\
+    string role,ContextEntry[] context,string nonce,uint256 expiresAt)\
+    ContextEntry(string key,string value)";
+const CONTEXT_ENTRY_TYPE: &str = "ContextEntry(string key,string value)";
+
+/// EIP-712 domain of the typed join message.
+#[derive(Clone, Debug, PartialEq, Eq, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Eip712Domain {
+    pub name: String,
+    pub version: String,
+    pub chain_id: u64,
+    pub verifying_contract: String,
+}
+
+impl Eip712Domain {
+    pub fn separator(&self) -> Result<[u8; 32], String> {
+        let mut encoder = StructEncoder::new(DOMAIN_TYPE);
+        encoder.string(&self.name);
+        encoder.string(&self.version);
+        encoder.uint(self.chain_id);
+        encoder.address(&self.verifying_contract)?;
+        Ok(encoder.finalize())
+    }
+}
+
+/// Computes `hashStruct` of a struct by encoding its members in the order of
+/// the type string.
+///
+/// Only the member types used by the join message are supported.
+pub struct StructEncoder {
+    hasher: Keccak256,
+}
+
+impl StructEncoder {
+    pub fn new(type_string: &str) -> Self {
+        let mut hasher = Keccak256::new();
+        hasher.update(Keccak256::digest(type_string.as_bytes()));
+        Self { hasher }
+    }
+
+    pub fn string(&mut self, value: &str) {
+        self.hasher.update(Keccak256::digest(value.as_bytes()));
+    }
+
+    pub fn uint(&mut self, value: u64) {
+        self.hasher.update(U256::from_u64(value).to_be_bytes());
+    }
+
+    pub fn address(&mut self, address: &str) -> Result<(), String> {
+        self.hasher.update(parse_address(address)?.to_be_bytes());
+        Ok(())
+    }
+
+    /// Encodes a struct member given by its `hashStruct`.
+    pub fn struct_hash(&mut self, struct_hash: &[u8; 32]) {
+        self.hasher.update(struct_hash);
+    }
+
+    /// Encodes an array of structs given by their `hashStruct`s.
+    pub fn struct_array(&mut self, struct_hashes: &[[u8; 32]]) {
+        let mut hasher = Keccak256::new();
+        for struct_hash in struct_hashes {
+            hasher.update(struct_hash);
+        }
+        self.hasher.update(hasher.finalize());
+    }
+
+    pub fn finalize(self) -> [u8; 32] {
+        self.hasher.finalize().into()
+    }
+}
+
+/// Returns `hashStruct` of the join message.
+///
+/// Every claim is a typed member of the struct, absent optional claims are
+/// encoded as empty strings and context entries as an array of key-value
+/// structs in lexicographic key order.
+pub fn join_struct_hash(challenge: &JoinChallenge) -> [u8; 32] {
+    let claims = &challenge.claims;
+    let context = claims
+        .context
+        .iter()
+        .map(|(key, value)| {
+            let mut encoder = StructEncoder::new(CONTEXT_ENTRY_TYPE);
+            encoder.string(key);
+            encoder.string(value);
+            encoder.finalize()
+        })
+        .collect::<Vec<_>>();
+
+    let mut encoder = StructEncoder::new(JOIN_GUILD_TYPE);
+    encoder.string(&claims.guild_id);
+    encoder.string(claims.platform.as_deref().unwrap_or_default());
+    encoder.string(claims.platform_user_id.as_deref().unwrap_or_default());
+    encoder.string(claims.role.as_deref().unwrap_or_default());
+    encoder.struct_array(&context);
+    encoder.string(&challenge.nonce);
+    encoder.uint(challenge.expires_at);
+    encoder.finalize()
+}
+
+/// Checks that the claims can be encoded unambiguously into the typed join
+/// message.
+pub fn check_claims(claims: &ProofClaims) -> Result<(), String> {
+    let optional = [
+        claims.platform.as_ref(),
+        claims.platform_user_id.as_ref(),
+        claims.role.as_ref(),
+    ];
+    // NOTE absent claims are encoded as empty strings
+    if optional.into_iter().flatten().any(String::is_empty) {
+        return Err("empty optional claim in typed message".to_string());
+    }
+    Ok(())
+}
+
+/// Returns the digest signed by `eth_signTypedData`.
+pub fn typed_data_hash(domain: &Eip712Domain, struct_hash: &[u8; 32]) -> Result<U256, String> {
+    let mut hasher = Keccak256::new();
+    hasher.update([0x19, 0x01]);
+    hasher.update(domain.separator()?);
+    hasher.update(struct_hash);
+    Ok(U256::from_be_bytes(hasher.finalize().into()))
+}
+
+// NOTE returns the address left-padded to 32 bytes, i.e. its ABI encoding
+fn parse_address(address: &str) -> Result<U256, String> {
+    let stripped = address.trim_start_matches("0x");
+    // NOTE this check avoids explicit panics by `from_be_hex`
+    if stripped.len() != 40 || !stripped.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("invalid verifying contract address".to_string());
+    }
+    Ok(U256::from_be_hex(&format!("{:0>64}", stripped)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arithmetic::{Modular, Scalar};
+    use crate::claims::ProofClaims;
+    use crate::curve::Secp256k1;
+    use crate::message::MessageScheme;
+
+    fn mail_domain() -> Eip712Domain {
+        Eip712Domain {
+            name: "Ether Mail".to_string(),
+            version: "1".to_string(),
+            chain_id: 1,
+            verifying_contract: "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC".to_string(),
+        }
+    }
+
+    #[test]
+    fn domain_separator() {
+        // example from the EIP-712 specification
+        let separator = mail_domain().separator().unwrap();
+        assert_eq!(
+            U256::from_be_bytes(separator),
+            U256::from_be_hex("f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f")
+        );
+
+        let mut domain = mail_domain();
+        domain.verifying_contract = "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccZ".to_string();
+        assert_eq!(
+            domain.separator(),
+            Err("invalid verifying contract address".to_string())
+        );
+        domain.verifying_contract = "0xCcCCcccc".to_string();
+        assert!(domain.separator().is_err());
+    }
+
+    #[test]
+    fn mail_struct_hash() {
+        // example from the EIP-712 specification
+        let person = |name: &str, wallet: &str| {
+            let mut encoder = StructEncoder::new("Person(string name,address wallet)");
+            encoder.string(name);
+            encoder.address(wallet).unwrap();
+            encoder.finalize()
+        };
+        let mut encoder = StructEncoder::new(
+            "Mail(Person from,Person to,string contents)Person(string name,address wallet)",
+        );
+        encoder.struct_hash(&person("Cow", "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"));
+        encoder.struct_hash(&person("Bob", "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"));
+        encoder.string("Hello, Bob!");
+        let struct_hash = encoder.finalize();
+        assert_eq!(
+            U256::from_be_bytes(struct_hash),
+            U256::from_be_hex("c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e")
+        );
+        assert_eq!(
+            typed_data_hash(&mail_domain(), &struct_hash).unwrap(),
+            U256::from_be_hex("be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2")
+        );
+    }
+
+    #[test]
+    fn join_message_fields() {
+        let mut claims = ProofClaims::new("almafa".to_string());
+        claims.platform = Some("discord".to_string());
+        claims.platform_user_id = Some("1234".to_string());
+        claims.role = Some("holder".to_string());
+        claims
+            .context
+            .insert("token".to_string(), "0xdead".to_string());
+        claims.context.insert("chain".to_string(), "1".to_string());
+        let mut challenge = JoinChallenge {
+            claims,
+            nonce: "a1b2c3".to_string(),
+            expires_at: 1_700_000_000,
+            domain: Some(mail_domain()),
+        };
+        assert!(challenge.validate().is_ok());
+
+        // computed with an independent implementation of the encoding
+        let struct_hash = join_struct_hash(&challenge);
+        assert_eq!(
+            U256::from_be_bytes(struct_hash),
+            U256::from_be_hex("0d8d45b7b7b7013ad38759f5f02f88a2e8e97f77a1a32266176e02fa9321c6b0")
+        );
+        let digest = typed_data_hash(&mail_domain(), &struct_hash).unwrap();
+        assert_eq!(
+            digest,
+            U256::from_be_hex("893cd493626ce4b6e27e65e6ae83d5b740f05cd562886c6de4649e1a531a58f0")
+        );
+        assert_eq!(
+            challenge.msg_hash::<Secp256k1>(MessageScheme::Eip191),
+            Ok(Scalar::<Secp256k1>::new(digest))
+        );
+        // the scheme would be silently ignored for typed messages
+        assert_eq!(
+            challenge.msg_hash::<Secp256k1>(MessageScheme::Bitcoin),
+            Err("message scheme is not supported for typed messages".to_string())
+        );
+
+        // claims cannot be moved between fields
+        challenge.claims.platform = Some("1234".to_string());
+        challenge.claims.platform_user_id = Some("discord".to_string());
+        assert_ne!(join_struct_hash(&challenge), struct_hash);
+        challenge.claims.platform = Some("discord".to_string());
+        challenge.claims.platform_user_id = Some("1234".to_string());
+        challenge.claims.role = None;
+        assert_ne!(join_struct_hash(&challenge), struct_hash);
+        challenge.claims.role = Some("holder".to_string());
+        challenge.expires_at += 1;
+        assert_ne!(join_struct_hash(&challenge), struct_hash);
+        challenge.expires_at -= 1;
+        assert_eq!(join_struct_hash(&challenge), struct_hash);
+
+        // an empty claim would be encoded like an absent one
+        challenge.claims.role = Some(String::new());
+        assert_eq!(
+            challenge.validate(),
+            Err("empty optional claim in typed message".to_string())
+        );
+
+        let mut domain = mail_domain();
+        domain.chain_id = 5;
+        assert_ne!(typed_data_hash(&domain, &struct_hash).unwrap(), digest);
+    }
+}
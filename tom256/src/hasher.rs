@@ -5,6 +5,7 @@ use crate::U256;
 use bigint::Encoding;
 use sha3::{Digest, Keccak256};
 
+#[derive(Clone)]
 pub struct PointHasher {
     hasher: Keccak256,
 }
@@ -17,6 +18,10 @@ impl PointHasher {
         Self { hasher }
     }
 
+    pub fn insert_bytes(&mut self, bytes: &[u8]) {
+        self.hasher.update(bytes);
+    }
+
     pub fn insert_point<C: Curve>(&mut self, pt: &Point<C>) {
         self.hasher.update(pt.x().inner().to_be_bytes());
         self.hasher.update(pt.y().inner().to_be_bytes());
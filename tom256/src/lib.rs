@@ -1,17 +1,22 @@
 #![feature(int_log)]
 pub mod arithmetic;
+pub mod challenge;
+pub mod claims;
 pub mod curve;
+pub mod eip712;
 mod hasher;
+pub mod message;
 pub mod parse;
 pub mod pedersen;
 pub mod proofs;
+pub mod ring;
 mod rng;
 
 pub use bigint::U256;
 use borsh::BorshSerialize;
+use challenge::JoinChallenge;
 use curve::{Secp256k1, Tom256k1};
 use parse::*;
-use pedersen::PedersenCycle;
 use proofs::ZkAttestProof;
 use wasm_bindgen::prelude::*;
 
@@ -19,16 +24,18 @@ use crate::arithmetic::Point;
 #[derive(serde::Serialize)]
 #[serde(rename_all = "camelCase")]
 struct ProofOutput {
-    guild_id: String,
-    r_point: Point<Secp256k1>,
+    challenge: JoinChallenge,
+    r_point: Option<Point<Secp256k1>>,
+    // NOTE only set for proofs generated with the secret key, signatures
+    // cannot produce a linkability tag
+    nullifier: Option<String>,
+    ring_digest: String,
     proof_binary: Vec<u8>,
 }
 
 #[wasm_bindgen(js_name = "generateProof")]
 pub fn generate_proof(input: JsValue, ring: JsValue) -> Result<JsValue, JsValue> {
     let mut rng = rand_core::OsRng;
-    let pedersen = PedersenCycle::<Secp256k1, Tom256k1>::new(&mut rng);
-
     let input: ParsedProofInput<Secp256k1> = input
         .into_serde::<ProofInput>()
         .map_err(|e| e.to_string())?
@@ -37,15 +44,24 @@ pub fn generate_proof(input: JsValue, ring: JsValue) -> Result<JsValue, JsValue>
     let ring: ParsedRing<Tom256k1> =
         parse_ring(ring.into_serde::<Ring>().map_err(|e| e.to_string())?)?;
 
-    let zk_attest_proof = ZkAttestProof::construct(&mut rng, pedersen, input, &ring)?;
+    let zk_attest_proof = ZkAttestProof::construct(&mut rng, input, &ring)?;
 
     let proof_binary = zk_attest_proof
         .try_to_vec()
         .map_err(|e| JsValue::from(e.to_string()))?;
 
+    let r_point = zk_attest_proof.r_point().cloned();
+    let nullifier = zk_attest_proof.nullifier().map(|tag| tag.to_string());
+    // NOTE the proof is generated on a single ring
+    let ring_digest = zk_attest_proof.ring_digests[0]
+        .iter()
+        .fold("0x".to_string(), |hex, byte| hex + &format!("{:02x}", byte));
+
     let proof_output = ProofOutput {
-        guild_id: zk_attest_proof.guild_id,
-        r_point: zk_attest_proof.r_point,
+        challenge: zk_attest_proof.challenge,
+        r_point,
+        nullifier,
+        ring_digest,
         proof_binary,
     };
 
@@ -65,6 +81,6 @@ pub fn verify_proof(proof: Vec<u8>, ring: JsValue) -> Result<JsValue, JsValue> {
     let ring: ParsedRing<Tom256k1> =
         parse_ring(ring.into_serde::<Ring>().map_err(|e| e.to_string())?)?;
 
-    proof.verify(&mut rng, &ring)?;
-    Ok(JsValue::from(true))
+    let claims = proof.verify(&mut rng, &ring)?;
+    JsValue::from_serde(claims).map_err(|e| JsValue::from(e.to_string()))
 }
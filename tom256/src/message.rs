@@ -0,0 +1,209 @@
+use crate::arithmetic::{Modular, Scalar};
+use crate::curve::Curve;
+use crate::U256;
+
+use bigint::Encoding;
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+
+const EIP191_PREFIX: &str = "\x19Ethereum Signed Message:\n";
+const BITCOIN_PREFIX: &str = "Bitcoin Signed Message:\n";
+
+/// Describes how a wallet turns a message into the digest it signs.
+pub trait SignedMessageScheme {
+    fn digest(&self, msg: &[u8]) -> Result<U256, String>;
+}
+
+/// Ethereum `personal_sign` (EIP-191 version 0x45) with Keccak-256.
+pub struct Eip191;
+
+/// Bitcoin signed message (BIP-137) with double SHA-256.
+pub struct BitcoinSignedMessage;
+
+/// Pre-hashed 32-byte digest signed as is, used by tooling that signs raw
+/// hashes.
+pub struct RawDigest;
+
+impl SignedMessageScheme for Eip191 {
+    fn digest(&self, msg: &[u8]) -> Result<U256, String> {
+        let mut hasher = Keccak256::new();
+        hasher.update(EIP191_PREFIX.as_bytes());
+        hasher.update(msg.len().to_string().as_bytes());
+        hasher.update(msg);
+        Ok(U256::from_be_bytes(hasher.finalize().into()))
+    }
+}
+
+impl SignedMessageScheme for BitcoinSignedMessage {
+    fn digest(&self, msg: &[u8]) -> Result<U256, String> {
+        let mut hasher = Sha256::new();
+        hasher.update(compact_size(BITCOIN_PREFIX.len()));
+        hasher.update(BITCOIN_PREFIX.as_bytes());
+        hasher.update(compact_size(msg.len()));
+        hasher.update(msg);
+        let first = hasher.finalize();
+        Ok(U256::from_be_bytes(Sha256::digest(first).into()))
+    }
+}
+
+impl SignedMessageScheme for RawDigest {
+    fn digest(&self, msg: &[u8]) -> Result<U256, String> {
+        if msg.len() != 32 {
+            return Err("raw digest must be 32 bytes".to_string());
+        }
+        Ok(U256::from_be_slice(msg))
+    }
+}
+
+/// Signed message scheme selected by the prover and recorded in the proof.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum MessageScheme {
+    #[default]
+    Eip191,
+    Bitcoin,
+    Raw,
+}
+
+impl MessageScheme {
+    /// Returns the signed digest of the message as a scalar.
+    ///
+    /// Raw hash signers sign the Keccak-256 digest of the message.
+    pub fn hash<C: Curve>(&self, msg: &str) -> Scalar<C> {
+        let digest = match self {
+            Self::Raw => self.digest(&Keccak256::digest(msg)),
+            _ => self.digest(msg.as_bytes()),
+        };
+        Scalar::new(digest.unwrap_or_else(|_| unreachable!("Keccak-256 digests are 32 bytes")))
+    }
+}
+
+impl SignedMessageScheme for MessageScheme {
+    fn digest(&self, msg: &[u8]) -> Result<U256, String> {
+        match self {
+            Self::Eip191 => Eip191.digest(msg),
+            Self::Bitcoin => BitcoinSignedMessage.digest(msg),
+            Self::Raw => RawDigest.digest(msg),
+        }
+    }
+}
+
+// Bitcoin's variable length integer encoding
+fn compact_size(len: usize) -> Vec<u8> {
+    if len < 0xfd {
+        vec![len as u8]
+    } else if len <= 0xffff {
+        let mut bytes = vec![0xfd];
+        bytes.extend_from_slice(&(len as u16).to_le_bytes());
+        bytes
+    } else if len <= 0xffff_ffff {
+        let mut bytes = vec![0xfe];
+        bytes.extend_from_slice(&(len as u32).to_le_bytes());
+        bytes
+    } else {
+        let mut bytes = vec![0xff];
+        bytes.extend_from_slice(&(len as u64).to_le_bytes());
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curve::Secp256k1;
+    use crate::parse::{parse_pubkey, Signature};
+
+    #[test]
+    fn eip191_digest() {
+        assert_eq!(
+            Eip191.digest(b"hello").unwrap(),
+            U256::from_be_hex("50b2c43fd39106bafbba0da34fc430e1f91e3c96ea2acee2bc34119f92b37750")
+        );
+    }
+
+    #[test]
+    fn bitcoin_digest() {
+        assert_eq!(
+            BitcoinSignedMessage.digest(b"hello").unwrap(),
+            U256::from_be_hex("cf0447ec85f0ce7150a257db32ebfcb7523dae17c36dbd1be598779fec0484f4")
+        );
+        // long messages use a multi-byte length prefix
+        assert_eq!(
+            BitcoinSignedMessage.digest(&[b'a'; 300]).unwrap(),
+            U256::from_be_hex("3ec158a43b80359df647352dac1d37dbf26a94e5f06e5790760290c75cd11dc0")
+        );
+    }
+
+    #[test]
+    fn raw_digest() {
+        // signature test vector of go-ethereum's crypto package
+        let hash =
+            U256::from_be_hex("ce0677bb30baa8cf067c88db9811f4333d131bf8bcf12fe7065d211dce971008")
+                .to_be_bytes();
+        let digest = RawDigest.digest(&hash).unwrap();
+        assert_eq!(digest, U256::from_be_slice(&hash));
+
+        let signature = Signature {
+            r: Scalar::new(U256::from_be_hex(
+                "90f27b8b488db00b00606796d2987f6a5f59ae62ea05effe84fef5b8b0e54998",
+            )),
+            s: Scalar::new(U256::from_be_hex(
+                "4a691139ad57a3f0b906637673aa2f63d1f55cb1a69199d4009eea23ceaddc93",
+            )),
+            recovery_id: 1,
+        };
+        let pubkey = signature
+            .recover_pubkey(&Scalar::<Secp256k1>::new(digest))
+            .unwrap();
+        assert_eq!(
+            pubkey,
+            parse_pubkey(
+                "04e32df42865e97135acfb65f3bae71bdc86f4d49150ad6a440b6f15878109880a\
+                0a2b2667f7e725ceea70c673093bf67663e0312623c8e091b13cf2c0f11ef652"
+            )
+            .unwrap()
+        );
+
+        assert_eq!(
+            RawDigest.digest(&hash[1..]),
+            Err("raw digest must be 32 bytes".to_string())
+        );
+        assert!(RawDigest.digest(b"").is_err());
+    }
+
+    #[test]
+    fn scheme_dispatch() {
+        let msg = "#zkp/join.guild.xyz/almafa";
+        assert_eq!(
+            MessageScheme::default().digest(msg.as_bytes()),
+            Eip191.digest(msg.as_bytes())
+        );
+        assert_eq!(
+            MessageScheme::Bitcoin.digest(msg.as_bytes()),
+            BitcoinSignedMessage.digest(msg.as_bytes())
+        );
+        assert!(MessageScheme::Raw.digest(msg.as_bytes()).is_err());
+        // text messages are pre-hashed with Keccak-256
+        assert_eq!(
+            MessageScheme::Raw.hash::<Secp256k1>(msg),
+            Scalar::new(RawDigest.digest(&Keccak256::digest(msg)).unwrap())
+        );
+        assert_ne!(
+            MessageScheme::Eip191.hash::<Secp256k1>(msg),
+            MessageScheme::Raw.hash::<Secp256k1>(msg)
+        );
+    }
+}
@@ -0,0 +1,308 @@
+use super::{ParsedRing, Ring};
+use crate::arithmetic::{AffinePoint, FieldElement, Modular};
+use crate::curve::{Curve, Cycle, Secp256k1};
+use crate::U256;
+
+use bigint::Encoding;
+use sha3::{Digest, Keccak256};
+
+use std::fmt;
+
+/// Ethereum address, i.e. the last 20 bytes of the Keccak-256 hash of the
+/// uncompressed public key coordinates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Address(pub [u8; 20]);
+
+impl Address {
+    pub fn from_pubkey(pubkey: &AffinePoint<Secp256k1>) -> Self {
+        let mut hasher = Keccak256::new();
+        hasher.update(pubkey.x().inner().to_be_bytes());
+        hasher.update(pubkey.y().inner().to_be_bytes());
+        let hash = hasher.finalize();
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&hash[12..]);
+        Self(address)
+    }
+
+    /// Parses a hex encoded address.
+    ///
+    /// Mixed-case input is treated as an EIP-55 checksummed address and is
+    /// rejected if the checksum does not match.
+    pub fn parse(address: &str) -> Result<Self, String> {
+        let bytes = decode_hex(address)?;
+        let address_bytes: [u8; 20] = bytes
+            .try_into()
+            .map_err(|_| "invalid address length".to_string())?;
+        let parsed = Self(address_bytes);
+
+        let digits = address.trim_start_matches("0x");
+        let has_upper = digits.chars().any(|c| c.is_ascii_uppercase());
+        let has_lower = digits.chars().any(|c| c.is_ascii_lowercase());
+        if has_upper && has_lower && parsed.to_checksum()[2..] != *digits {
+            return Err("invalid address checksum".to_string());
+        }
+        Ok(parsed)
+    }
+
+    /// Returns the EIP-55 checksummed encoding of the address.
+    pub fn to_checksum(&self) -> String {
+        let lower = self
+            .0
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>();
+        let hash = Keccak256::digest(lower.as_bytes());
+        let checksummed = lower
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                let nibble = (hash[i / 2] >> (4 * (1 - i % 2))) & 0x0f;
+                if nibble >= 8 {
+                    c.to_ascii_uppercase()
+                } else {
+                    c
+                }
+            })
+            .collect::<String>();
+        format!("0x{}", checksummed)
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_checksum())
+    }
+}
+
+/// Decodes a hex string with an optional `0x` prefix.
+pub fn decode_hex(hex: &str) -> Result<Vec<u8>, String> {
+    let offset = if hex.starts_with("0x") { 2 } else { 0 };
+    let digits = &hex.as_bytes()[offset..];
+    if digits.len() & 1 != 0 {
+        return Err("odd number of hex digits".to_string());
+    }
+    let nibble = |i: usize| -> Result<u8, String> {
+        (digits[i] as char)
+            .to_digit(16)
+            .map(|digit| digit as u8)
+            .ok_or_else(|| format!("invalid hex character at position {}", offset + i))
+    };
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| Ok((nibble(i)? << 4) | nibble(i + 1)?))
+        .collect()
+}
+
+/// Parses a hex encoded secp256k1 public key.
+///
+/// See [`pubkey_from_bytes`] for the accepted formats.
+pub fn parse_pubkey(pubkey: &str) -> Result<AffinePoint<Secp256k1>, String> {
+    pubkey_from_bytes(&decode_hex(pubkey)?)
+}
+
+/// Parses a secp256k1 public key from SEC1 compressed (33 bytes) or
+/// uncompressed (65 bytes) encoding, or from the raw 64 bytes of its
+/// coordinates.
+pub fn pubkey_from_bytes(bytes: &[u8]) -> Result<AffinePoint<Secp256k1>, String> {
+    let pubkey = match (bytes.len(), bytes.first()) {
+        (33, Some(prefix @ (0x02 | 0x03))) => {
+            AffinePoint::decompress(coordinate(&bytes[1..])?, *prefix == 0x03)
+                .map_err(|_| "pubkey is not on the curve".to_string())?
+        }
+        (65, Some(0x04)) => AffinePoint::new(coordinate(&bytes[1..33])?, coordinate(&bytes[33..])?),
+        (64, _) => AffinePoint::new(coordinate(&bytes[..32])?, coordinate(&bytes[32..])?),
+        _ => return Err("invalid pubkey encoding".to_string()),
+    };
+    if !pubkey.is_on_curve() {
+        return Err("pubkey is not on the curve".to_string());
+    }
+    Ok(pubkey)
+}
+
+/// Returns the uncompressed SEC1 encoding of a public key, i.e. the format
+/// of the generated rings.
+pub fn encode_pubkey(pubkey: &AffinePoint<Secp256k1>) -> String {
+    let bytes = [
+        &[0x04][..],
+        &pubkey.x().inner().to_be_bytes(),
+        &pubkey.y().inner().to_be_bytes(),
+    ]
+    .concat();
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Parses the x coordinate of a ring member.
+///
+/// Besides the formats of [`parse_pubkey`] a ring entry may consist of the 32
+/// bytes of the x coordinate only, which is checked to belong to a point on
+/// the curve.
+pub fn parse_ring_entry(entry: &str) -> Result<FieldElement<Secp256k1>, String> {
+    let bytes = decode_hex(entry)?;
+    if bytes.len() == 32 {
+        let x = coordinate(&bytes)?;
+        AffinePoint::decompress(x, false).map_err(|_| "pubkey is not on the curve".to_string())?;
+        Ok(x)
+    } else {
+        Ok(*pubkey_from_bytes(&bytes)?.x())
+    }
+}
+
+/// Parses the x coordinates of the ring members as scalars of the cycle
+/// curve.
+///
+/// Errors point to the offending entry and, for malformed hex, to the
+/// character position within the entry.
+pub fn parse_ring<C: Cycle<Secp256k1>>(ring: Ring) -> Result<ParsedRing<C>, String> {
+    ring.iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            parse_ring_entry(entry)
+                .map(|x| x.to_cycle_scalar())
+                .map_err(|e| format!("invalid ring entry {}: {}", i, e))
+        })
+        .collect()
+}
+
+/// Returns the index of the public key in the ring.
+pub fn locate_signer<C: Curve, CC: Cycle<C>>(
+    ring: &ParsedRing<CC>,
+    pubkey: &AffinePoint<C>,
+) -> Result<usize, String> {
+    let x = pubkey.x().to_cycle_scalar::<CC>();
+    ring.iter()
+        .position(|entry| entry == &x)
+        .ok_or_else(|| "signer is not in the ring".to_string())
+}
+
+fn coordinate(bytes: &[u8]) -> Result<FieldElement<Secp256k1>, String> {
+    let number = U256::from_be_slice(bytes);
+    if number >= Secp256k1::PRIME_MODULUS {
+        return Err("coordinate exceeds the field modulus".to_string());
+    }
+    Ok(FieldElement::new(number))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arithmetic::Scalar;
+    use crate::curve::Tom256k1;
+
+    // account #0 of ganache's deterministic wallet
+    const UNCOMPRESSED: &str = "0x04e68acfc0253a10620dff706b0a1b1f1f5833ea3beb3bde2250d5f271f3563606672ebc45e0b7ea2e816ecb70ca03137b1c9476eec63d4632e990020b7b6fba39";
+    const COMPRESSED: &str = "03e68acfc0253a10620dff706b0a1b1f1f5833ea3beb3bde2250d5f271f3563606";
+
+    #[test]
+    fn hex_decoding() {
+        assert_eq!(decode_hex("0x00ff10"), Ok(vec![0x00, 0xff, 0x10]));
+        assert_eq!(decode_hex("A0b1"), Ok(vec![0xa0, 0xb1]));
+        assert_eq!(decode_hex(""), Ok(vec![]));
+        assert_eq!(
+            decode_hex("0x00f"),
+            Err("odd number of hex digits".to_string())
+        );
+        assert_eq!(
+            decode_hex("0x00fg"),
+            Err("invalid hex character at position 5".to_string())
+        );
+    }
+
+    #[test]
+    fn pubkey_formats() {
+        let pubkey = parse_pubkey(UNCOMPRESSED).unwrap();
+        assert_eq!(encode_pubkey(&pubkey), UNCOMPRESSED[2..]);
+        assert_eq!(parse_pubkey(COMPRESSED), Ok(pubkey.clone()));
+        assert_eq!(parse_pubkey(&UNCOMPRESSED[4..]), Ok(pubkey.clone()));
+
+        // even y
+        let compressed_g = "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+        assert_eq!(parse_pubkey(compressed_g), Ok(AffinePoint::GENERATOR));
+
+        assert_eq!(
+            parse_pubkey(&UNCOMPRESSED.replace("0x04", "0x05")),
+            Err("invalid pubkey encoding".to_string())
+        );
+        assert_eq!(
+            parse_pubkey(&UNCOMPRESSED.replace("ba39", "ba3a")),
+            Err("pubkey is not on the curve".to_string())
+        );
+        let not_on_curve = format!("02{:064x}", 5);
+        assert_eq!(
+            parse_pubkey(&not_on_curve),
+            Err("pubkey is not on the curve".to_string())
+        );
+        assert_eq!(
+            parse_pubkey(&format!("02{}", "f".repeat(64))),
+            Err("coordinate exceeds the field modulus".to_string())
+        );
+    }
+
+    #[test]
+    fn addresses() {
+        let pubkey = parse_pubkey(UNCOMPRESSED).unwrap();
+        let address = Address::from_pubkey(&pubkey);
+        assert_eq!(
+            address.to_string(),
+            "0x90F8bf6A479f320ead074411a4B0e7944Ea8c9C1"
+        );
+        assert_eq!(
+            Address::parse("0x90f8bf6a479f320ead074411a4b0e7944ea8c9c1"),
+            Ok(address)
+        );
+
+        // examples from EIP-55
+        for checksummed in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            let address = Address::parse(checksummed).unwrap();
+            assert_eq!(address.to_checksum(), checksummed);
+        }
+        assert_eq!(
+            Address::parse("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD"),
+            Err("invalid address checksum".to_string())
+        );
+        assert_eq!(
+            Address::parse("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeA"),
+            Err("invalid address length".to_string())
+        );
+    }
+
+    #[test]
+    fn ring_entries() {
+        // NOTE this x coordinate starts with a 04 byte
+        let x = "049370a4b5f43412ea25f514e8ecdad05266115e4a7ecb1387231808f8b45963";
+        let y = "758f3f41afd6ed428b3081b0512fd62a54c3f3afbb5b6764b653052a12949c9a";
+        let expected = FieldElement::<Secp256k1>::new(U256::from_be_hex(x));
+        assert_eq!(parse_ring_entry(x), Ok(expected));
+        assert_eq!(parse_ring_entry(&format!("04{}{}", x, y)), Ok(expected));
+        assert_eq!(parse_ring_entry(&format!("0x{}{}", x, y)), Ok(expected));
+        assert_eq!(parse_ring_entry(&format!("02{}", x)), Ok(expected));
+
+        let ring = vec![
+            x.to_string(),
+            UNCOMPRESSED.to_string(),
+            format!("{:064x}", 5),
+            "0x79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f8179z".to_string(),
+        ];
+        assert_eq!(
+            parse_ring::<Tom256k1>(ring.clone()),
+            Err("invalid ring entry 2: pubkey is not on the curve".to_string())
+        );
+        assert_eq!(
+            parse_ring::<Tom256k1>(ring[3..].to_vec()),
+            Err("invalid ring entry 0: invalid hex character at position 65".to_string())
+        );
+
+        let parsed_ring = parse_ring::<Tom256k1>(ring[..2].to_vec()).unwrap();
+        assert_eq!(parsed_ring[0], Scalar::new(U256::from_be_hex(x)));
+        let pubkey = parse_pubkey(UNCOMPRESSED).unwrap();
+        assert_eq!(locate_signer(&parsed_ring, &pubkey), Ok(1));
+        assert_eq!(
+            locate_signer(&parsed_ring[..1].to_vec(), &pubkey),
+            Err("signer is not in the ring".to_string())
+        );
+    }
+}
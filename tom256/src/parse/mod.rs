@@ -0,0 +1,541 @@
+mod keys;
+mod rlp;
+mod siwe;
+mod transaction;
+
+pub use keys::{
+    decode_hex, encode_pubkey, locate_signer, parse_pubkey, parse_ring, parse_ring_entry,
+    pubkey_from_bytes, Address,
+};
+pub use rlp::Rlp;
+pub use siwe::{
+    claims_resource, SiweMessage, SiwePolicy, ANONYMOUS_ADDRESS, CLAIMS_RESOURCE_PREFIX,
+};
+pub use transaction::SignedTransaction;
+
+use crate::arithmetic::{AffinePoint, Modular, Point, Scalar};
+use crate::challenge::JoinChallenge;
+use crate::curve::{Curve, Secp256k1};
+use crate::message::MessageScheme;
+use crate::U256;
+
+use serde::{Deserialize, Serialize};
+
+pub type Ring = Vec<String>;
+pub type ParsedRing<C> = Vec<Scalar<C>>;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofInput {
+    pub msg_hash: String,
+    /// Uncompressed public key of the signer, recovered from the signature
+    /// or derived from the secret key if omitted.
+    #[serde(default)]
+    pub pubkey: Option<String>,
+    /// Signature over the message hash, required unless a secret key is
+    /// given.
+    #[serde(default)]
+    pub signature: Option<String>,
+    /// Secret key of the ring member, proven to be known instead of a
+    /// signature.
+    #[serde(default)]
+    pub secret_key: Option<String>,
+    /// Position of the signer in the ring, located automatically if omitted.
+    #[serde(default)]
+    pub index: Option<usize>,
+    pub challenge: JoinChallenge,
+    /// Attach a guild-scoped linkability tag to the proof, requires the
+    /// secret key. Wallets that only sign messages cannot produce a tag, so
+    /// they cannot join guilds whose verifier requires one.
+    #[serde(default)]
+    pub nullifier: bool,
+    /// Scheme the wallet used to hash the signed messages, defaults to
+    /// EIP-191.
+    #[serde(default)]
+    pub message_scheme: MessageScheme,
+    /// Anonymous SIWE (EIP-4361) message signed instead of the join message.
+    #[serde(default)]
+    pub siwe_message: Option<String>,
+    /// Reject signatures with a high `s` value instead of normalizing them.
+    #[serde(default)]
+    pub strict_low_s: bool,
+    /// Commit to the `R` point of the signature instead of revealing it.
+    #[serde(default)]
+    pub hide_r_point: bool,
+}
+
+/// Secret the prover has on the ring member's public key.
+pub enum Witness<C: Curve> {
+    /// Signature over the message hash.
+    Signature(Signature<C>),
+    /// The secret key itself.
+    SecretKey(Scalar<C>),
+}
+
+pub struct ParsedProofInput<C: Curve> {
+    pub msg_hash: Scalar<C>,
+    pub pubkey: AffinePoint<C>,
+    pub witness: Witness<C>,
+    pub index: Option<usize>,
+    pub challenge: JoinChallenge,
+    pub nullifier: bool,
+    pub message_scheme: MessageScheme,
+    pub siwe: Option<SiweMessage>,
+    pub hide_r_point: bool,
+}
+
+impl TryFrom<ProofInput> for ParsedProofInput<Secp256k1> {
+    type Error = String;
+    fn try_from(rhs: ProofInput) -> Result<Self, Self::Error> {
+        let hash = decode_hex(&rhs.msg_hash)?;
+        if hash.len() != 32 {
+            return Err("invalid hash length".to_string());
+        }
+        let msg_hash = Scalar::new(U256::from_be_slice(&hash));
+        let siwe = match rhs.siwe_message.as_deref() {
+            Some(message) => {
+                let siwe = SiweMessage::parse(message)?;
+                if siwe.to_string() != message {
+                    return Err("non-canonical SIWE message".to_string());
+                }
+                if rhs.message_scheme.hash::<Secp256k1>(message) != msg_hash {
+                    return Err("SIWE message hash mismatch".to_string());
+                }
+                Some(siwe)
+            }
+            None => None,
+        };
+        let (witness, derived) = match (rhs.signature.as_deref(), rhs.secret_key.as_deref()) {
+            (Some(signature), None) => {
+                let signature = parse_canonical_signature(signature, rhs.strict_low_s)?;
+                let recovered = signature.recover_pubkey(&msg_hash)?;
+                (Witness::Signature(signature), recovered)
+            }
+            (None, Some(secret_key)) => {
+                let secret_key = parse_secret_key(secret_key)?;
+                let pubkey = (&Point::<Secp256k1>::GENERATOR * secret_key).to_affine();
+                (Witness::SecretKey(secret_key), pubkey)
+            }
+            (Some(_), Some(_)) => {
+                return Err("signature and secret key are mutually exclusive".to_string())
+            }
+            (None, None) => {
+                return Err("either a signature or a secret key is required".to_string())
+            }
+        };
+        if rhs.hide_r_point && matches!(witness, Witness::SecretKey(_)) {
+            return Err("R can only be hidden for signatures".to_string());
+        }
+        if rhs.nullifier && matches!(witness, Witness::Signature(_)) {
+            return Err("nullifiers require the secret key".to_string());
+        }
+        if let Some(pubkey) = rhs.pubkey.as_deref() {
+            if parse_pubkey(pubkey)? != derived {
+                return Err(match witness {
+                    Witness::Signature(_) => "pubkey does not match the signature",
+                    Witness::SecretKey(_) => "pubkey does not match the secret key",
+                }
+                .to_string());
+            }
+        }
+        Ok(Self {
+            msg_hash,
+            pubkey: derived,
+            witness,
+            index: rhs.index,
+            challenge: rhs.challenge,
+            nullifier: rhs.nullifier,
+            message_scheme: rhs.message_scheme,
+            siwe,
+            hide_r_point: rhs.hide_r_point,
+        })
+    }
+}
+
+pub struct Signature<C> {
+    pub r: Scalar<C>,
+    pub s: Scalar<C>,
+    pub recovery_id: u8,
+}
+
+impl<C: Curve> Signature<C> {
+    /// Returns true if `s` is in the upper half of the scalar range.
+    pub fn is_high_s(&self) -> bool {
+        self.s.inner() > &(C::ORDER >> 1)
+    }
+
+    /// Replaces a high `s` by `n - s` and returns whether the signature
+    /// changed.
+    ///
+    /// Both values verify, the normalized one belongs to the negated `R`
+    /// point, thus the parity bit of the recovery id is flipped as well.
+    pub fn normalize_s(&mut self) -> bool {
+        if self.is_high_s() {
+            self.s = -self.s;
+            self.recovery_id ^= 1;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl Signature<Secp256k1> {
+    /// Recovers the public key of the signer, see [`Point::recover_pubkey`].
+    pub fn recover_pubkey(
+        &self,
+        msg_hash: &Scalar<Secp256k1>,
+    ) -> Result<AffinePoint<Secp256k1>, String> {
+        Point::recover_pubkey(msg_hash, &self.r, &self.s, self.recovery_id)
+    }
+}
+
+fn parse_secret_key<C: Curve>(secret_key: &str) -> Result<Scalar<C>, String> {
+    let bytes = decode_hex(secret_key)?;
+    if bytes.len() != 32 {
+        return Err("invalid secret key length".to_string());
+    }
+    let number = U256::from_be_slice(&bytes);
+    if number == U256::ZERO || number >= C::ORDER {
+        return Err("secret key out of range".to_string());
+    }
+    Ok(Scalar::new(number))
+}
+
+fn parse_canonical_signature<C: Curve>(
+    signature: &str,
+    strict_low_s: bool,
+) -> Result<Signature<C>, String> {
+    let mut signature = parse_signature(signature)?;
+    if strict_low_s && signature.is_high_s() {
+        return Err("signature has a high s value".to_string());
+    }
+    signature.normalize_s();
+    Ok(signature)
+}
+
+fn parse_signature<C: Curve>(signature: &str) -> Result<Signature<C>, String> {
+    let bytes = decode_hex(signature)?;
+    // NOTE EIP-155 `v` values may be longer than a byte
+    if bytes.len() < 65 || bytes.len() > 72 {
+        return Err("invalid signature length".to_string());
+    }
+    // NOTE the remaining bytes represent the recovery `v` parameter
+    let v = bytes[64..]
+        .iter()
+        .fold(0u64, |v, byte| (v << 8) | u64::from(*byte));
+    signature_from_parts(
+        U256::from_be_slice(&bytes[..32]),
+        U256::from_be_slice(&bytes[32..64]),
+        recovery_id(v)?,
+    )
+}
+
+fn signature_from_parts<C: Curve>(
+    r: U256,
+    s: U256,
+    recovery_id: u8,
+) -> Result<Signature<C>, String> {
+    // NOTE zero or overflowing values would silently wrap in `Scalar::new`
+    if r == U256::ZERO || r >= C::ORDER {
+        return Err("signature r out of range".to_string());
+    }
+    if s == U256::ZERO || s >= C::ORDER {
+        return Err("signature s out of range".to_string());
+    }
+    Ok(Signature {
+        r: Scalar::new(r),
+        s: Scalar::new(s),
+        recovery_id,
+    })
+}
+
+/// Converts the `v` value of a signature into a recovery id.
+///
+/// Accepts plain recovery ids (0-3), Ethereum's 27-30 and EIP-155 values of
+/// `chain_id * 2 + 35 + parity`.
+pub fn recovery_id(v: u64) -> Result<u8, String> {
+    match v {
+        0..=3 => Ok(v as u8),
+        27..=30 => Ok((v - 27) as u8),
+        35.. => Ok(((v - 35) % 2) as u8),
+        _ => Err("invalid recovery id".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arithmetic::{FieldElement, Modular};
+    use crate::claims::ProofClaims;
+    use crate::curve::{Secp256k1, Tom256k1};
+    use crate::hasher::PointHasher;
+    use crate::U256;
+
+    use bigint::Encoding;
+
+    impl<C: Curve> Signature<C> {
+        /// Signs a message hash with a nonce derived from the secret key and
+        /// the message, similarly to RFC 6979.
+        pub fn sign(secret: &Scalar<C>, msg_hash: &Scalar<C>) -> Self {
+            let hasher = PointHasher::new(
+                &[secret.inner().to_be_bytes(), msg_hash.inner().to_be_bytes()].concat(),
+            );
+            let k = Scalar::<C>::new(hasher.finalize());
+            let r_point = (&crate::arithmetic::Point::<C>::GENERATOR * k).to_affine();
+            let r = Scalar::<C>::new(*r_point.x().inner());
+            let s = k.inverse() * (*msg_hash + r * *secret);
+            let parity = r_point.y().is_odd() as u8;
+            let overflow = (r_point.x().inner() >= &C::ORDER) as u8;
+            Self {
+                r,
+                s,
+                recovery_id: (overflow << 1) | parity,
+            }
+        }
+    }
+
+    #[test]
+    fn pubkey_extraction() {
+        let pubkey = "0x0408c6cd9400645819c8c556a6e83e0a7728f070a813bb9d24d5c24290e21fc5e438396f9333264d3e7c1d3e6ee1bc572b2f00b98db7065e9bf278f2b8dbe02718";
+        let x_coord = parse_ring_entry(pubkey)
+            .unwrap()
+            .to_cycle_scalar::<Tom256k1>();
+
+        assert_eq!(
+            x_coord,
+            Scalar::<Tom256k1>::new(U256::from_be_hex(
+                "08c6cd9400645819c8c556a6e83e0a7728f070a813bb9d24d5c24290e21fc5e4"
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_helpers() {
+        let signature = "0x45c4039b611c0cc207ff7fb7a6899ea0431aac2cf37515d74a71f2df00e2c3e0096fad5e7eda762898fffd4644f8a7a406bf6bde868814ea03058c882fcd23311c";
+
+        let sig = parse_signature(signature).unwrap();
+        assert_eq!(
+            sig.r,
+            Scalar::<Secp256k1>::new(U256::from_be_hex(
+                "45c4039b611c0cc207ff7fb7a6899ea0431aac2cf37515d74a71f2df00e2c3e0"
+            ))
+        );
+        assert_eq!(
+            sig.s,
+            Scalar::<Secp256k1>::new(U256::from_be_hex(
+                "096fad5e7eda762898fffd4644f8a7a406bf6bde868814ea03058c882fcd2331"
+            ))
+        );
+
+        let pubkey = "0x0408c6cd9400645819c8c556a6e83e0a7728f070a813bb9d24d5c24290e21fc5e438396f9333264d3e7c1d3e6ee1bc572b2f00b98db7065e9bf278f2b8dbe02718";
+
+        let pubkey_point = parse_pubkey(pubkey).unwrap();
+        assert_eq!(
+            pubkey_point.x(),
+            &FieldElement::<Secp256k1>::new(U256::from_be_hex(
+                "08c6cd9400645819c8c556a6e83e0a7728f070a813bb9d24d5c24290e21fc5e4"
+            ))
+        );
+        assert_eq!(
+            pubkey_point.y(),
+            &FieldElement::<Secp256k1>::new(U256::from_be_hex(
+                "38396f9333264d3e7c1d3e6ee1bc572b2f00b98db7065e9bf278f2b8dbe02718"
+            ))
+        );
+
+        assert_eq!(pubkey_point.z(), &FieldElement::ONE);
+    }
+
+    #[test]
+    fn parse() {
+        let input = ProofInput {
+            msg_hash: "0x1ab4850e7f0a85a521e87b274e3130efdb45f6a47e74e6dcebf5591c6bc8f16e".to_string(),
+            signature: Some("0x45c4039b611c0cc207ff7fb7a6899ea0431aac2cf37515d74a71f2df00e2c3e0096fad5e7eda762898fffd4644f8a7a406bf6bde868814ea03058c882fcd23311c".to_string()),
+            secret_key: None,
+            pubkey: Some("0x0408c6cd9400645819c8c556a6e83e0a7728f070a813bb9d24d5c24290e21fc5e438396f9333264d3e7c1d3e6ee1bc572b2f00b98db7065e9bf278f2b8dbe02718".to_string()),
+            index: Some(1),
+            challenge: JoinChallenge {
+                claims: ProofClaims::new("Our-guild#2314".to_string()),
+                nonce: "a1b2c3".to_string(),
+                expires_at: 1_700_000_000,
+                domain: None,
+            },
+            nullifier: false,
+            message_scheme: MessageScheme::default(),
+            siwe_message: None,
+            strict_low_s: false,
+            hide_r_point: false,
+        };
+        // G, 2G and 3G in uncompressed, compressed and x-only form
+        let ring = vec![
+            "0x0479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8".to_string(),
+            "0x02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5".to_string(),
+            "f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9".to_string(),
+        ];
+
+        let parsed_input: ParsedProofInput<Secp256k1> = input.try_into().unwrap();
+        let parsed_ring: ParsedRing<Tom256k1> = parse_ring(ring).unwrap();
+
+        assert_eq!(
+            parsed_input.msg_hash,
+            Scalar::new(U256::from_be_hex(
+                "1ab4850e7f0a85a521e87b274e3130efdb45f6a47e74e6dcebf5591c6bc8f16e"
+            ))
+        );
+        assert_eq!(parsed_input.challenge.claims.guild_id, "Our-guild#2314");
+        assert_eq!(
+            parsed_ring[0],
+            Scalar::new(U256::from_be_hex(
+                "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+            ))
+        );
+        assert_eq!(
+            parsed_ring[1],
+            Scalar::new(U256::from_be_hex(
+                "c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5"
+            ))
+        );
+        assert_eq!(
+            parsed_ring[2],
+            Scalar::new(U256::from_be_hex(
+                "f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9"
+            ))
+        );
+    }
+
+    #[test]
+    fn recovery_ids() {
+        assert_eq!(recovery_id(0), Ok(0));
+        assert_eq!(recovery_id(27), Ok(0));
+        assert_eq!(recovery_id(28), Ok(1));
+        assert_eq!(recovery_id(30), Ok(3));
+        // EIP-155 with chain ids 1 and 137
+        assert_eq!(recovery_id(37), Ok(0));
+        assert_eq!(recovery_id(38), Ok(1));
+        assert_eq!(recovery_id(310), Ok(1));
+        assert!(recovery_id(4).is_err());
+        assert!(recovery_id(26).is_err());
+        assert!(recovery_id(34).is_err());
+
+        let rs = "45c4039b611c0cc207ff7fb7a6899ea0431aac2cf37515d74a71f2df00e2c3e0096fad5e7eda762898fffd4644f8a7a406bf6bde868814ea03058c882fcd2331";
+        let sig = parse_signature::<Secp256k1>(&format!("0x{}0136", rs)).unwrap();
+        assert_eq!(sig.recovery_id, 1);
+        assert!(parse_signature::<Secp256k1>(&format!("0x{}1", rs)).is_err());
+        assert!(parse_signature::<Secp256k1>(&format!("0x{}zz", rs)).is_err());
+    }
+
+    #[test]
+    fn pubkey_recovery() {
+        let mut input = ProofInput {
+            msg_hash: "0x1ab4850e7f0a85a521e87b274e3130efdb45f6a47e74e6dcebf5591c6bc8f16e".to_string(),
+            signature: Some("0x45c4039b611c0cc207ff7fb7a6899ea0431aac2cf37515d74a71f2df00e2c3e0096fad5e7eda762898fffd4644f8a7a406bf6bde868814ea03058c882fcd23311c".to_string()),
+            secret_key: None,
+            pubkey: None,
+            index: None,
+            challenge: JoinChallenge {
+                claims: ProofClaims::new("almafa".to_string()),
+                nonce: "a1b2c3".to_string(),
+                expires_at: 1_700_000_000,
+                domain: None,
+            },
+            nullifier: false,
+            message_scheme: MessageScheme::default(),
+            siwe_message: None,
+            strict_low_s: false,
+            hide_r_point: false,
+        };
+        let pubkey = "0x0408c6cd9400645819c8c556a6e83e0a7728f070a813bb9d24d5c24290e21fc5e438396f9333264d3e7c1d3e6ee1bc572b2f00b98db7065e9bf278f2b8dbe02718";
+
+        let parsed_input: ParsedProofInput<Secp256k1> = input.clone().try_into().unwrap();
+        assert_eq!(parsed_input.pubkey, parse_pubkey(pubkey).unwrap());
+
+        input.pubkey = Some(pubkey.to_string());
+        assert!(ParsedProofInput::<Secp256k1>::try_from(input.clone()).is_ok());
+
+        // wrong recovery id
+        input.signature = input
+            .signature
+            .map(|signature| signature.replace("311c", "311b"));
+        assert_eq!(
+            ParsedProofInput::<Secp256k1>::try_from(input.clone()).err(),
+            Some("pubkey does not match the signature".to_string())
+        );
+
+        // point not on the curve
+        input.pubkey = Some(pubkey.replacen("0408", "0409", 1));
+        assert_eq!(
+            ParsedProofInput::<Secp256k1>::try_from(input).err(),
+            Some("pubkey is not on the curve".to_string())
+        );
+    }
+
+    #[test]
+    fn signature_ranges() {
+        let r = "45c4039b611c0cc207ff7fb7a6899ea0431aac2cf37515d74a71f2df00e2c3e0";
+        let s = "096fad5e7eda762898fffd4644f8a7a406bf6bde868814ea03058c882fcd2331";
+        let zero = "0000000000000000000000000000000000000000000000000000000000000000";
+        let order = "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141";
+
+        let parse = |r: &str, s: &str| parse_signature::<Secp256k1>(&format!("0x{}{}1c", r, s));
+        assert!(parse(r, s).is_ok());
+        assert_eq!(
+            parse(zero, s).err(),
+            Some("signature r out of range".to_string())
+        );
+        assert_eq!(
+            parse(order, s).err(),
+            Some("signature r out of range".to_string())
+        );
+        assert_eq!(
+            parse(r, zero).err(),
+            Some("signature s out of range".to_string())
+        );
+        assert_eq!(
+            parse(r, &"f".repeat(64)).err(),
+            Some("signature s out of range".to_string())
+        );
+    }
+
+    #[test]
+    fn low_s_normalization() {
+        let high_s = "0x45c4039b611c0cc207ff7fb7a6899ea0431aac2cf37515d74a71f2df00e2c3e0f69052a1812589d7670002b9bb07585ab3ef710828c08b51bcccd204a0691e101b";
+        let mut input = ProofInput {
+            msg_hash: "0x1ab4850e7f0a85a521e87b274e3130efdb45f6a47e74e6dcebf5591c6bc8f16e".to_string(),
+            signature: Some(high_s.to_string()),
+            secret_key: None,
+            pubkey: Some("0x0408c6cd9400645819c8c556a6e83e0a7728f070a813bb9d24d5c24290e21fc5e438396f9333264d3e7c1d3e6ee1bc572b2f00b98db7065e9bf278f2b8dbe02718".to_string()),
+            index: None,
+            challenge: JoinChallenge {
+                claims: ProofClaims::new("almafa".to_string()),
+                nonce: "a1b2c3".to_string(),
+                expires_at: 1_700_000_000,
+                domain: None,
+            },
+            nullifier: false,
+            message_scheme: MessageScheme::default(),
+            siwe_message: None,
+            strict_low_s: false,
+            hide_r_point: false,
+        };
+
+        let signature = parse_signature::<Secp256k1>(high_s).unwrap();
+        assert!(signature.is_high_s());
+
+        // (r, n - s) is normalized into the wallet's low-s signature
+        let parsed_input: ParsedProofInput<Secp256k1> = input.clone().try_into().unwrap();
+        let parsed_signature = match parsed_input.witness {
+            Witness::Signature(signature) => signature,
+            Witness::SecretKey(_) => panic!("expected a signature"),
+        };
+        assert!(!parsed_signature.is_high_s());
+        assert_eq!(parsed_signature.s, -signature.s);
+        assert_eq!(parsed_signature.recovery_id, 1);
+
+        input.strict_low_s = true;
+        assert_eq!(
+            ParsedProofInput::<Secp256k1>::try_from(input).err(),
+            Some("signature has a high s value".to_string())
+        );
+    }
+}
@@ -0,0 +1,256 @@
+use crate::U256;
+
+// NOTE the storage keys of an access list are the deepest lists in a
+// transaction: transaction > access list > entry > storage keys
+const MAX_LIST_DEPTH: usize = 4;
+
+/// Recursive Length Prefix encoded item.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Rlp {
+    Bytes(Vec<u8>),
+    List(Vec<Rlp>),
+}
+
+impl Rlp {
+    /// Decodes a single item spanning the whole input.
+    ///
+    /// Only the canonical (shortest) encoding is accepted, thus decoding and
+    /// re-encoding an item always results in the same bytes. Lists may be
+    /// nested at most `MAX_LIST_DEPTH` deep.
+    pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+        let (item, rest) = decode_item(bytes, 0)?;
+        if !rest.is_empty() {
+            return Err("trailing bytes after RLP item".to_string());
+        }
+        Ok(item)
+    }
+
+    /// Returns the canonical encoding of an integer, i.e. its big-endian bytes
+    /// without leading zeros.
+    pub fn from_u64(value: u64) -> Self {
+        Self::Bytes(
+            value
+                .to_be_bytes()
+                .into_iter()
+                .skip_while(|byte| *byte == 0)
+                .collect(),
+        )
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            Self::Bytes(bytes) if bytes.len() == 1 && bytes[0] < 0x80 => bytes.clone(),
+            Self::Bytes(bytes) => [encode_header(bytes.len(), 0x80), bytes.clone()].concat(),
+            Self::List(items) => {
+                let payload = items.iter().flat_map(Self::encode).collect::<Vec<u8>>();
+                [encode_header(payload.len(), 0xc0), payload].concat()
+            }
+        }
+    }
+
+    pub fn as_bytes(&self) -> Result<&[u8], String> {
+        match self {
+            Self::Bytes(bytes) => Ok(bytes),
+            Self::List(_) => Err("expected RLP bytes, found a list".to_string()),
+        }
+    }
+
+    pub fn as_list(&self) -> Result<&[Rlp], String> {
+        match self {
+            Self::List(items) => Ok(items),
+            Self::Bytes(_) => Err("expected RLP list, found bytes".to_string()),
+        }
+    }
+
+    pub fn as_u64(&self) -> Result<u64, String> {
+        let bytes = self.as_integer_bytes()?;
+        if bytes.len() > 8 {
+            return Err("RLP integer overflow".to_string());
+        }
+        Ok(bytes
+            .iter()
+            .fold(0, |acc, byte| (acc << 8) | u64::from(*byte)))
+    }
+
+    pub fn as_u256(&self) -> Result<U256, String> {
+        let bytes = self.as_integer_bytes()?;
+        if bytes.len() > 32 {
+            return Err("RLP integer overflow".to_string());
+        }
+        Ok(U256::from_be_slice(
+            &[vec![0u8; 32 - bytes.len()], bytes.to_vec()].concat(),
+        ))
+    }
+
+    fn as_integer_bytes(&self) -> Result<&[u8], String> {
+        let bytes = self.as_bytes()?;
+        if bytes.first() == Some(&0) {
+            return Err("non-canonical RLP integer".to_string());
+        }
+        Ok(bytes)
+    }
+}
+
+fn decode_item(bytes: &[u8], depth: usize) -> Result<(Rlp, &[u8]), String> {
+    let prefix = *bytes
+        .first()
+        .ok_or_else(|| "unexpected end of RLP data".to_string())?;
+    let (is_list, offset, len) = match prefix {
+        0x00..=0x7f => return Ok((Rlp::Bytes(vec![prefix]), &bytes[1..])),
+        0x80..=0xb7 => (false, 1, usize::from(prefix - 0x80)),
+        0xb8..=0xbf => (
+            false,
+            1 + usize::from(prefix - 0xb7),
+            decode_length(bytes, prefix - 0xb7)?,
+        ),
+        0xc0..=0xf7 => (true, 1, usize::from(prefix - 0xc0)),
+        0xf8..=0xff => (
+            true,
+            1 + usize::from(prefix - 0xf7),
+            decode_length(bytes, prefix - 0xf7)?,
+        ),
+    };
+    let end = offset
+        .checked_add(len)
+        .filter(|end| *end <= bytes.len())
+        .ok_or_else(|| "unexpected end of RLP data".to_string())?;
+    let payload = &bytes[offset..end];
+    let item = if is_list {
+        if depth == MAX_LIST_DEPTH {
+            return Err("RLP lists nested too deep".to_string());
+        }
+        let mut items = Vec::new();
+        let mut rest = payload;
+        while !rest.is_empty() {
+            let (item, next) = decode_item(rest, depth + 1)?;
+            items.push(item);
+            rest = next;
+        }
+        Rlp::List(items)
+    } else {
+        if len == 1 && payload[0] < 0x80 {
+            return Err("non-canonical RLP encoding".to_string());
+        }
+        Rlp::Bytes(payload.to_vec())
+    };
+    Ok((item, &bytes[end..]))
+}
+
+fn decode_length(bytes: &[u8], len_of_len: u8) -> Result<usize, String> {
+    let len_bytes = bytes
+        .get(1..1 + usize::from(len_of_len))
+        .ok_or_else(|| "unexpected end of RLP data".to_string())?;
+    if len_bytes[0] == 0 || len_bytes.len() > std::mem::size_of::<usize>() {
+        return Err("non-canonical RLP encoding".to_string());
+    }
+    let len = len_bytes
+        .iter()
+        .fold(0usize, |acc, byte| (acc << 8) | usize::from(*byte));
+    // NOTE shorter payloads have to be encoded with the short form
+    if len < 56 {
+        return Err("non-canonical RLP encoding".to_string());
+    }
+    Ok(len)
+}
+
+fn encode_header(len: usize, offset: u8) -> Vec<u8> {
+    if len < 56 {
+        vec![offset + len as u8]
+    } else {
+        let len_bytes = len
+            .to_be_bytes()
+            .into_iter()
+            .skip_while(|byte| *byte == 0)
+            .collect::<Vec<u8>>();
+        [vec![offset + 55 + len_bytes.len() as u8], len_bytes].concat()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn encoding() {
+        // examples from the Ethereum wiki
+        let dog = Rlp::Bytes(b"dog".to_vec());
+        assert_eq!(dog.encode(), vec![0x83, b'd', b'o', b'g']);
+        let cat_dog = Rlp::List(vec![Rlp::Bytes(b"cat".to_vec()), dog.clone()]);
+        assert_eq!(
+            cat_dog.encode(),
+            vec![0xc8, 0x83, b'c', b'a', b't', 0x83, b'd', b'o', b'g']
+        );
+        assert_eq!(Rlp::Bytes(vec![]).encode(), vec![0x80]);
+        assert_eq!(Rlp::List(vec![]).encode(), vec![0xc0]);
+        assert_eq!(Rlp::Bytes(vec![0x0f]).encode(), vec![0x0f]);
+        assert_eq!(
+            Rlp::Bytes(vec![0x04, 0x00]).encode(),
+            vec![0x82, 0x04, 0x00]
+        );
+
+        let lorem = b"Lorem ipsum dolor sit amet, consectetur adipisicing elit".to_vec();
+        let encoded = Rlp::Bytes(lorem.clone()).encode();
+        assert_eq!(encoded[..2], [0xb8, 0x38]);
+
+        for item in [
+            dog,
+            cat_dog,
+            Rlp::Bytes(lorem),
+            Rlp::List(vec![Rlp::List(vec![])]),
+        ] {
+            assert_eq!(Rlp::decode(&item.encode()), Ok(item));
+        }
+    }
+
+    #[test]
+    fn non_canonical_decoding() {
+        assert_eq!(
+            Rlp::decode(&[0x81, 0x05]),
+            Err("non-canonical RLP encoding".to_string())
+        );
+        assert_eq!(
+            Rlp::decode(&[0xb8, 0x02, 0x01, 0x02]),
+            Err("non-canonical RLP encoding".to_string())
+        );
+        assert_eq!(
+            Rlp::decode(&[0x83, b'd', b'o']),
+            Err("unexpected end of RLP data".to_string())
+        );
+        assert_eq!(
+            Rlp::decode(&[0x83, b'd', b'o', b'g', 0x00]),
+            Err("trailing bytes after RLP item".to_string())
+        );
+        assert_eq!(
+            Rlp::decode(&[0x82, 0x00, 0x01]).unwrap().as_u64(),
+            Err("non-canonical RLP integer".to_string())
+        );
+        assert_eq!(Rlp::decode(&[0x82, 0x04, 0x00]).unwrap().as_u64(), Ok(1024));
+        assert_eq!(Rlp::decode(&[0x80]).unwrap().as_u256(), Ok(U256::ZERO));
+        assert_eq!(Rlp::from_u64(0).encode(), vec![0x80]);
+        assert_eq!(Rlp::from_u64(1024).encode(), vec![0x82, 0x04, 0x00]);
+    }
+
+    #[test]
+    fn nesting_depth() {
+        let mut item = Rlp::Bytes(vec![]);
+        for _ in 0..MAX_LIST_DEPTH {
+            item = Rlp::List(vec![item]);
+        }
+        assert_eq!(Rlp::decode(&item.encode()), Ok(item.clone()));
+        let item = Rlp::List(vec![item]);
+        assert_eq!(
+            Rlp::decode(&item.encode()),
+            Err("RLP lists nested too deep".to_string())
+        );
+
+        // the recursion is bounded regardless of the input length
+        let mut bytes = vec![0xc0];
+        for _ in 0..1000 {
+            bytes = [encode_header(bytes.len(), 0xc0), bytes].concat();
+        }
+        assert_eq!(
+            Rlp::decode(&bytes),
+            Err("RLP lists nested too deep".to_string())
+        );
+    }
+}
@@ -0,0 +1,511 @@
+use crate::challenge::{JoinChallenge, JOIN_GUILD_MSG};
+use crate::claims::ProofClaims;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use sha3::{Digest, Keccak256};
+
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Split;
+
+const HEADER_SUFFIX: &str = " wants you to sign in with your Ethereum account:";
+const SIWE_VERSION: &str = "1";
+const MIN_NONCE_LEN: usize = 8;
+
+/// Address line of anonymous SIWE messages.
+pub const ANONYMOUS_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+/// Prefix of the resource that binds the application claims to a SIWE
+/// message, followed by the hex encoded Keccak-256 hash of the claims.
+pub const CLAIMS_RESOURCE_PREFIX: &str = "urn:zkp.guild.xyz:claims:";
+
+/// Sign-In With Ethereum (EIP-4361) message.
+///
+/// The address line may be omitted, or replaced by [`ANONYMOUS_ADDRESS`],
+/// otherwise the message would reveal the signer.
+#[derive(Clone, Debug, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
+pub struct SiweMessage {
+    pub domain: String,
+    pub address: Option<String>,
+    pub statement: Option<String>,
+    pub uri: String,
+    pub version: String,
+    pub chain_id: u64,
+    pub nonce: String,
+    pub issued_at: String,
+    pub expiration_time: Option<String>,
+    pub not_before: Option<String>,
+    pub request_id: Option<String>,
+    pub resources: Vec<String>,
+}
+
+/// Requirements of the verifier on the SIWE messages it accepts.
+#[derive(Clone, Debug)]
+pub struct SiwePolicy {
+    pub domain: String,
+    pub chain_id: Option<u64>,
+}
+
+impl SiweMessage {
+    /// Parses a SIWE message, rejecting any deviation from the EIP-4361
+    /// grammar, so that re-encoding yields exactly the signed bytes.
+    pub fn parse(message: &str) -> Result<Self, String> {
+        let mut lines = message.split('\n').peekable();
+
+        let domain = next_line(&mut lines)?
+            .strip_suffix(HEADER_SUFFIX)
+            .filter(|domain| !domain.is_empty() && !domain.contains(' '))
+            .ok_or_else(|| "invalid SIWE header".to_string())?
+            .to_string();
+
+        let mut line = next_line(&mut lines)?;
+        let address = if line.is_empty() {
+            None
+        } else {
+            if !is_valid_address(line) {
+                return Err("invalid SIWE address".to_string());
+            }
+            expect_empty(&mut lines)?;
+            Some(line.to_string())
+        };
+
+        line = next_line(&mut lines)?;
+        let statement = if line.is_empty() {
+            None
+        } else {
+            expect_empty(&mut lines)?;
+            Some(line.to_string())
+        };
+
+        let uri = tagged(&mut lines, "URI: ")?.to_string();
+        let version = tagged(&mut lines, "Version: ")?.to_string();
+        if version != SIWE_VERSION {
+            return Err("unsupported SIWE version".to_string());
+        }
+        let chain_id = tagged(&mut lines, "Chain ID: ")?
+            .parse::<u64>()
+            .map_err(|_| "invalid SIWE chain id".to_string())?;
+        let nonce = tagged(&mut lines, "Nonce: ")?.to_string();
+        if nonce.len() < MIN_NONCE_LEN || !nonce.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err("invalid SIWE nonce".to_string());
+        }
+        let issued_at = tagged(&mut lines, "Issued At: ")?.to_string();
+        parse_timestamp(&issued_at)?;
+        let expiration_time = optional_tagged(&mut lines, "Expiration Time: ");
+        if let Some(timestamp) = expiration_time.as_ref() {
+            parse_timestamp(timestamp)?;
+        }
+        let not_before = optional_tagged(&mut lines, "Not Before: ");
+        if let Some(timestamp) = not_before.as_ref() {
+            parse_timestamp(timestamp)?;
+        }
+        let request_id = optional_tagged(&mut lines, "Request ID: ");
+
+        let mut resources = Vec::new();
+        if lines.next_if_eq(&"Resources:").is_some() {
+            for line in lines.by_ref() {
+                let resource = line
+                    .strip_prefix("- ")
+                    .ok_or_else(|| "invalid SIWE resource".to_string())?;
+                resources.push(resource.to_string());
+            }
+        }
+        if lines.next().is_some() {
+            return Err("unexpected SIWE line".to_string());
+        }
+
+        Ok(Self {
+            domain,
+            address,
+            statement,
+            uri,
+            version,
+            chain_id,
+            nonce,
+            issued_at,
+            expiration_time,
+            not_before,
+            request_id,
+            resources,
+        })
+    }
+
+    /// Checks that the message answers the given join challenge without
+    /// revealing the signer's address.
+    pub fn check_challenge(&self, challenge: &JoinChallenge) -> Result<(), String> {
+        if matches!(self.address.as_deref(), Some(address) if address != ANONYMOUS_ADDRESS) {
+            return Err("SIWE message reveals the address".to_string());
+        }
+        if self.nonce != challenge.nonce {
+            return Err("SIWE nonce mismatch".to_string());
+        }
+        let expiration_time = self
+            .expiration_time
+            .as_ref()
+            .ok_or_else(|| "missing SIWE expiration time".to_string())?;
+        if parse_timestamp(expiration_time)? != challenge.expires_at {
+            return Err("SIWE expiration mismatch".to_string());
+        }
+        if !self.resources.contains(&claims_resource(&challenge.claims)) {
+            return Err("SIWE message does not bind the claims".to_string());
+        }
+        Ok(())
+    }
+
+    /// Checks the message against the verifier's policy at time `now`
+    /// (seconds since the Unix epoch).
+    pub fn check_policy(&self, policy: &SiwePolicy, now: u64) -> Result<(), String> {
+        if self.domain != policy.domain {
+            return Err("SIWE domain mismatch".to_string());
+        }
+        if matches!(policy.chain_id, Some(chain_id) if chain_id != self.chain_id) {
+            return Err("SIWE chain id mismatch".to_string());
+        }
+        if parse_timestamp(&self.issued_at)? > now {
+            return Err("SIWE message issued in the future".to_string());
+        }
+        if let Some(timestamp) = self.not_before.as_ref() {
+            if parse_timestamp(timestamp)? > now {
+                return Err("SIWE message not yet valid".to_string());
+            }
+        }
+        if let Some(timestamp) = self.expiration_time.as_ref() {
+            if parse_timestamp(timestamp)? < now {
+                return Err("SIWE message expired".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SiweMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}{}", self.domain, HEADER_SUFFIX)?;
+        if let Some(address) = self.address.as_ref() {
+            writeln!(f, "{}", address)?;
+        }
+        writeln!(f)?;
+        if let Some(statement) = self.statement.as_ref() {
+            writeln!(f, "{}", statement)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "URI: {}", self.uri)?;
+        writeln!(f, "Version: {}", self.version)?;
+        writeln!(f, "Chain ID: {}", self.chain_id)?;
+        writeln!(f, "Nonce: {}", self.nonce)?;
+        write!(f, "Issued At: {}", self.issued_at)?;
+        if let Some(expiration_time) = self.expiration_time.as_ref() {
+            write!(f, "\nExpiration Time: {}", expiration_time)?;
+        }
+        if let Some(not_before) = self.not_before.as_ref() {
+            write!(f, "\nNot Before: {}", not_before)?;
+        }
+        if let Some(request_id) = self.request_id.as_ref() {
+            write!(f, "\nRequest ID: {}", request_id)?;
+        }
+        if !self.resources.is_empty() {
+            write!(f, "\nResources:")?;
+            for resource in self.resources.iter() {
+                write!(f, "\n- {}", resource)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the SIWE resource binding the claims.
+pub fn claims_resource(claims: &ProofClaims) -> String {
+    let hash = Keccak256::digest(claims.encode(JOIN_GUILD_MSG).as_bytes());
+    let hex = hash
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+    format!("{}{}", CLAIMS_RESOURCE_PREFIX, hex)
+}
+
+type Lines<'a> = Peekable<Split<'a, char>>;
+
+fn next_line<'a>(lines: &mut Lines<'a>) -> Result<&'a str, String> {
+    lines
+        .next()
+        .ok_or_else(|| "unexpected end of SIWE message".to_string())
+}
+
+fn expect_empty(lines: &mut Lines) -> Result<(), String> {
+    if next_line(lines)?.is_empty() {
+        Ok(())
+    } else {
+        Err("expected empty line in SIWE message".to_string())
+    }
+}
+
+fn tagged<'a>(lines: &mut Lines<'a>, tag: &str) -> Result<&'a str, String> {
+    next_line(lines)?
+        .strip_prefix(tag)
+        .ok_or_else(|| format!("missing SIWE field \"{}\"", tag.trim_end_matches(": ")))
+}
+
+fn optional_tagged(lines: &mut Lines, tag: &str) -> Option<String> {
+    lines
+        .next_if(|line| line.starts_with(tag))
+        .map(|line| line[tag.len()..].to_string())
+}
+
+fn is_valid_address(address: &str) -> bool {
+    address.len() == 42
+        && address.starts_with("0x")
+        && address[2..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Parses an RFC 3339 timestamp into seconds since the Unix epoch.
+pub(crate) fn parse_timestamp(timestamp: &str) -> Result<u64, String> {
+    let invalid = || format!("invalid timestamp \"{}\"", timestamp);
+    // the fields below are sliced at byte offsets
+    if !timestamp.is_ascii() {
+        return Err(invalid());
+    }
+    let bytes = timestamp.as_bytes();
+    if bytes.len() < 20
+        || bytes[4] != b'-'
+        || bytes[7] != b'-'
+        || !matches!(bytes[10], b'T' | b't')
+        || bytes[13] != b':'
+        || bytes[16] != b':'
+    {
+        return Err(invalid());
+    }
+    let number = |range: std::ops::Range<usize>| -> Result<i64, String> {
+        let digits = &timestamp[range];
+        if digits.chars().all(|c| c.is_ascii_digit()) {
+            digits.parse::<i64>().map_err(|_| invalid())
+        } else {
+            Err(invalid())
+        }
+    };
+
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let (hour, minute, second) = (number(11..13)?, number(14..16)?, number(17..19)?);
+    if !(1..=12).contains(&month)
+        || day < 1
+        || day > days_in_month(year, month)
+        || hour > 23
+        || minute > 59
+        || second > 60
+    {
+        return Err(invalid());
+    }
+
+    // fractional seconds are truncated
+    let mut rest = &timestamp[19..];
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let digits = fraction.chars().take_while(|c| c.is_ascii_digit()).count();
+        if digits == 0 {
+            return Err(invalid());
+        }
+        rest = &fraction[digits..];
+    }
+    let start = timestamp.len() - rest.len();
+    let offset = match rest.as_bytes() {
+        [b'Z' | b'z'] => 0,
+        [sign @ (b'+' | b'-'), _, _, b':', _, _] => {
+            let (hours, minutes) = (number(start + 1..start + 3)?, number(start + 4..start + 6)?);
+            if hours > 23 || minutes > 59 {
+                return Err(invalid());
+            }
+            let offset = hours * 3600 + minutes * 60;
+            if *sign == b'+' {
+                offset
+            } else {
+                -offset
+            }
+        }
+        _ => return Err(invalid()),
+    };
+
+    let seconds =
+        days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second - offset;
+    u64::try_from(seconds).map_err(|_| invalid())
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// days since 1970-01-01 in the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // example from the EIP-4361 specification
+    const SPEC_MESSAGE: &str = "service.invalid wants you to sign in with your Ethereum account:
+0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2
+
+I accept the ServiceOrg Terms of Service: https://service.invalid/tos
+
+URI: https://service.invalid/login
+Version: 1
+Chain ID: 1
+Nonce: 32891756
+Issued At: 2021-09-30T16:25:24Z
+Resources:
+- ipfs://bafybeiemxf5abjwjbikoz4mc3a3dla6ual3jsgpdr4cjr3oz3evfyavhwq/
+- https://example.com/my-web2-claim.json";
+
+    fn anonymous_message() -> SiweMessage {
+        SiweMessage {
+            domain: "guild.xyz".to_string(),
+            address: None,
+            statement: Some("Join guild anonymously".to_string()),
+            uri: "https://guild.xyz/almafa".to_string(),
+            version: "1".to_string(),
+            chain_id: 1,
+            nonce: "9f3c2a71d04b5e86".to_string(),
+            issued_at: "2023-11-14T21:13:20Z".to_string(),
+            expiration_time: Some("2023-11-14T22:13:20Z".to_string()),
+            not_before: None,
+            request_id: None,
+            resources: vec![claims_resource(&ProofClaims::new("almafa".to_string()))],
+        }
+    }
+
+    #[test]
+    fn parse_spec_message() {
+        let message = SiweMessage::parse(SPEC_MESSAGE).unwrap();
+        assert_eq!(message.domain, "service.invalid");
+        assert_eq!(
+            message.address.as_deref(),
+            Some("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2")
+        );
+        assert_eq!(message.chain_id, 1);
+        assert_eq!(message.nonce, "32891756");
+        assert_eq!(message.expiration_time, None);
+        assert_eq!(message.resources.len(), 2);
+        assert_eq!(message.to_string(), SPEC_MESSAGE);
+    }
+
+    #[test]
+    fn anonymous_round_trip() {
+        let mut message = anonymous_message();
+        let encoded = message.to_string();
+        assert!(encoded.starts_with(
+            "guild.xyz wants you to sign in with your Ethereum account:\n\nJoin guild"
+        ));
+        assert_eq!(SiweMessage::parse(&encoded), Ok(message.clone()));
+
+        message.statement = None;
+        message.address = Some(ANONYMOUS_ADDRESS.to_string());
+        message.not_before = Some("2023-11-14T21:13:20Z".to_string());
+        message.request_id = Some("1".to_string());
+        assert_eq!(SiweMessage::parse(&message.to_string()), Ok(message));
+    }
+
+    #[test]
+    fn invalid_messages() {
+        let encoded = anonymous_message().to_string();
+        assert_eq!(
+            SiweMessage::parse(&encoded.replace("wants you", "asks you")),
+            Err("invalid SIWE header".to_string())
+        );
+        assert_eq!(
+            SiweMessage::parse(&encoded.replace("Version: 1", "Version: 2")),
+            Err("unsupported SIWE version".to_string())
+        );
+        assert_eq!(
+            SiweMessage::parse(&encoded.replace("Nonce: 9f3c2a71d04b5e86", "Nonce: 1234")),
+            Err("invalid SIWE nonce".to_string())
+        );
+        assert_eq!(
+            SiweMessage::parse(&encoded.replace("\nChain ID: 1", "")),
+            Err("missing SIWE field \"Chain ID\"".to_string())
+        );
+        assert!(SiweMessage::parse(&encoded.replace("22:13:20Z", "24:13:20Z")).is_err());
+        assert!(SiweMessage::parse(&format!("{}\n", encoded)).is_err());
+        assert!(SiweMessage::parse(&encoded[..encoded.len() / 2]).is_err());
+    }
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(parse_timestamp("2023-11-14T22:13:20Z"), Ok(1_700_000_000));
+        assert_eq!(
+            parse_timestamp("2021-09-30T16:25:24.000Z"),
+            Ok(1_633_019_124)
+        );
+        assert_eq!(
+            parse_timestamp("2023-11-15T00:13:20+02:00"),
+            Ok(1_700_000_000)
+        );
+        assert_eq!(
+            parse_timestamp("2000-02-29T12:00:00-05:30"),
+            Ok(951_845_400)
+        );
+        assert!(parse_timestamp("2001-02-29T12:00:00Z").is_err());
+        assert!(parse_timestamp("1969-12-31T23:59:59Z").is_err());
+        assert!(parse_timestamp("2023-11-14 22:13:20Z").is_err());
+        assert!(parse_timestamp("2023-11-14T22:13:20").is_err());
+        assert!(parse_timestamp("2023-11-14T22:13:20+2:00").is_err());
+        // multibyte characters that pass the separator checks
+        assert!(parse_timestamp("2023-11-14T22:13:2€Z").is_err());
+        assert!(parse_timestamp("2023-1€-14T22:13:20Z").is_err());
+    }
+
+    #[test]
+    fn challenge_and_policy() {
+        let challenge = JoinChallenge {
+            claims: ProofClaims::new("almafa".to_string()),
+            nonce: "9f3c2a71d04b5e86".to_string(),
+            expires_at: 1_700_000_000,
+            domain: None,
+        };
+        let mut message = anonymous_message();
+        assert!(message.check_challenge(&challenge).is_ok());
+
+        message.address = Some("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2".to_string());
+        assert_eq!(
+            message.check_challenge(&challenge),
+            Err("SIWE message reveals the address".to_string())
+        );
+        message.address = None;
+        message.resources.clear();
+        assert_eq!(
+            message.check_challenge(&challenge),
+            Err("SIWE message does not bind the claims".to_string())
+        );
+
+        let message = anonymous_message();
+        let policy = SiwePolicy {
+            domain: "guild.xyz".to_string(),
+            chain_id: Some(1),
+        };
+        assert!(message.check_policy(&policy, 1_699_999_000).is_ok());
+        assert_eq!(
+            message.check_policy(&policy, 1_700_000_001),
+            Err("SIWE message expired".to_string())
+        );
+        assert_eq!(
+            message.check_policy(&policy, 1_699_990_000),
+            Err("SIWE message issued in the future".to_string())
+        );
+        let policy = SiwePolicy {
+            domain: "evil.xyz".to_string(),
+            chain_id: None,
+        };
+        assert_eq!(
+            message.check_policy(&policy, 1_699_999_000),
+            Err("SIWE domain mismatch".to_string())
+        );
+    }
+}
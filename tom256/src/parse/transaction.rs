@@ -0,0 +1,164 @@
+use super::{decode_hex, recovery_id, signature_from_parts, Address, Rlp, Signature};
+use crate::arithmetic::{AffinePoint, Modular, Scalar};
+use crate::curve::Secp256k1;
+use crate::U256;
+
+use bigint::Encoding;
+use sha3::{Digest, Keccak256};
+
+const EIP2930_TX_TYPE: u8 = 0x01;
+const EIP1559_TX_TYPE: u8 = 0x02;
+
+/// Signed Ethereum transaction, reduced to the data required for recovering
+/// its sender.
+pub struct SignedTransaction {
+    /// Keccak-256 hash of the unsigned transaction payload.
+    pub signing_hash: Scalar<Secp256k1>,
+    pub signature: Signature<Secp256k1>,
+}
+
+impl SignedTransaction {
+    /// Parses a hex encoded raw transaction.
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        Self::decode(&decode_hex(raw)?)
+    }
+
+    /// Decodes a legacy (optionally EIP-155 replay protected), an EIP-2930
+    /// or an EIP-1559 transaction from its network encoding.
+    pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+        match bytes.first() {
+            Some(0xc0..=0xff) => Self::decode_legacy(bytes),
+            Some(&EIP2930_TX_TYPE) => Self::decode_typed(bytes, 8),
+            Some(&EIP1559_TX_TYPE) => Self::decode_typed(bytes, 9),
+            Some(tx_type) => Err(format!("unsupported transaction type {}", tx_type)),
+            None => Err("empty transaction".to_string()),
+        }
+    }
+
+    /// Recovers the public key of the sender.
+    pub fn sender_pubkey(&self) -> Result<AffinePoint<Secp256k1>, String> {
+        self.signature.recover_pubkey(&self.signing_hash)
+    }
+
+    pub fn sender(&self) -> Result<Address, String> {
+        self.sender_pubkey()
+            .map(|pubkey| Address::from_pubkey(&pubkey))
+    }
+
+    // nonce, gasPrice, gas, to, value, data, v, r, s
+    fn decode_legacy(bytes: &[u8]) -> Result<Self, String> {
+        let rlp = Rlp::decode(bytes)?;
+        let fields = transaction_fields(&rlp, 9)?;
+        let v = fields[6].as_u64()?;
+        let mut unsigned = fields[..6].to_vec();
+        match v {
+            27 | 28 => {}
+            // NOTE EIP-155 appends the chain id and two empty fields to the
+            // signed payload
+            35.. => unsigned.extend([
+                Rlp::from_u64((v - 35) / 2),
+                Rlp::Bytes(vec![]),
+                Rlp::Bytes(vec![]),
+            ]),
+            _ => return Err("invalid legacy transaction v value".to_string()),
+        }
+        Ok(Self {
+            signing_hash: keccak_scalar(&Rlp::List(unsigned).encode()),
+            signature: signature_from_parts(
+                fields[7].as_u256()?,
+                fields[8].as_u256()?,
+                recovery_id(v)?,
+            )?,
+        })
+    }
+
+    // the unsigned fields are followed by yParity, r, s
+    fn decode_typed(bytes: &[u8], unsigned_len: usize) -> Result<Self, String> {
+        let rlp = Rlp::decode(&bytes[1..])?;
+        let fields = transaction_fields(&rlp, unsigned_len + 3)?;
+        let y_parity = fields[unsigned_len].as_u64()?;
+        if y_parity > 1 {
+            return Err("invalid transaction y parity".to_string());
+        }
+        let unsigned = Rlp::List(fields[..unsigned_len].to_vec()).encode();
+        Ok(Self {
+            signing_hash: keccak_scalar(&[&bytes[..1], &unsigned].concat()),
+            signature: signature_from_parts(
+                fields[unsigned_len + 1].as_u256()?,
+                fields[unsigned_len + 2].as_u256()?,
+                y_parity as u8,
+            )?,
+        })
+    }
+}
+
+fn transaction_fields(rlp: &Rlp, len: usize) -> Result<&[Rlp], String> {
+    let fields = rlp.as_list()?;
+    if fields.len() != len {
+        return Err(format!(
+            "expected {} transaction fields, found {}",
+            len,
+            fields.len()
+        ));
+    }
+    Ok(fields)
+}
+
+fn keccak_scalar(payload: &[u8]) -> Scalar<Secp256k1> {
+    Scalar::new(U256::from_be_bytes(Keccak256::digest(payload).into()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // example from EIP-155, signed with the private key 0x4646..46
+    const EIP155_TX: &str = "0xf86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+
+    #[test]
+    fn legacy_transaction() {
+        let tx = SignedTransaction::parse(EIP155_TX).unwrap();
+        assert_eq!(
+            tx.signing_hash,
+            Scalar::new(U256::from_be_hex(
+                "daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53"
+            ))
+        );
+        assert_eq!(
+            tx.sender().unwrap().to_string(),
+            "0x9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F"
+        );
+
+        // v = 37 replaced by 38 recovers a different key
+        let flipped = EIP155_TX.replace("8025a0", "8026a0");
+        assert_ne!(
+            SignedTransaction::parse(&flipped).unwrap().sender(),
+            tx.sender()
+        );
+        let invalid_v = EIP155_TX.replace("8025a0", "801fa0");
+        assert_eq!(
+            SignedTransaction::parse(&invalid_v).err(),
+            Some("invalid legacy transaction v value".to_string())
+        );
+    }
+
+    #[test]
+    fn malformed_transactions() {
+        assert_eq!(
+            SignedTransaction::parse("0x03c0").err(),
+            Some("unsupported transaction type 3".to_string())
+        );
+        assert_eq!(
+            SignedTransaction::parse("0x").err(),
+            Some("empty transaction".to_string())
+        );
+        assert_eq!(
+            SignedTransaction::parse("0x02c0").err(),
+            Some("expected 12 transaction fields, found 0".to_string())
+        );
+        assert_eq!(
+            SignedTransaction::parse(&EIP155_TX[..EIP155_TX.len() - 2]).err(),
+            Some("unexpected end of RLP data".to_string())
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use crate::arithmetic::{Point, Scalar};
+use crate::arithmetic::{AffinePoint, Point, Scalar};
 use crate::curve::{Curve, Cycle};
 use crate::rng::CryptoCoreRng;
 
@@ -18,6 +18,19 @@ impl<C: Curve, CC: Cycle<C>> PedersenCycle<C, CC> {
         }
     }
 
+    /// Returns the generators fixed by the protocol.
+    ///
+    /// The generators are derived by hash-to-curve, thus nobody knows their
+    /// discrete logarithms. Commitments with generators chosen by the prover
+    /// are not binding, since the prover may know their discrete logarithms,
+    /// so verifiers must use these.
+    pub fn fixed() -> Self {
+        Self {
+            base: PedersenGenerator::hash_to_curve(b"pedersen-generator"),
+            cycle: PedersenGenerator::hash_to_curve(b"pedersen-generator"),
+        }
+    }
+
     pub fn base(&self) -> &PedersenGenerator<C> {
         &self.base
     }
@@ -36,6 +49,12 @@ impl<C: Curve> PedersenGenerator<C> {
         Self(&Point::<C>::GENERATOR * random_scalar)
     }
 
+    /// Returns a generator derived by hash-to-curve, whose discrete logarithm
+    /// is unknown.
+    pub fn hash_to_curve(label: &[u8]) -> Self {
+        Self(Point::from(AffinePoint::hash_to_curve(label, 0)))
+    }
+
     pub fn generator(&self) -> &Point<C> {
         &self.0
     }
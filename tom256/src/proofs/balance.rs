@@ -0,0 +1,365 @@
+use super::weighted::{surplus, BALANCE_BITS};
+use super::{
+    signer_index, verify_message, BulletproofGenerators, DistinctnessProof, ExpCommitmentPoints,
+    OwnershipProof, RangeProof, WeightedMembershipProof,
+};
+use crate::arithmetic::{Point, Scalar};
+use crate::challenge::JoinChallenge;
+use crate::claims::ProofClaims;
+use crate::curve::{Curve, Cycle};
+use crate::message::MessageScheme;
+use crate::parse::{ParsedProofInput, ParsedRing};
+use crate::pedersen::{PedersenCommitment, PedersenCycle};
+use crate::ring::{ring_digest, RingView};
+use crate::rng::CryptoCoreRng;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Committed public key and balance of one of the signers of a
+/// [`BalanceAttestProof`] with its ownership and weighted membership proofs.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct BalanceMember<C: Curve, CC: Cycle<C>> {
+    pub exp_commitments: ExpCommitmentPoints<C, CC>, // exp, pkx, pky
+    pub ownership_proof: OwnershipProof<C, CC>,
+    pub commitment_to_balance: Point<CC>,
+    pub membership_proof: WeightedMembershipProof<CC>,
+}
+
+/// Zero-knowledge proof that the prover controls distinct keys of a weighted
+/// ring whose balances sum up to at least a public threshold.
+///
+/// Every signer proves the ownership of its committed key like in a
+/// [`ThresholdAttestProof`](super::ThresholdAttestProof) and that the key
+/// and its committed balance form an entry of the weighted ring. The keys
+/// are shown to be pairwise distinct, thus no entry is counted twice. The
+/// balance commitments are added up and a [`RangeProof`] shows that the sum
+/// minus the threshold is in `[0, 2^BALANCE_BITS)`.
+///
+/// The commitments are made with the generators fixed by the protocol, see
+/// [`PedersenCycle::fixed`].
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct BalanceAttestProof<C: Curve, CC: Cycle<C>> {
+    pub msg_hash: Scalar<C>,
+    pub ring_digest: [u8; 32],
+    pub balances_digest: [u8; 32],
+    pub members: Vec<BalanceMember<C, CC>>,
+    pub distinctness_proof: DistinctnessProof<CC>,
+    pub threshold: Scalar<CC>,
+    pub range_proof: RangeProof<CC>,
+    pub challenge: JoinChallenge,
+    pub message_scheme: MessageScheme,
+}
+
+impl<C: Curve, CC: Cycle<C>> BalanceAttestProof<C, CC> {
+    /// Generates the proof from the inputs of the signers.
+    ///
+    /// The inputs have to sign the same message, the challenge and the message
+    /// scheme of the proof are taken from the first one.
+    pub fn construct<R: CryptoCoreRng>(
+        rng: &mut R,
+        generators: &BulletproofGenerators<CC>,
+        inputs: Vec<ParsedProofInput<C>>,
+        ring: &ParsedRing<CC>,
+        balances: &[Scalar<CC>],
+        threshold: Scalar<CC>,
+    ) -> Result<Self, String> {
+        let first = inputs
+            .first()
+            .ok_or_else(|| "no signers given".to_string())?;
+        let msg_hash = first.msg_hash;
+        if inputs.iter().any(|input| input.msg_hash != msg_hash) {
+            return Err("signers sign different messages".to_string());
+        }
+        if inputs.iter().any(|input| input.nullifier) {
+            return Err("nullifiers are not supported for balance proofs".to_string());
+        }
+        if inputs.iter().any(|input| input.siwe.is_some()) {
+            return Err("SIWE messages are not supported for balance proofs".to_string());
+        }
+        if ring.len() != balances.len() {
+            return Err("ring column length mismatch".to_string());
+        }
+
+        let challenge = first.challenge.clone();
+        let message_scheme = first.message_scheme;
+        let pedersen = PedersenCycle::fixed();
+
+        let mut members = Vec::with_capacity(inputs.len());
+        let mut commitments_to_pk_x = Vec::with_capacity(inputs.len());
+        let mut pubkeys_x = Vec::with_capacity(inputs.len());
+        let mut total_balance = Scalar::<CC>::ZERO;
+        let mut commitment_to_total: Option<PedersenCommitment<CC>> = None;
+        for input in inputs {
+            let index = signer_index(ring, &input)?;
+            let pubkey_x = input.pubkey.x().to_cycle_scalar::<CC>();
+            let commitment_to_pk_x = pedersen.cycle().commit(rng, pubkey_x);
+            let commitment_to_pk_y = pedersen
+                .cycle()
+                .commit(rng, input.pubkey.y().to_cycle_scalar());
+            let commitment_to_balance = pedersen.cycle().commit(rng, balances[index]);
+
+            let membership_proof = WeightedMembershipProof::construct(
+                rng,
+                pedersen.cycle(),
+                &commitment_to_pk_x,
+                &commitment_to_balance,
+                index,
+                ring,
+                balances,
+            )?;
+            let (ownership_proof, exp_commitments) = OwnershipProof::construct(
+                rng,
+                &pedersen,
+                msg_hash,
+                input.witness,
+                input.pubkey,
+                input.hide_r_point,
+                commitment_to_pk_x.clone(),
+                commitment_to_pk_y,
+            )?;
+
+            total_balance += balances[index];
+            commitment_to_total = Some(match commitment_to_total {
+                Some(total) => &total + &commitment_to_balance,
+                None => commitment_to_balance.clone(),
+            });
+            members.push(BalanceMember {
+                exp_commitments,
+                ownership_proof,
+                commitment_to_balance: commitment_to_balance.into_commitment(),
+                membership_proof,
+            });
+            commitments_to_pk_x.push(commitment_to_pk_x);
+            pubkeys_x.push(pubkey_x);
+        }
+
+        let distinctness_proof =
+            DistinctnessProof::construct(rng, pedersen.cycle(), &commitments_to_pk_x, &pubkeys_x)
+                .map_err(|_| "signers are not distinct".to_string())?;
+
+        // NOTE the sum cannot overflow the order for any realistic ring
+        if total_balance < threshold {
+            return Err("balance is below the threshold".to_string());
+        }
+        // unwrap is fine because there is at least one signer
+        let commitment_to_total = commitment_to_total.unwrap();
+        let range_proof = RangeProof::construct(
+            rng,
+            pedersen.cycle(),
+            generators,
+            &[surplus(&commitment_to_total, threshold)],
+            &[total_balance - threshold],
+            BALANCE_BITS,
+        )?;
+
+        Ok(Self {
+            msg_hash,
+            ring_digest: ring_digest(ring)?,
+            balances_digest: ring_digest(balances)?,
+            members,
+            distinctness_proof,
+            threshold,
+            range_proof,
+            challenge,
+            message_scheme,
+        })
+    }
+
+    /// Verifies that the signers' balances in the weighted ring sum up to at
+    /// least `threshold` and returns the claims bound to the proof.
+    ///
+    /// A proof generated for a higher threshold is accepted as well.
+    pub fn verify<R, K, B>(
+        &self,
+        rng: &mut R,
+        generators: &BulletproofGenerators<CC>,
+        ring: &K,
+        balances: &B,
+        threshold: Scalar<CC>,
+    ) -> Result<&ProofClaims, String>
+    where
+        R: CryptoCoreRng,
+        K: RingView<CC> + ?Sized,
+        B: RingView<CC> + ?Sized,
+    {
+        if self.threshold < threshold {
+            return Err("balance threshold not met".to_string());
+        }
+        if self.members.is_empty() {
+            return Err("no signers given".to_string());
+        }
+        if ring_digest(ring)? != self.ring_digest || ring_digest(balances)? != self.balances_digest
+        {
+            return Err("ring digest mismatch".to_string());
+        }
+        verify_message(
+            self.msg_hash,
+            &self.challenge,
+            self.message_scheme,
+            None,
+            None,
+        )?;
+
+        // NOTE the range proof is only sound if the prover does not know the
+        // discrete logarithm of the blinding generator
+        let pedersen = PedersenCycle::fixed();
+        for member in self.members.iter() {
+            member.ownership_proof.verify(
+                rng,
+                &pedersen,
+                self.msg_hash,
+                &member.exp_commitments,
+            )?;
+            member.membership_proof.verify(
+                rng,
+                pedersen.cycle(),
+                &member.exp_commitments.px,
+                &member.commitment_to_balance,
+                ring,
+                balances,
+            )?;
+        }
+
+        let commitments_to_pk_x = self
+            .members
+            .iter()
+            .map(|member| &member.exp_commitments.px)
+            .collect::<Vec<&Point<CC>>>();
+        self.distinctness_proof
+            .verify(rng, pedersen.cycle(), &commitments_to_pk_x)?;
+
+        let commitment_to_total = self
+            .members
+            .iter()
+            .fold(Point::<CC>::IDENTITY, |total, member| {
+                &total + &member.commitment_to_balance
+            });
+        let commitment_to_surplus =
+            &commitment_to_total - &(&Point::<CC>::GENERATOR * self.threshold);
+        self.range_proof.verify(
+            rng,
+            pedersen.cycle(),
+            generators,
+            &[&commitment_to_surplus],
+            BALANCE_BITS,
+        )?;
+
+        Ok(&self.challenge.claims)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arithmetic::Modular;
+    use crate::curve::{Secp256k1, Tom256k1};
+    use crate::parse::{Signature, Witness};
+    use crate::U256;
+    use rand::rngs::StdRng;
+    use rand_core::SeedableRng;
+
+    #[test]
+    fn balance_attest() {
+        let mut rng = StdRng::from_seed([49; 32]);
+        let generators = BulletproofGenerators::new(BALANCE_BITS);
+
+        let challenge = JoinChallenge {
+            claims: ProofClaims::new("almafa".to_string()),
+            nonce: "e3a91f0c5d7b2846".to_string(),
+            expires_at: 1_700_000_000,
+            domain: None,
+        };
+        let msg_hash = MessageScheme::default().hash(&challenge.message());
+        let secrets = (0..2)
+            .map(|_| Scalar::<Secp256k1>::random(&mut rng))
+            .collect::<Vec<_>>();
+        let pubkeys = secrets
+            .iter()
+            .map(|secret| (&Point::<Secp256k1>::GENERATOR * *secret).to_affine())
+            .collect::<Vec<_>>();
+        let mut ring = (0..4u8)
+            .map(|i| Scalar::<Tom256k1>::new(U256::from_u8(i)))
+            .collect::<Vec<_>>();
+        ring[0] = pubkeys[0].x().to_cycle_scalar();
+        ring[3] = pubkeys[1].x().to_cycle_scalar();
+        let balances = [60u64, 500, 500, 70]
+            .iter()
+            .map(|balance| Scalar::<Tom256k1>::new(U256::from_u64(*balance)))
+            .collect::<Vec<_>>();
+
+        let inputs = |signers: &[usize]| {
+            signers
+                .iter()
+                .map(|&i| ParsedProofInput {
+                    msg_hash,
+                    pubkey: pubkeys[i].clone(),
+                    witness: Witness::Signature(Signature::sign(&secrets[i], &msg_hash)),
+                    index: None,
+                    challenge: challenge.clone(),
+                    nullifier: false,
+                    message_scheme: MessageScheme::default(),
+                    siwe: None,
+                    hide_r_point: false,
+                })
+                .collect::<Vec<_>>()
+        };
+        let amount = |value: u64| Scalar::<Tom256k1>::new(U256::from_u64(value));
+
+        // neither key reaches the threshold alone
+        assert_eq!(
+            BalanceAttestProof::construct(
+                &mut rng,
+                &generators,
+                inputs(&[1]),
+                &ring,
+                &balances,
+                amount(100),
+            )
+            .err(),
+            Some("balance is below the threshold".to_string())
+        );
+        // the same key cannot be counted twice
+        assert_eq!(
+            BalanceAttestProof::construct(
+                &mut rng,
+                &generators,
+                inputs(&[1, 1]),
+                &ring,
+                &balances,
+                amount(100),
+            )
+            .err(),
+            Some("signers are not distinct".to_string())
+        );
+
+        let proof = BalanceAttestProof::construct(
+            &mut rng,
+            &generators,
+            inputs(&[1, 0]),
+            &ring,
+            &balances,
+            amount(130),
+        )
+        .unwrap();
+        assert_eq!(
+            proof.verify(&mut rng, &generators, &ring, &balances, amount(130)),
+            Ok(&challenge.claims)
+        );
+        assert!(proof
+            .verify(&mut rng, &generators, &ring, &balances, amount(100))
+            .is_ok());
+        assert_eq!(
+            proof.verify(&mut rng, &generators, &ring, &balances, amount(131)),
+            Err("balance threshold not met".to_string())
+        );
+
+        // a member cannot be dropped from the sum
+        let mut forged =
+            BalanceAttestProof::<Secp256k1, Tom256k1>::try_from_slice(&proof.try_to_vec().unwrap())
+                .unwrap();
+        forged.members.pop();
+        assert!(forged
+            .verify(&mut rng, &generators, &ring, &balances, amount(130))
+            .is_err());
+    }
+}
@@ -0,0 +1,348 @@
+use super::exp::{ChallengeShare, ExpCommitments, ExpProof};
+use super::sigma::{SigmaProtocol, Transcript};
+use super::{
+    MembershipProof, OwnershipProof, RingComposition, RingProof, ZkAttestProof, SEC_PARAM,
+};
+use crate::arithmetic::multimult::{MultiMult, Relation};
+use crate::arithmetic::{AffinePoint, Modular, Point, Scalar};
+use crate::challenge::JoinChallenge;
+use crate::claims::ProofClaims;
+use crate::curve::{Curve, Cycle};
+use crate::message::MessageScheme;
+use crate::parse::{locate_signer, ParsedProofInput, ParsedRing};
+use crate::pedersen::*;
+use crate::ring::{ring_digest, RingView};
+use crate::rng::CryptoCoreRng;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use std::ops::Neg;
+
+/// Statement of knowledge of the secret key of a public key.
+#[derive(Clone, Copy)]
+pub struct SchnorrStatement<'a, C: Curve> {
+    pub pubkey: &'a Point<C>,
+}
+
+impl<'a, C: Curve> SigmaProtocol<C> for SchnorrStatement<'a, C> {
+    type Witness = Scalar<C>;
+    type Commitment = Point<C>;
+    type Response = Scalar<C>;
+    type Nonce = Scalar<C>;
+
+    fn commit<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        _witness: &Self::Witness,
+    ) -> (Self::Commitment, Self::Nonce) {
+        let nonce = Scalar::random(rng);
+        (&Point::<C>::GENERATOR * nonce, nonce)
+    }
+
+    fn absorb(&self, commitment: &Self::Commitment, transcript: &mut Transcript) {
+        transcript.append_points(&[self.pubkey, commitment]);
+    }
+
+    fn respond(
+        &self,
+        witness: &Self::Witness,
+        nonce: Self::Nonce,
+        challenge: Scalar<C>,
+    ) -> Self::Response {
+        nonce - challenge * *witness
+    }
+
+    fn simulate<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        challenge: Scalar<C>,
+    ) -> (Self::Commitment, Self::Response) {
+        let response = Scalar::random(rng);
+        let commitment = Point::<C>::GENERATOR.double_mul(&response, self.pubkey, &challenge);
+        (commitment, response)
+    }
+
+    fn verify_into<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        commitment: &Self::Commitment,
+        challenge: Scalar<C>,
+        response: &Self::Response,
+        multimult: &mut MultiMult<C>,
+    ) -> Result<(), String> {
+        let mut relation = Relation::new();
+        relation.insert(Point::<C>::GENERATOR, *response);
+        relation.insert(self.pubkey.clone(), challenge);
+        relation.insert(commitment.neg(), Scalar::ONE);
+        relation.drain(rng, multimult);
+        Ok(())
+    }
+}
+
+/// [`ZkAttestProof`] that only convinces the verifier it is generated for.
+///
+/// The ownership proof is composed in an OR with a Schnorr proof of the
+/// verifier's secret key. The prover simulates the Schnorr proof and the
+/// challenge of the ownership proof is the Fiat-Shamir hash minus the
+/// simulated challenge. The verifier could have generated such a proof for
+/// any member of the ring with its own secret key, see
+/// [`DesignatedAttestProof::simulate`], thus the proof is no evidence to
+/// anyone else. The rest of the proof needs no secret and it is verified by
+/// the same logic as a plain [`ZkAttestProof`].
+///
+/// Only signatures with a public `R` point are supported, without a
+/// nullifier or a SIWE message.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct DesignatedAttestProof<C: Curve, CC: Cycle<C>> {
+    pub proof: ZkAttestProof<C, CC>,
+    pub verifier_commitment: Point<C>,
+    pub verifier_challenge: Scalar<C>,
+    pub verifier_response: Scalar<C>,
+}
+
+impl<C: Curve, CC: Cycle<C>> DesignatedAttestProof<C, CC> {
+    pub fn construct<R: CryptoCoreRng>(
+        rng: &mut R,
+        input: ParsedProofInput<C>,
+        ring: &ParsedRing<CC>,
+        verifier_key: &Point<C>,
+    ) -> Result<Self, String> {
+        if input.nullifier {
+            return Err("nullifiers are not supported for designated proofs".to_string());
+        }
+
+        let verifier_challenge = Scalar::random(rng);
+        let (verifier_commitment, verifier_response) = SchnorrStatement {
+            pubkey: verifier_key,
+        }
+        .simulate(rng, verifier_challenge);
+
+        let share = ChallengeShare {
+            points: [verifier_key, &verifier_commitment],
+            challenge: verifier_challenge,
+        };
+        let (proof, _) = ZkAttestProof::construct_rings(
+            rng,
+            input,
+            &[ring],
+            RingComposition::Single,
+            None,
+            Some(&share),
+        )?;
+
+        Ok(Self {
+            proof,
+            verifier_commitment,
+            verifier_challenge,
+            verifier_response,
+        })
+    }
+
+    /// Generates a proof for any member of the ring with the verifier's
+    /// secret key instead of the member's signature.
+    ///
+    /// The ownership proof is simulated with a challenge chosen in advance
+    /// and the Schnorr proof answers the rest of the hash honestly. The
+    /// result is distributed exactly like a proof of the member.
+    pub fn simulate<R: CryptoCoreRng>(
+        rng: &mut R,
+        verifier_secret_key: Scalar<C>,
+        pubkey: AffinePoint<C>,
+        challenge: JoinChallenge,
+        message_scheme: MessageScheme,
+        ring: &ParsedRing<CC>,
+    ) -> Result<Self, String> {
+        let msg_hash = challenge.msg_hash::<C>(message_scheme)?;
+        let index = locate_signer(ring, &pubkey)?;
+        let pedersen = PedersenCycle::fixed();
+
+        let commitment_to_pk_x = pedersen.cycle().commit(rng, pubkey.x().to_cycle_scalar());
+        let commitment_to_pk_y = pedersen.cycle().commit(rng, pubkey.y().to_cycle_scalar());
+        let membership_proof =
+            MembershipProof::construct(rng, pedersen.cycle(), &commitment_to_pk_x, index, ring)?;
+
+        // random canonical R, the exponent s1 with s1·R = P + Q is unknown,
+        // however the commitment to it is P + Q + h^r
+        let mut r_point = &Point::<C>::GENERATOR * Scalar::random(rng);
+        if r_point.to_affine().y().is_odd() {
+            r_point = -r_point;
+        }
+        let r_inv = Scalar::<C>::new(*r_point.to_affine().x().inner()).inverse();
+        let q_point = &Point::<C>::GENERATOR * (r_inv * msg_hash);
+        let randomness = Scalar::random(rng);
+        let exp_commitments = ExpCommitments {
+            px: commitment_to_pk_x,
+            py: commitment_to_pk_y,
+            exp: PedersenCommitment::new(
+                &(&Point::from(&pubkey) + &q_point) + &(pedersen.base().generator() * randomness),
+                randomness,
+            ),
+        };
+
+        let verifier_key = &Point::<C>::GENERATOR * verifier_secret_key;
+        let statement = SchnorrStatement {
+            pubkey: &verifier_key,
+        };
+        let (verifier_commitment, nonce) = statement.commit(rng, &verifier_secret_key);
+        let exp_challenge = Scalar::random(rng);
+        let (ownership_proof, hash) = ExpProof::simulate(
+            rng,
+            &r_point,
+            &pedersen,
+            &pubkey,
+            &exp_commitments,
+            SEC_PARAM,
+            Some(q_point),
+            [&verifier_key, &verifier_commitment],
+            exp_challenge,
+        )?;
+        let verifier_challenge = hash - exp_challenge;
+        let verifier_response = statement.respond(&verifier_secret_key, nonce, verifier_challenge);
+
+        let proof = ZkAttestProof {
+            msg_hash,
+            exp_commitments: exp_commitments.into_commitments(),
+            ownership_proof: OwnershipProof::Signature {
+                r_point,
+                proof: ownership_proof,
+            },
+            ring_digests: vec![ring_digest(ring)?],
+            membership_proof: RingProof::Single(membership_proof),
+            non_membership_proof: None,
+            nullifier_proof: None,
+            challenge,
+            message_scheme,
+            siwe: None,
+        };
+
+        Ok(Self {
+            proof,
+            verifier_commitment,
+            verifier_challenge,
+            verifier_response,
+        })
+    }
+
+    /// Verifies the proof with the verifier's public key and returns the
+    /// claims bound to it.
+    pub fn verify<R: CryptoCoreRng, V: RingView<CC> + ?Sized>(
+        &self,
+        rng: &mut R,
+        verifier_key: &Point<C>,
+        ring: &V,
+    ) -> Result<&ProofClaims, String> {
+        let mut multimult = MultiMult::new();
+        SchnorrStatement {
+            pubkey: verifier_key,
+        }
+        .verify_into(
+            rng,
+            &self.verifier_commitment,
+            self.verifier_challenge,
+            &self.verifier_response,
+            &mut multimult,
+        )?;
+        if !multimult.evaluate().is_identity() {
+            return Err("failed to verify designated proof".to_string());
+        }
+
+        let share = ChallengeShare {
+            points: [verifier_key, &self.verifier_commitment],
+            challenge: self.verifier_challenge,
+        };
+        self.proof
+            .verify_shared(rng, &[ring], RingComposition::Single, None, Some(&share))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curve::{Secp256k1, Tom256k1};
+    use crate::parse::{Signature, Witness};
+    use crate::U256;
+    use rand::rngs::StdRng;
+    use rand_core::SeedableRng;
+
+    #[test]
+    fn designated_attest() {
+        let mut rng = StdRng::from_seed([51; 32]);
+
+        let challenge = JoinChallenge {
+            claims: ProofClaims::new("almafa".to_string()),
+            nonce: "6e1b3f90a5c27d48".to_string(),
+            expires_at: 1_700_000_000,
+            domain: None,
+        };
+        let msg_hash = MessageScheme::default().hash(&challenge.message());
+        let secret = Scalar::<Secp256k1>::random(&mut rng);
+        let pubkey = (&Point::<Secp256k1>::GENERATOR * secret).to_affine();
+        let mut ring = (0..5u8)
+            .map(|i| Scalar::<Tom256k1>::new(U256::from_u8(i)))
+            .collect::<Vec<_>>();
+        ring[3] = pubkey.x().to_cycle_scalar();
+
+        let verifier_secret_key = Scalar::<Secp256k1>::random(&mut rng);
+        let verifier_key = &Point::<Secp256k1>::GENERATOR * verifier_secret_key;
+        let other_key = &Point::<Secp256k1>::GENERATOR * Scalar::random(&mut rng);
+
+        let input = || ParsedProofInput {
+            msg_hash,
+            pubkey: pubkey.clone(),
+            witness: Witness::Signature(Signature::sign(&secret, &msg_hash)),
+            index: None,
+            challenge: challenge.clone(),
+            nullifier: false,
+            message_scheme: MessageScheme::default(),
+            siwe: None,
+            hide_r_point: false,
+        };
+        let proof =
+            DesignatedAttestProof::construct(&mut rng, input(), &ring, &verifier_key).unwrap();
+        assert_eq!(
+            proof.verify(&mut rng, &verifier_key, &ring),
+            Ok(&challenge.claims)
+        );
+        assert_eq!(
+            proof.verify(&mut rng, &other_key, &ring),
+            Err("failed to verify designated proof".to_string())
+        );
+        // the inner proof alone does not verify
+        assert!(proof.proof.verify(&mut rng, &ring).is_err());
+
+        // the verifier may generate a proof for any member
+        let member = Point::<Secp256k1>::GENERATOR.to_affine();
+        ring[1] = member.x().to_cycle_scalar();
+        let simulated = DesignatedAttestProof::simulate(
+            &mut rng,
+            verifier_secret_key,
+            member,
+            challenge.clone(),
+            MessageScheme::default(),
+            &ring,
+        )
+        .unwrap();
+        let simulated = DesignatedAttestProof::<Secp256k1, Tom256k1>::try_from_slice(
+            &simulated.try_to_vec().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            simulated.verify(&mut rng, &verifier_key, &ring),
+            Ok(&challenge.claims)
+        );
+        assert!(simulated.verify(&mut rng, &other_key, &ring).is_err());
+
+        let mut hidden = input();
+        hidden.hide_r_point = true;
+        assert_eq!(
+            DesignatedAttestProof::construct(&mut rng, hidden, &ring, &verifier_key).err(),
+            Some("designated proofs require a signature with a public R".to_string())
+        );
+        let mut with_nullifier = input();
+        with_nullifier.nullifier = true;
+        assert_eq!(
+            DesignatedAttestProof::construct(&mut rng, with_nullifier, &ring, &verifier_key).err(),
+            Some("nullifiers are not supported for designated proofs".to_string())
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use super::multiplication::{
+    MultiplicationCommitment, MultiplicationResponse, MultiplicationWitness,
+};
+use super::non_membership::inverse_statement;
+use super::sigma::{SigmaProtocol, Transcript};
+use crate::arithmetic::multimult::MultiMult;
+use crate::arithmetic::{Modular, Point, Scalar};
+use crate::curve::Curve;
+use crate::pedersen::*;
+use crate::rng::CryptoCoreRng;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Proof that the committed values are pairwise distinct.
+///
+/// For each pair `i < j` the commitment to `v_i - v_j` is derived from the
+/// commitments and the prover shows that it has an inverse, see
+/// [`NonMembershipProof`](super::NonMembershipProof).
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
+pub struct DistinctnessProof<C: Curve> {
+    commitments_to_inverses: Vec<Point<C>>,
+    commitment: Vec<MultiplicationCommitment<C>>,
+    response: Vec<MultiplicationResponse<C>>,
+}
+
+impl<C: Curve> DistinctnessProof<C> {
+    const HASH_ID: &'static [u8] = b"distinctness-proof";
+
+    pub fn construct<R: CryptoCoreRng>(
+        rng: &mut R,
+        pedersen_generator: &PedersenGenerator<C>,
+        commitments: &[PedersenCommitment<C>],
+        values: &[Scalar<C>],
+    ) -> Result<Self, String> {
+        if commitments.len() != values.len() {
+            return Err("invalid number of values".to_string());
+        }
+        // commitment to one with zero randomness
+        let commitment_to_one = PedersenCommitment::new(Point::<C>::GENERATOR, Scalar::<C>::ZERO);
+
+        let mut commitments_to_differences = Vec::new();
+        let mut commitments_to_inverses = Vec::new();
+        let mut witness = Vec::new();
+        for (i, j) in pairs(values.len()) {
+            let difference = values[i] - values[j];
+            if difference == Scalar::ZERO {
+                return Err("committed values are not distinct".to_string());
+            }
+            let inverse = difference.inverse();
+            let commitment_to_difference = PedersenCommitment::new(
+                commitments[i].commitment() - commitments[j].commitment(),
+                *commitments[i].randomness() - *commitments[j].randomness(),
+            );
+            let commitment_to_inverse = pedersen_generator.commit(rng, inverse);
+            witness.push(MultiplicationWitness::new(
+                &commitment_to_difference,
+                &commitment_to_inverse,
+                &commitment_to_one,
+                difference,
+                inverse,
+                Scalar::ONE,
+            ));
+            commitments_to_differences.push(commitment_to_difference.into_commitment());
+            commitments_to_inverses.push(commitment_to_inverse.into_commitment());
+        }
+
+        let statement = inverse_statement(
+            pedersen_generator,
+            &commitments_to_differences,
+            &commitments_to_inverses,
+            commitment_to_one.commitment(),
+        );
+        let (commitment, response) = statement.prove(rng, &witness, Transcript::new(Self::HASH_ID));
+
+        Ok(Self {
+            commitments_to_inverses,
+            commitment,
+            response,
+        })
+    }
+
+    pub fn verify<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        pedersen_generator: &PedersenGenerator<C>,
+        commitments: &[&Point<C>],
+    ) -> Result<(), String> {
+        let commitments_to_differences = pairs(commitments.len())
+            .map(|(i, j)| commitments[i] - commitments[j])
+            .collect::<Vec<_>>();
+        if self.commitments_to_inverses.len() != commitments_to_differences.len() {
+            return Err("invalid number of inverses".to_string());
+        }
+
+        let commitment_to_one = Point::<C>::GENERATOR;
+        let statement = inverse_statement(
+            pedersen_generator,
+            &commitments_to_differences,
+            &self.commitments_to_inverses,
+            &commitment_to_one,
+        );
+        let mut multimult = MultiMult::new();
+        statement.verify(
+            rng,
+            &self.commitment,
+            &self.response,
+            Transcript::new(Self::HASH_ID),
+            &mut multimult,
+        )?;
+
+        if multimult.evaluate().is_identity() {
+            Ok(())
+        } else {
+            Err("failed to verify distinctness".to_owned())
+        }
+    }
+}
+
+// index pairs (i, j) with i < j in lexicographic order
+fn pairs(len: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..len).flat_map(move |i| (i + 1..len).map(move |j| (i, j)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curve::Tom256k1;
+    use rand::rngs::StdRng;
+    use rand_core::SeedableRng;
+
+    #[test]
+    fn distinctness_proof() {
+        let mut rng = StdRng::from_seed([44; 32]);
+        let pedersen_generator = PedersenGenerator::<Tom256k1>::new(&mut rng);
+        let mut values = (0..4)
+            .map(|_| Scalar::<Tom256k1>::random(&mut rng))
+            .collect::<Vec<_>>();
+        let commitments = values
+            .iter()
+            .map(|value| pedersen_generator.commit(&mut rng, *value))
+            .collect::<Vec<_>>();
+        let points = commitments
+            .iter()
+            .map(PedersenCommitment::commitment)
+            .collect::<Vec<_>>();
+
+        let proof =
+            DistinctnessProof::construct(&mut rng, &pedersen_generator, &commitments, &values)
+                .unwrap();
+        assert_eq!(proof.verify(&mut rng, &pedersen_generator, &points), Ok(()));
+        assert_eq!(
+            proof.verify(&mut rng, &pedersen_generator, &points[1..]),
+            Err("invalid number of inverses".to_string())
+        );
+
+        // the proof is bound to the order of the commitments
+        let mut other_points = points.clone();
+        other_points.swap(0, 3);
+        assert_eq!(
+            proof.verify(&mut rng, &pedersen_generator, &other_points),
+            Err("failed to verify distinctness".to_string())
+        );
+
+        values[2] = values[0];
+        assert_eq!(
+            DistinctnessProof::construct(&mut rng, &pedersen_generator, &commitments, &values)
+                .err(),
+            Some("committed values are not distinct".to_string())
+        );
+    }
+}
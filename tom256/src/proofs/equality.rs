@@ -1,7 +1,7 @@
+use super::sigma::{SigmaProtocol, Transcript};
 use crate::arithmetic::multimult::{MultiMult, Relation};
-use crate::arithmetic::{Modular, Point, Scalar};
+use crate::arithmetic::{Point, Scalar};
 use crate::curve::Curve;
-use crate::hasher::PointHasher;
 use crate::pedersen::*;
 use crate::rng::CryptoCoreRng;
 
@@ -9,103 +9,153 @@ use borsh::{BorshDeserialize, BorshSerialize};
 
 use std::ops::Neg;
 
+/// Statement that two Pedersen commitments, possibly with different blinding
+/// generators, open to the same secret.
+#[derive(Clone, Copy)]
+pub struct EqualityStatement<'a, C: Curve> {
+    pub pedersen_generator_1: &'a PedersenGenerator<C>,
+    pub commitment_1: &'a Point<C>,
+    pub pedersen_generator_2: &'a PedersenGenerator<C>,
+    pub commitment_2: &'a Point<C>,
+}
+
+pub struct EqualityWitness<C: Curve> {
+    secret: Scalar<C>,
+    randomness_1: Scalar<C>,
+    randomness_2: Scalar<C>,
+}
+
+impl<C: Curve> EqualityWitness<C> {
+    pub fn new(
+        secret: Scalar<C>,
+        commitment_1: &PedersenCommitment<C>,
+        commitment_2: &PedersenCommitment<C>,
+    ) -> Self {
+        Self {
+            secret,
+            randomness_1: *commitment_1.randomness(),
+            randomness_2: *commitment_2.randomness(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, BorshDeserialize, BorshSerialize)]
-pub struct EqualityProof<C: Curve> {
+pub struct EqualityCommitment<C: Curve> {
     commitment_to_random_1: Point<C>,
     commitment_to_random_2: Point<C>,
+}
+
+#[derive(Clone, Debug, BorshDeserialize, BorshSerialize)]
+pub struct EqualityResponse<C: Curve> {
     mask_secret: Scalar<C>,
     mask_random_1: Scalar<C>,
     mask_random_2: Scalar<C>,
 }
 
-impl<C: Curve> EqualityProof<C> {
-    const HASH_ID: &'static [u8] = b"equality-proof";
+impl<'a, C: Curve> SigmaProtocol<C> for EqualityStatement<'a, C> {
+    type Witness = EqualityWitness<C>;
+    type Commitment = EqualityCommitment<C>;
+    type Response = EqualityResponse<C>;
+    type Nonce = (Scalar<C>, Scalar<C>, Scalar<C>);
 
-    pub fn construct<R: CryptoCoreRng>(
+    fn commit<R: CryptoCoreRng>(
+        &self,
         rng: &mut R,
-        pedersen_generator: &PedersenGenerator<C>,
-        commitment_1: &PedersenCommitment<C>,
-        commitment_2: &PedersenCommitment<C>,
-        secret: Scalar<C>,
-    ) -> Self {
+        _witness: &Self::Witness,
+    ) -> (Self::Commitment, Self::Nonce) {
         let random_scalar = Scalar::random(rng);
-        let commitment_to_random_1 = pedersen_generator.commit(rng, random_scalar);
-        let commitment_to_random_2 = pedersen_generator.commit(rng, random_scalar);
-
-        let mut hasher = PointHasher::new(Self::HASH_ID);
-        hasher.insert_points(&[
-            commitment_1.commitment(),
-            commitment_2.commitment(),
-            commitment_to_random_1.commitment(),
-            commitment_to_random_2.commitment(),
-        ]);
-        let challenge = hasher.finalize();
-        let challenge_scalar = Scalar::new(challenge);
-        let mask_secret = random_scalar - challenge_scalar * secret;
-        let mask_random_1 =
-            *commitment_to_random_1.randomness() - &challenge_scalar * commitment_1.randomness();
-        let mask_random_2 =
-            *commitment_to_random_2.randomness() - &challenge_scalar * commitment_2.randomness();
-
-        Self {
+        let commitment_to_random_1 = self.pedersen_generator_1.commit(rng, random_scalar);
+        let commitment_to_random_2 = self.pedersen_generator_2.commit(rng, random_scalar);
+        let nonce = (
+            random_scalar,
+            *commitment_to_random_1.randomness(),
+            *commitment_to_random_2.randomness(),
+        );
+        let commitment = EqualityCommitment {
             commitment_to_random_1: commitment_to_random_1.into_commitment(),
             commitment_to_random_2: commitment_to_random_2.into_commitment(),
-            mask_secret,
-            mask_random_1,
-            mask_random_2,
+        };
+        (commitment, nonce)
+    }
+
+    fn absorb(&self, commitment: &Self::Commitment, transcript: &mut Transcript) {
+        transcript.append_points(&[
+            self.pedersen_generator_1.generator(),
+            self.commitment_1,
+            self.pedersen_generator_2.generator(),
+            self.commitment_2,
+            &commitment.commitment_to_random_1,
+            &commitment.commitment_to_random_2,
+        ]);
+    }
+
+    fn respond(
+        &self,
+        witness: &Self::Witness,
+        nonce: Self::Nonce,
+        challenge: Scalar<C>,
+    ) -> Self::Response {
+        EqualityResponse {
+            mask_secret: nonce.0 - challenge * witness.secret,
+            mask_random_1: nonce.1 - challenge * witness.randomness_1,
+            mask_random_2: nonce.2 - challenge * witness.randomness_2,
         }
     }
 
-    pub fn aggregate<R: CryptoCoreRng>(
+    fn simulate<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        challenge: Scalar<C>,
+    ) -> (Self::Commitment, Self::Response) {
+        let response = EqualityResponse {
+            mask_secret: Scalar::random(rng),
+            mask_random_1: Scalar::random(rng),
+            mask_random_2: Scalar::random(rng),
+        };
+        let commitment = EqualityCommitment {
+            commitment_to_random_1: &self.pedersen_generator_1.generator().double_mul(
+                &response.mask_random_1,
+                &Point::<C>::GENERATOR,
+                &response.mask_secret,
+            ) + &(self.commitment_1 * challenge),
+            commitment_to_random_2: &self.pedersen_generator_2.generator().double_mul(
+                &response.mask_random_2,
+                &Point::<C>::GENERATOR,
+                &response.mask_secret,
+            ) + &(self.commitment_2 * challenge),
+        };
+        (commitment, response)
+    }
+
+    fn verify_into<R: CryptoCoreRng>(
         &self,
         rng: &mut R,
-        pedersen_generator: &PedersenGenerator<C>,
-        commitment_1: &Point<C>,
-        commitment_2: &Point<C>,
+        commitment: &Self::Commitment,
+        challenge: Scalar<C>,
+        response: &Self::Response,
         multimult: &mut MultiMult<C>,
-    ) {
-        let mut hasher = PointHasher::new(Self::HASH_ID);
-        hasher.insert_points(&[
-            commitment_1,
-            commitment_2,
-            &self.commitment_to_random_1,
-            &self.commitment_to_random_2,
-        ]);
-        let challenge = hasher.finalize();
-        let challenge_scalar = Scalar::new(challenge);
+    ) -> Result<(), String> {
         let mut relation_1 = Relation::new();
         let mut relation_2 = Relation::new();
-        relation_1.insert(Point::<C>::GENERATOR, self.mask_secret);
-        relation_1.insert(pedersen_generator.generator().clone(), self.mask_random_1);
-        relation_1.insert(commitment_1.clone(), challenge_scalar);
-        relation_1.insert((&self.commitment_to_random_1).neg(), Scalar::ONE);
+        relation_1.insert(Point::<C>::GENERATOR, response.mask_secret);
+        relation_1.insert(
+            self.pedersen_generator_1.generator().clone(),
+            response.mask_random_1,
+        );
+        relation_1.insert(self.commitment_1.clone(), challenge);
+        relation_1.insert((&commitment.commitment_to_random_1).neg(), Scalar::ONE);
 
-        relation_2.insert(Point::<C>::GENERATOR, self.mask_secret);
-        relation_2.insert(pedersen_generator.generator().clone(), self.mask_random_2);
-        relation_2.insert(commitment_2.clone(), challenge_scalar);
-        relation_2.insert((&self.commitment_to_random_2).neg(), Scalar::ONE);
+        relation_2.insert(Point::<C>::GENERATOR, response.mask_secret);
+        relation_2.insert(
+            self.pedersen_generator_2.generator().clone(),
+            response.mask_random_2,
+        );
+        relation_2.insert(self.commitment_2.clone(), challenge);
+        relation_2.insert((&commitment.commitment_to_random_2).neg(), Scalar::ONE);
 
         relation_1.drain(rng, multimult);
         relation_2.drain(rng, multimult);
-    }
-
-    #[cfg(test)]
-    pub fn verify<R: CryptoCoreRng>(
-        &self,
-        rng: &mut R,
-        pedersen_generator: &PedersenGenerator<C>,
-        commitment_1: &Point<C>,
-        commitment_2: &Point<C>,
-    ) -> bool {
-        let mut multimult = MultiMult::new();
-        self.aggregate(
-            rng,
-            pedersen_generator,
-            commitment_1,
-            commitment_2,
-            &mut multimult,
-        );
-        multimult.evaluate().is_identity()
+        Ok(())
     }
 }
 
@@ -116,6 +166,27 @@ mod test {
     use rand::rngs::StdRng;
     use rand_core::SeedableRng;
 
+    const HASH_ID: &[u8] = b"equality-proof";
+
+    fn verify(
+        statement: EqualityStatement<Tom256k1>,
+        commitment: &EqualityCommitment<Tom256k1>,
+        response: &EqualityResponse<Tom256k1>,
+    ) -> bool {
+        let mut rng = StdRng::from_seed([2; 32]);
+        let mut multimult = MultiMult::new();
+        statement
+            .verify(
+                &mut rng,
+                commitment,
+                response,
+                Transcript::new(HASH_ID),
+                &mut multimult,
+            )
+            .is_ok()
+            && multimult.evaluate().is_identity()
+    }
+
     #[test]
     fn valid_equality_proof() {
         let mut rng = StdRng::from_seed([1; 32]);
@@ -124,20 +195,16 @@ mod test {
         let secret_commitment_1 = pedersen_generator.commit(&mut rng, secret);
         let secret_commitment_2 = pedersen_generator.commit(&mut rng, secret);
 
-        let equality_proof = EqualityProof::construct(
-            &mut rng,
-            &pedersen_generator,
-            &secret_commitment_1,
-            &secret_commitment_2,
-            secret,
-        );
+        let statement = EqualityStatement {
+            pedersen_generator_1: &pedersen_generator,
+            commitment_1: secret_commitment_1.commitment(),
+            pedersen_generator_2: &pedersen_generator,
+            commitment_2: secret_commitment_2.commitment(),
+        };
+        let witness = EqualityWitness::new(secret, &secret_commitment_1, &secret_commitment_2);
+        let (commitment, response) = statement.prove(&mut rng, &witness, Transcript::new(HASH_ID));
 
-        assert!(equality_proof.verify(
-            &mut rng,
-            &pedersen_generator,
-            secret_commitment_1.commitment(),
-            secret_commitment_2.commitment(),
-        ));
+        assert!(verify(statement, &commitment, &response));
     }
 
     #[test]
@@ -148,31 +215,32 @@ mod test {
         let secret_commitment_1 = pedersen_generator.commit(&mut rng, secret);
         let secret_commitment_2 = pedersen_generator.commit(&mut rng, secret);
 
-        let equality_proof = EqualityProof::construct(
-            &mut rng,
-            &pedersen_generator,
-            &secret_commitment_1,
-            &secret_commitment_2,
-            secret,
-        );
+        let statement = EqualityStatement {
+            pedersen_generator_1: &pedersen_generator,
+            commitment_1: secret_commitment_1.commitment(),
+            pedersen_generator_2: &pedersen_generator,
+            commitment_2: secret_commitment_2.commitment(),
+        };
+        let witness = EqualityWitness::new(secret, &secret_commitment_1, &secret_commitment_2);
+        let (commitment, response) = statement.prove(&mut rng, &witness, Transcript::new(HASH_ID));
 
         let invalid_pedersen_generator = PedersenGenerator::new(&mut rng);
-        assert!(!equality_proof.verify(
-            &mut rng,
-            &invalid_pedersen_generator,
-            secret_commitment_1.commitment(),
-            secret_commitment_2.commitment(),
-        ));
+        let invalid_statement = EqualityStatement {
+            pedersen_generator_1: &invalid_pedersen_generator,
+            pedersen_generator_2: &invalid_pedersen_generator,
+            ..statement
+        };
+        assert!(!verify(invalid_statement, &commitment, &response));
 
         let invalid_secret = Scalar::<Tom256k1>::random(&mut rng);
         let invalid_secret_commitment_1 = pedersen_generator.commit(&mut rng, invalid_secret);
         let invalid_secret_commitment_2 = pedersen_generator.commit(&mut rng, invalid_secret);
 
-        assert!(!equality_proof.verify(
-            &mut rng,
-            &pedersen_generator,
-            invalid_secret_commitment_1.commitment(),
-            invalid_secret_commitment_2.commitment(),
-        ));
+        let invalid_statement = EqualityStatement {
+            commitment_1: invalid_secret_commitment_1.commitment(),
+            commitment_2: invalid_secret_commitment_2.commitment(),
+            ..statement
+        };
+        assert!(!verify(invalid_statement, &commitment, &response));
     }
 }
@@ -1,13 +1,14 @@
+use super::session::Interactive;
 use crate::arithmetic::multimult::{MultiMult, Relation};
 use crate::arithmetic::AffinePoint;
-use crate::arithmetic::{Point, Scalar};
+use crate::arithmetic::{Modular, Point, Scalar};
 use crate::curve::{Curve, Cycle};
 use crate::hasher::PointHasher;
 use crate::pedersen::*;
 use crate::proofs::point_add::{PointAddCommitmentPoints, PointAddProof, PointAddSecrets};
 use crate::rng::CryptoCoreRng;
 
-use bigint::{Encoding, Integer, U256};
+use bigint::{Encoding, U256};
 use borsh::{BorshDeserialize, BorshSerialize};
 
 use std::ops::Neg;
@@ -30,14 +31,31 @@ pub enum ExpProofVariant<C: Curve, CC: Cycle<C>> {
     },
 }
 
-#[derive(BorshDeserialize, BorshSerialize)]
-pub struct SingleExpProof<C: Curve, CC: Cycle<C>> {
+/// Commitments of a single repetition: `A = g^alpha + h^r` on the base curve
+/// and the coordinates of `T = g^alpha` on the cycle curve.
+#[derive(Clone, BorshDeserialize, BorshSerialize)]
+pub struct ExpRoundCommitment<C: Curve, CC: Cycle<C>> {
     a: Point<C>,
     tx_p: Point<CC>,
     ty_p: Point<CC>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct SingleExpProof<C: Curve, CC: Cycle<C>> {
+    commitment: ExpRoundCommitment<C, CC>,
     variant: ExpProofVariant<C, CC>,
 }
 
+/// Secret state of a single repetition between the commitment and the
+/// response.
+pub struct ExpRound<C: Curve, CC: Cycle<C>> {
+    alpha: Scalar<C>,
+    r: Scalar<C>,
+    t: Point<C>,
+    tx: PedersenCommitment<CC>,
+    ty: PedersenCommitment<CC>,
+}
+
 #[derive(Clone)]
 pub struct ExpSecrets<C: Curve> {
     point: AffinePoint<C>,
@@ -100,6 +118,373 @@ impl<C: Curve, CC: Cycle<C>> ExpCommitmentPoints<C, CC> {
     }
 }
 
+impl<C: Curve, CC: Cycle<C>> ExpProofVariant<C, CC> {
+    // the challenge bit the variant answers
+    fn bit(&self) -> bool {
+        matches!(self, ExpProofVariant::Odd { .. })
+    }
+}
+
+impl<C: Curve, CC: Cycle<C>> ExpRoundCommitment<C, CC> {
+    fn absorb(&self, point_hasher: &mut PointHasher) {
+        point_hasher.insert_point(&self.a);
+        point_hasher.insert_point(&self.tx_p);
+        point_hasher.insert_point(&self.ty_p);
+    }
+}
+
+impl<C: Curve, CC: Cycle<C>> ExpRound<C, CC> {
+    fn new<R: CryptoCoreRng>(
+        rng: &mut R,
+        base_gen: &Point<C>,
+        pedersen: &PedersenCycle<C, CC>,
+    ) -> Result<(Self, ExpRoundCommitment<C, CC>), String> {
+        // exponent
+        let alpha = Scalar::random(rng);
+        // random r scalar
+        let r = Scalar::random(rng);
+        // T = g^alpha
+        let t = base_gen * alpha;
+        // A = g^alpha + h^r (essentially a commitment in the base curve)
+        let a = &t + &(pedersen.base().generator() * r);
+        let (tx, ty) = commit_coordinates(rng, pedersen, &t)?;
+        let commitment = ExpRoundCommitment {
+            a,
+            tx_p: tx.commitment().clone(),
+            ty_p: ty.commitment().clone(),
+        };
+        Ok((
+            Self {
+                alpha,
+                r,
+                t,
+                tx,
+                ty,
+            },
+            commitment,
+        ))
+    }
+
+    // answers an odd challenge bit by opening the commitments
+    fn open(self) -> ExpProofVariant<C, CC> {
+        ExpProofVariant::Odd {
+            alpha: self.alpha,
+            r: self.r,
+            tx_r: *self.tx.randomness(),
+            ty_r: *self.ty.randomness(),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn respond<R: CryptoCoreRng>(
+        self,
+        rng: &mut R,
+        bit: bool,
+        base_gen: &Point<C>,
+        pedersen: &PedersenCycle<C, CC>,
+        secrets: &ExpSecrets<C>,
+        commitments: &ExpCommitments<C, CC>,
+        q_point: Option<&Point<C>>,
+    ) -> Result<ExpProofVariant<C, CC>, String> {
+        if bit {
+            return Ok(self.open());
+        }
+
+        let z = self.alpha - secrets.exp;
+        let mut t1 = base_gen * z;
+        if let Some(pt) = q_point {
+            t1 += pt;
+        }
+
+        if t1.is_identity() {
+            return Err("intermediate value is identity".to_owned());
+        }
+
+        Ok(even_variant(
+            rng,
+            pedersen,
+            commitments,
+            &secrets.point,
+            t1,
+            self.t,
+            (&self.tx, &self.ty),
+            z,
+            self.r - (*commitments.exp.randomness()),
+        ))
+    }
+}
+
+/// Statement that the point whose coordinates are committed on the cycle
+/// curve is `exp·g - Q`, where `exp` is committed on the base curve.
+///
+/// It is the statement of an [`ExpProof`] run interactively, see
+/// [`ProverSession`](super::ProverSession). The verifier chooses the
+/// challenge bits itself and checks `security_param` of the `repetitions`
+/// committed by the prover.
+#[derive(Clone, Copy)]
+pub struct ExpStatement<'a, C: Curve, CC: Cycle<C>> {
+    pub base_gen: &'a Point<C>,
+    pub pedersen: &'a PedersenCycle<C, CC>,
+    pub commitments: &'a ExpCommitmentPoints<C, CC>,
+    pub q_point: Option<&'a Point<C>>,
+    pub repetitions: usize,
+    pub security_param: usize,
+}
+
+/// Witness of an [`ExpStatement`], the exponent and the point with the
+/// openings of their commitments.
+pub struct ExpWitness<C: Curve, CC: Cycle<C>> {
+    pub secrets: ExpSecrets<C>,
+    pub commitments: ExpCommitments<C, CC>,
+}
+
+/// Challenge of an interactive [`ExpStatement`]: the indices of the checked
+/// repetitions and their challenge bits.
+#[derive(Clone, Debug, BorshDeserialize, BorshSerialize)]
+pub struct ExpChallenge {
+    pub indices: Vec<u32>,
+    pub bits: Vec<bool>,
+}
+
+impl<'a, C: Curve, CC: Cycle<C>> ExpStatement<'a, C, CC> {
+    fn multimults(&self) -> (MultiMult<C>, MultiMult<CC>) {
+        let mut tom_multimult = MultiMult::<CC>::new();
+        let mut base_multimult = MultiMult::<C>::new();
+
+        tom_multimult.add_known(Point::<CC>::GENERATOR);
+        tom_multimult.add_known(self.pedersen.cycle().generator().clone());
+
+        base_multimult.add_known(self.base_gen.clone());
+        base_multimult.add_known(self.pedersen.base().generator().clone());
+        base_multimult.add_known(self.commitments.exp.clone());
+
+        (base_multimult, tom_multimult)
+    }
+
+    fn verify_round<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        commitment: &ExpRoundCommitment<C, CC>,
+        variant: &ExpProofVariant<C, CC>,
+        base_multimult: &mut MultiMult<C>,
+        tom_multimult: &mut MultiMult<CC>,
+    ) -> Result<(), String> {
+        let pedersen = self.pedersen;
+        match variant {
+            ExpProofVariant::Odd {
+                alpha,
+                r,
+                tx_r,
+                ty_r,
+            } => {
+                let t = self.base_gen.scalar_mul(alpha);
+                let mut relation_a = Relation::<C>::new();
+
+                relation_a.insert(t.clone(), Scalar::<C>::ONE);
+                relation_a.insert(pedersen.base().generator().clone(), *r);
+                relation_a.insert((&commitment.a).neg(), Scalar::<C>::ONE);
+
+                relation_a.drain(rng, base_multimult);
+
+                let coord_t: AffinePoint<C> = t.into();
+                if coord_t.is_identity() {
+                    return Err("intermediate value is identity".to_owned());
+                }
+
+                let sx = coord_t.x().to_cycle_scalar::<CC>();
+                let sy = coord_t.y().to_cycle_scalar::<CC>();
+
+                let mut relation_tx = Relation::new();
+                let mut relation_ty = Relation::new();
+
+                relation_tx.insert(Point::<CC>::GENERATOR, sx);
+                relation_tx.insert(pedersen.cycle().generator().clone(), *tx_r);
+                relation_tx.insert((&commitment.tx_p).neg(), Scalar::<CC>::ONE);
+
+                relation_ty.insert(Point::<CC>::GENERATOR, sy);
+                relation_ty.insert(pedersen.cycle().generator().clone(), *ty_r);
+                relation_ty.insert((&commitment.ty_p).neg(), Scalar::<CC>::ONE);
+
+                relation_tx.drain(rng, tom_multimult);
+                relation_ty.drain(rng, tom_multimult);
+            }
+            ExpProofVariant::Even {
+                z,
+                r,
+                add_proof,
+                t1_x,
+                t1_y,
+            } => {
+                let mut t = self.base_gen.scalar_mul(z);
+
+                let mut relation_a = Relation::<C>::new();
+                relation_a.insert(t.clone(), Scalar::<C>::ONE);
+                relation_a.insert(self.commitments.exp.clone(), Scalar::<C>::ONE);
+                relation_a.insert((&commitment.a).neg(), Scalar::<C>::ONE);
+                relation_a.insert(pedersen.base().generator().clone(), *r);
+
+                relation_a.drain(rng, base_multimult);
+
+                if let Some(pt) = self.q_point {
+                    t += pt;
+                }
+
+                let coord_t: AffinePoint<C> = t.clone().into();
+                if coord_t.is_identity() {
+                    return Err("intermediate value is identity".to_owned());
+                }
+
+                let sx = coord_t.x().to_cycle_scalar::<CC>();
+                let sy = coord_t.y().to_cycle_scalar::<CC>();
+
+                let t1_com_x = pedersen.cycle().commit_with_randomness(sx, *t1_x);
+                let t1_com_y = pedersen.cycle().commit_with_randomness(sy, *t1_y);
+
+                let point_add_commitments = PointAddCommitmentPoints::new(
+                    t1_com_x.into_commitment(),
+                    t1_com_y.into_commitment(),
+                    self.commitments.px.clone(),
+                    self.commitments.py.clone(),
+                    commitment.tx_p.clone(),
+                    commitment.ty_p.clone(),
+                );
+
+                add_proof.aggregate(
+                    rng,
+                    pedersen.cycle(),
+                    &point_add_commitments,
+                    tom_multimult,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn evaluate(base_multimult: MultiMult<C>, tom_multimult: MultiMult<CC>) -> Result<(), String> {
+        let tom_res = tom_multimult.evaluate();
+        let base_res = base_multimult.evaluate();
+
+        if !(tom_res.is_identity() && base_res.is_identity()) {
+            return Err("proof is invalid".to_owned());
+        }
+        Ok(())
+    }
+}
+
+impl<'a, C: Curve, CC: Cycle<C>> Interactive<C> for ExpStatement<'a, C, CC> {
+    type Witness = ExpWitness<C, CC>;
+    type Commitment = Vec<ExpRoundCommitment<C, CC>>;
+    type Challenge = ExpChallenge;
+    type Response = Vec<ExpProofVariant<C, CC>>;
+    type State = Vec<ExpRound<C, CC>>;
+
+    fn commit_message<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        _witness: &Self::Witness,
+    ) -> Result<(Self::Commitment, Self::State), String> {
+        let (rounds, commitments) = (0..self.repetitions)
+            .map(|_| ExpRound::new(rng, self.base_gen, self.pedersen))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .unzip();
+        Ok((commitments, rounds))
+    }
+
+    fn challenge_message<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        _commitment: &Self::Commitment,
+    ) -> Self::Challenge {
+        let indices = generate_indices(self.security_param, self.repetitions, rng);
+        ExpChallenge {
+            bits: indices.iter().map(|_| rng.next_u32() & 1 == 1).collect(),
+            indices: indices.into_iter().map(|i| i as u32).collect(),
+        }
+    }
+
+    fn respond_message<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        witness: &Self::Witness,
+        state: Self::State,
+        challenge: &Self::Challenge,
+    ) -> Result<Self::Response, String> {
+        if challenge.indices.len() != challenge.bits.len() {
+            return Err("invalid challenge".to_string());
+        }
+        // NOTE a repetition is answered at most once, answering both bits
+        // would reveal the exponent
+        let mut rounds = state.into_iter().map(Some).collect::<Vec<_>>();
+        challenge
+            .indices
+            .iter()
+            .zip(&challenge.bits)
+            .map(|(&index, &bit)| {
+                rounds
+                    .get_mut(index as usize)
+                    .and_then(Option::take)
+                    .ok_or_else(|| "invalid challenge".to_string())?
+                    .respond(
+                        rng,
+                        bit,
+                        self.base_gen,
+                        self.pedersen,
+                        &witness.secrets,
+                        &witness.commitments,
+                        self.q_point,
+                    )
+            })
+            .collect()
+    }
+
+    fn check<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        commitment: &Self::Commitment,
+        challenge: &Self::Challenge,
+        response: &Self::Response,
+    ) -> Result<(), String> {
+        if commitment.len() != self.repetitions {
+            return Err("invalid commitment length".to_string());
+        }
+        if challenge.indices.len() < self.security_param {
+            return Err("security level not achieved".to_owned());
+        }
+        if response.len() != challenge.indices.len() {
+            return Err("invalid response length".to_string());
+        }
+
+        let (mut base_multimult, mut tom_multimult) = self.multimults();
+        for ((&index, &bit), variant) in challenge.indices.iter().zip(&challenge.bits).zip(response)
+        {
+            if variant.bit() != bit {
+                return Err("challenge bit mismatch".to_string());
+            }
+            self.verify_round(
+                rng,
+                &commitment[index as usize],
+                variant,
+                &mut base_multimult,
+                &mut tom_multimult,
+            )?;
+        }
+        Self::evaluate(base_multimult, tom_multimult)
+    }
+}
+
+/// Share of the Fiat-Shamir challenge of an [`ExpProof`] taken by another
+/// proof in an OR composition, see
+/// [`DesignatedAttestProof`](super::DesignatedAttestProof).
+///
+/// The points of the other proof are hashed into the challenge and the
+/// challenge bits of the exp proof are taken from the hash minus the share,
+/// thus the prover may only choose the challenge of one of the two proofs.
+pub(super) struct ChallengeShare<'a, C: Curve> {
+    pub points: [&'a Point<C>; 2],
+    pub challenge: Scalar<C>,
+}
+
 #[derive(BorshDeserialize, BorshSerialize)]
 pub struct ExpProof<C: Curve, CC: Cycle<C>> {
     proofs: Vec<SingleExpProof<C, CC>>,
@@ -117,112 +502,180 @@ impl<CC: Cycle<C>, C: Curve> ExpProof<C, CC> {
         security_param: usize,
         q_point: Option<Point<C>>,
     ) -> Result<Self, String> {
-        let mut alpha_vec = Vec::<Scalar<C>>::with_capacity(security_param);
-        let mut r_vec = Vec::<Scalar<C>>::with_capacity(security_param);
-        let mut t_vec = Vec::<Point<C>>::with_capacity(security_param);
-        let mut a_vec = Vec::<Point<C>>::with_capacity(security_param);
-        let mut tx_vec = Vec::<PedersenCommitment<CC>>::with_capacity(security_param);
-        let mut ty_vec = Vec::<PedersenCommitment<CC>>::with_capacity(security_param);
-
-        let mut point_hasher = PointHasher::new(Self::HASH_ID);
-        point_hasher.insert_point(commitments.px.commitment());
-        point_hasher.insert_point(commitments.py.commitment());
-
-        for i in 0..security_param {
-            // exponent
-            alpha_vec.push(Scalar::random(rng));
-            // random r scalars
-            r_vec.push(Scalar::random(rng));
-            // T = g^alpha
-            t_vec.push(base_gen * alpha_vec[i]);
-            // A = g^alpha + h^r (essentially a commitment in the base curve)
-            a_vec.push(&t_vec[i] + &(pedersen.base().generator() * r_vec[i]));
-
-            let coord_t = t_vec[i].to_affine();
-            if coord_t.is_identity() {
-                return Err("intermediate value is identity".to_owned());
-            }
-            // commitment to Tx
-            tx_vec.push(pedersen.cycle().commit(rng, coord_t.x().to_cycle_scalar()));
-            // commitment to Ty
-            ty_vec.push(pedersen.cycle().commit(rng, coord_t.y().to_cycle_scalar()));
+        Self::construct_shared(
+            rng,
+            base_gen,
+            pedersen,
+            secrets,
+            commitments,
+            security_param,
+            q_point,
+            None,
+        )
+    }
 
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn construct_shared<R: CryptoCoreRng>(
+        rng: &mut R,
+        base_gen: &Point<C>,
+        pedersen: &PedersenCycle<C, CC>,
+        secrets: &ExpSecrets<C>,
+        commitments: &ExpCommitments<C, CC>,
+        security_param: usize,
+        q_point: Option<Point<C>>,
+        share: Option<&ChallengeShare<C>>,
+    ) -> Result<Self, String> {
+        let mut point_hasher = Self::hasher(
+            base_gen,
+            commitments.exp.commitment(),
+            q_point.as_ref(),
+            commitments.px.commitment(),
+            commitments.py.commitment(),
+            share.map(|share| share.points),
+        );
+
+        let mut rounds = Vec::with_capacity(security_param);
+        for _ in 0..security_param {
+            let (round, commitment) = ExpRound::new(rng, base_gen, pedersen)?;
             // update hasher with current points
-            point_hasher.insert_point(&a_vec[i]);
-            point_hasher.insert_point(tx_vec[i].commitment());
-            point_hasher.insert_point(ty_vec[i].commitment());
+            commitment.absorb(&mut point_hasher);
+            rounds.push((round, commitment));
         }
 
-        let mut challenge = point_hasher.finalize();
-        let mut all_exp_proofs = Vec::<SingleExpProof<C, CC>>::with_capacity(security_param);
+        let challenge = Self::challenge_bits(point_hasher.finalize(), share);
+        let all_exp_proofs = rounds
+            .into_iter()
+            .zip(padded_bits(challenge, security_param))
+            .map(|((round, commitment), bit)| {
+                let variant = round.respond(
+                    rng,
+                    bit,
+                    base_gen,
+                    pedersen,
+                    secrets,
+                    commitments,
+                    q_point.as_ref(),
+                )?;
+                Ok(SingleExpProof {
+                    commitment,
+                    variant,
+                })
+            })
+            .collect::<Result<_, String>>()?;
 
-        for (alpha, (a, (r, (t, (tx, ty))))) in alpha_vec.into_iter().zip(
-            a_vec.into_iter().zip(
-                r_vec.into_iter().zip(
-                    t_vec
-                        .into_iter()
-                        .zip(tx_vec.into_iter().zip(ty_vec.into_iter())),
-                ),
-            ),
-        ) {
-            if challenge.is_odd().into() {
-                let tx_r = *tx.randomness();
-                let ty_r = *ty.randomness();
-                all_exp_proofs.push(SingleExpProof {
-                    a,
-                    tx_p: tx.into_commitment(),
-                    ty_p: ty.into_commitment(),
-                    variant: ExpProofVariant::Odd {
-                        alpha,
-                        r,
-                        tx_r,
-                        ty_r,
-                    },
-                });
+        Ok(Self {
+            proofs: all_exp_proofs,
+        })
+    }
+
+    /// Simulates a proof for the given challenge without knowing the
+    /// exponent and returns it together with the hash scalar it is bound to.
+    ///
+    /// The repetitions answered with `z` commit to `T = z·g (+ Q) + P`
+    /// directly, which is only possible if the challenge is known in
+    /// advance. Thus the simulated proof is only valid if the rest of the
+    /// hash, `hash - challenge`, is taken by another proof through a
+    /// [`ChallengeShare`] bound to `share_points`.
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn simulate<R: CryptoCoreRng>(
+        rng: &mut R,
+        base_gen: &Point<C>,
+        pedersen: &PedersenCycle<C, CC>,
+        point: &AffinePoint<C>,
+        commitments: &ExpCommitments<C, CC>,
+        security_param: usize,
+        q_point: Option<Point<C>>,
+        share_points: [&Point<C>; 2],
+        challenge: Scalar<C>,
+    ) -> Result<(Self, Scalar<C>), String> {
+        let mut point_hasher = Self::hasher(
+            base_gen,
+            commitments.exp.commitment(),
+            q_point.as_ref(),
+            commitments.px.commitment(),
+            commitments.py.commitment(),
+            Some(share_points),
+        );
+
+        let mut all_exp_proofs = Vec::<SingleExpProof<C, CC>>::with_capacity(security_param);
+        for bit in padded_bits(*challenge.inner(), security_param) {
+            let proof = if bit {
+                let (round, commitment) = ExpRound::new(rng, base_gen, pedersen)?;
+                SingleExpProof {
+                    commitment,
+                    variant: round.open(),
+                }
             } else {
-                let z = alpha - secrets.exp;
-                let mut t1 = base_gen * z;
+                let z = Scalar::<C>::random(rng);
+                let r = Scalar::<C>::random(rng);
+                let z_point = base_gen * z;
+                let mut t1 = z_point.clone();
                 if let Some(pt) = q_point.as_ref() {
                     t1 += pt;
                 }
-
                 if t1.is_identity() {
                     return Err("intermediate value is identity".to_owned());
                 }
-
-                // Generate point add proof
-                let add_secret = PointAddSecrets::new(t1.into(), secrets.point.clone(), t.into());
-                // NOTE only commits t1 and uses existing commitments for the rest
-                let add_commitments = add_secret.commit_p_only(
-                    rng,
-                    pedersen.cycle(),
-                    commitments.px.clone(),
-                    commitments.py.clone(),
-                    tx.clone(),
-                    ty.clone(),
-                );
-                let add_proof =
-                    PointAddProof::construct(rng, pedersen.cycle(), &add_commitments, &add_secret);
-
-                all_exp_proofs.push(SingleExpProof {
-                    a,
-                    tx_p: tx.into_commitment(),
-                    ty_p: ty.into_commitment(),
-                    variant: ExpProofVariant::Even {
-                        z,
-                        r: r - (*commitments.exp.randomness()),
-                        t1_x: *add_commitments.px.randomness(),
-                        t1_y: *add_commitments.py.randomness(),
-                        add_proof,
+                // A = z·g + C_exp + h^r, exactly what the verifier checks
+                let a =
+                    &(&z_point + commitments.exp.commitment()) + &(pedersen.base().generator() * r);
+                let t = &t1 + &Point::from(point);
+                let (tx, ty) = commit_coordinates(rng, pedersen, &t)?;
+                let variant =
+                    even_variant(rng, pedersen, commitments, point, t1, t, (&tx, &ty), z, r);
+                SingleExpProof {
+                    commitment: ExpRoundCommitment {
+                        a,
+                        tx_p: tx.into_commitment(),
+                        ty_p: ty.into_commitment(),
                     },
-                });
-            }
+                    variant,
+                }
+            };
 
-            challenge >>= 1;
+            proof.commitment.absorb(&mut point_hasher);
+            all_exp_proofs.push(proof);
+        }
+
+        Ok((
+            Self {
+                proofs: all_exp_proofs,
+            },
+            Scalar::new(point_hasher.finalize()),
+        ))
+    }
+
+    // NOTE the statement is hashed as well so that the challenge is bound
+    // to the base point and the optional offset (e.g. the message hash)
+    fn hasher(
+        base_gen: &Point<C>,
+        exp: &Point<C>,
+        q_point: Option<&Point<C>>,
+        px: &Point<CC>,
+        py: &Point<CC>,
+        share_points: Option<[&Point<C>; 2]>,
+    ) -> PointHasher {
+        let mut point_hasher = PointHasher::new(Self::HASH_ID);
+        point_hasher.insert_point(base_gen);
+        point_hasher.insert_point(exp);
+        if let Some(pt) = q_point {
+            point_hasher.insert_point(pt);
+        }
+        point_hasher.insert_point(px);
+        point_hasher.insert_point(py);
+        for pt in share_points.iter().flatten() {
+            point_hasher.insert_point(pt);
+        }
+        point_hasher
+    }
+
+    // with a share the bits are taken from the hash minus the share modulo
+    // the curve order, otherwise from the hash itself
+    fn challenge_bits(hash: U256, share: Option<&ChallengeShare<C>>) -> U256 {
+        match share {
+            Some(share) => *(Scalar::<C>::new(hash) - share.challenge).inner(),
+            None => hash,
         }
-        Ok(Self {
-            proofs: all_exp_proofs,
-        })
     }
 
     pub fn verify<R: CryptoCoreRng>(
@@ -233,140 +686,131 @@ impl<CC: Cycle<C>, C: Curve> ExpProof<C, CC> {
         commitments: &ExpCommitmentPoints<C, CC>,
         security_param: usize,
         q_point: Option<Point<C>>,
+    ) -> Result<(), String> {
+        self.verify_shared(
+            rng,
+            base_gen,
+            pedersen,
+            commitments,
+            security_param,
+            q_point,
+            None,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn verify_shared<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        base_gen: &Point<C>,
+        pedersen: &PedersenCycle<C, CC>,
+        commitments: &ExpCommitmentPoints<C, CC>,
+        security_param: usize,
+        q_point: Option<Point<C>>,
+        share: Option<&ChallengeShare<C>>,
     ) -> Result<(), String> {
         if security_param > self.proofs.len() {
             return Err("security level not achieved".to_owned());
         }
+        // NOTE the challenge only covers `security_param` rounds, the bits of
+        // any further round would be known to the prover in advance
+        if security_param < self.proofs.len() {
+            return Err("too many repetitions".to_owned());
+        }
 
-        let mut tom_multimult = MultiMult::<CC>::new();
-        let mut base_multimult = MultiMult::<C>::new();
-
-        tom_multimult.add_known(Point::<CC>::GENERATOR);
-        tom_multimult.add_known(pedersen.cycle().generator().clone());
-
-        base_multimult.add_known(base_gen.clone());
-        base_multimult.add_known(pedersen.base().generator().clone());
-        base_multimult.add_known(commitments.exp.clone());
-
-        let mut point_hasher = PointHasher::new(Self::HASH_ID);
-        point_hasher.insert_point(&commitments.px);
-        point_hasher.insert_point(&commitments.py);
-
-        for i in 0..security_param {
-            point_hasher.insert_point(&self.proofs[i].a);
-            point_hasher.insert_point(&self.proofs[i].tx_p);
-            point_hasher.insert_point(&self.proofs[i].ty_p);
+        let statement = ExpStatement {
+            base_gen,
+            pedersen,
+            commitments,
+            q_point: q_point.as_ref(),
+            repetitions: self.proofs.len(),
+            security_param,
+        };
+        let (mut base_multimult, mut tom_multimult) = statement.multimults();
+
+        let mut point_hasher = Self::hasher(
+            base_gen,
+            &commitments.exp,
+            q_point.as_ref(),
+            &commitments.px,
+            &commitments.py,
+            share.map(|share| share.points),
+        );
+
+        for proof in &self.proofs[..security_param] {
+            proof.commitment.absorb(&mut point_hasher);
         }
-        let challenge = point_hasher.finalize();
+        let challenge = Self::challenge_bits(point_hasher.finalize(), share);
 
         let indices = generate_indices(security_param, self.proofs.len(), rng);
         let challenge_bits = padded_bits(challenge, self.proofs.len());
 
         for i in indices.into_iter() {
-            match &self.proofs[i].variant {
-                ExpProofVariant::Odd {
-                    alpha,
-                    r,
-                    tx_r,
-                    ty_r,
-                } => {
-                    if !challenge_bits[i] {
-                        return Err("challenge hash mismatch".to_owned());
-                    }
-
-                    let t = base_gen.scalar_mul(alpha);
-                    let mut relation_a = Relation::<C>::new();
-
-                    relation_a.insert(t.clone(), Scalar::<C>::ONE);
-                    relation_a.insert(pedersen.base().generator().clone(), *r);
-                    relation_a.insert((&self.proofs[i].a).neg(), Scalar::<C>::ONE);
-
-                    relation_a.drain(rng, &mut base_multimult);
-
-                    let coord_t: AffinePoint<C> = t.into();
-                    if coord_t.is_identity() {
-                        return Err("intermediate value is identity".to_owned());
-                    }
-
-                    let sx = coord_t.x().to_cycle_scalar::<CC>();
-                    let sy = coord_t.y().to_cycle_scalar::<CC>();
-
-                    let mut relation_tx = Relation::new();
-                    let mut relation_ty = Relation::new();
-
-                    relation_tx.insert(Point::<CC>::GENERATOR, sx);
-                    relation_tx.insert(pedersen.cycle().generator().clone(), *tx_r);
-                    relation_tx.insert((&self.proofs[i].tx_p).neg(), Scalar::<CC>::ONE);
-
-                    relation_ty.insert(Point::<CC>::GENERATOR, sy);
-                    relation_ty.insert(pedersen.cycle().generator().clone(), *ty_r);
-                    relation_ty.insert((&self.proofs[i].ty_p).neg(), Scalar::<CC>::ONE);
-
-                    relation_tx.drain(rng, &mut tom_multimult);
-                    relation_ty.drain(rng, &mut tom_multimult);
-                }
-                ExpProofVariant::Even {
-                    z,
-                    r,
-                    add_proof,
-                    t1_x,
-                    t1_y,
-                } => {
-                    if challenge_bits[i] {
-                        return Err("challenge hash mismatch".to_owned());
-                    }
-
-                    let mut t = base_gen.scalar_mul(z);
-
-                    let mut relation_a = Relation::<C>::new();
-                    relation_a.insert(t.clone(), Scalar::<C>::ONE);
-                    relation_a.insert(commitments.exp.clone(), Scalar::<C>::ONE);
-                    relation_a.insert((&self.proofs[i].a).neg(), Scalar::<C>::ONE);
-                    relation_a.insert(pedersen.base().generator().clone(), *r);
-
-                    relation_a.drain(rng, &mut base_multimult);
-
-                    if let Some(pt) = q_point.as_ref() {
-                        t += pt;
-                    }
-
-                    let coord_t: AffinePoint<C> = t.clone().into();
-                    if coord_t.is_identity() {
-                        return Err("intermediate value is identity".to_owned());
-                    }
-
-                    let sx = coord_t.x().to_cycle_scalar::<CC>();
-                    let sy = coord_t.y().to_cycle_scalar::<CC>();
-
-                    let t1_com_x = pedersen.cycle().commit_with_randomness(sx, *t1_x);
-                    let t1_com_y = pedersen.cycle().commit_with_randomness(sy, *t1_y);
-
-                    let point_add_commitments = PointAddCommitmentPoints::new(
-                        t1_com_x.into_commitment(),
-                        t1_com_y.into_commitment(),
-                        commitments.px.clone(),
-                        commitments.py.clone(),
-                        self.proofs[i].tx_p.clone(),
-                        self.proofs[i].ty_p.clone(),
-                    );
-
-                    add_proof.aggregate(
-                        rng,
-                        pedersen.cycle(),
-                        &point_add_commitments,
-                        &mut tom_multimult,
-                    );
-                }
+            if self.proofs[i].variant.bit() != challenge_bits[i] {
+                return Err("challenge hash mismatch".to_owned());
             }
+            statement.verify_round(
+                rng,
+                &self.proofs[i].commitment,
+                &self.proofs[i].variant,
+                &mut base_multimult,
+                &mut tom_multimult,
+            )?;
         }
 
-        let tom_res = tom_multimult.evaluate();
-        let base_res = base_multimult.evaluate();
+        ExpStatement::evaluate(base_multimult, tom_multimult)
+    }
+}
 
-        if !(tom_res.is_identity() && base_res.is_identity()) {
-            return Err("proof is invalid".to_owned());
-        }
-        Ok(())
+fn commit_coordinates<C: Curve, CC: Cycle<C>, R: CryptoCoreRng>(
+    rng: &mut R,
+    pedersen: &PedersenCycle<C, CC>,
+    t: &Point<C>,
+) -> Result<(PedersenCommitment<CC>, PedersenCommitment<CC>), String> {
+    let coord_t = t.to_affine();
+    if coord_t.is_identity() {
+        return Err("intermediate value is identity".to_owned());
+    }
+    Ok((
+        // commitment to Tx
+        pedersen.cycle().commit(rng, coord_t.x().to_cycle_scalar()),
+        // commitment to Ty
+        pedersen.cycle().commit(rng, coord_t.y().to_cycle_scalar()),
+    ))
+}
+
+// answers an even challenge bit by proving T = T1 + P with the point add
+// proof
+#[allow(clippy::too_many_arguments)]
+fn even_variant<C: Curve, CC: Cycle<C>, R: CryptoCoreRng>(
+    rng: &mut R,
+    pedersen: &PedersenCycle<C, CC>,
+    commitments: &ExpCommitments<C, CC>,
+    point: &AffinePoint<C>,
+    t1: Point<C>,
+    t: Point<C>,
+    (tx, ty): (&PedersenCommitment<CC>, &PedersenCommitment<CC>),
+    z: Scalar<C>,
+    r: Scalar<C>,
+) -> ExpProofVariant<C, CC> {
+    let add_secret = PointAddSecrets::new(t1.into(), point.clone(), t.into());
+    // NOTE only commits t1 and uses existing commitments for the rest
+    let add_commitments = add_secret.commit_p_only(
+        rng,
+        pedersen.cycle(),
+        commitments.px.clone(),
+        commitments.py.clone(),
+        tx.clone(),
+        ty.clone(),
+    );
+    let add_proof = PointAddProof::construct(rng, pedersen.cycle(), &add_commitments, &add_secret);
+
+    ExpProofVariant::Even {
+        z,
+        r,
+        t1_x: *add_commitments.px.randomness(),
+        t1_y: *add_commitments.py.randomness(),
+        add_proof,
     }
 }
 
@@ -489,6 +933,65 @@ mod test {
             .is_ok())
     }
 
+    #[test]
+    fn exp_proof_repetitions() {
+        let mut rng = StdRng::from_seed([3; 32]);
+        let base_gen = Point::<Secp256k1>::GENERATOR;
+        let pedersen = PedersenCycle::<Secp256k1, Tom256k1>::new(&mut rng);
+
+        let exponent = Scalar::<Secp256k1>::random(&mut rng);
+        let result = Point::<Secp256k1>::GENERATOR.scalar_mul(&exponent);
+
+        let secrets = ExpSecrets::new(exponent, result.into());
+        let commitments = secrets.commit(&mut rng, &pedersen);
+        let commitment_points = commitments.clone().into_commitments();
+
+        let construct = |rng: &mut StdRng, security_param| {
+            ExpProof::construct(
+                rng,
+                &base_gen,
+                &pedersen,
+                &secrets,
+                &commitments,
+                security_param,
+                None,
+            )
+            .unwrap()
+        };
+        let verify = |rng: &mut StdRng, proof: &ExpProof<Secp256k1, Tom256k1>, security_param| {
+            proof.verify(
+                rng,
+                &base_gen,
+                &pedersen,
+                &commitment_points,
+                security_param,
+                None,
+            )
+        };
+
+        let exp_proof = construct(&mut rng, 12);
+        assert!(verify(&mut rng, &exp_proof, 12).is_ok());
+        assert_eq!(
+            verify(&mut rng, &exp_proof, 13),
+            Err("security level not achieved".to_string())
+        );
+        // rounds beyond the security parameter are not covered by the hash
+        assert_eq!(
+            verify(&mut rng, &exp_proof, 10),
+            Err("too many repetitions".to_string())
+        );
+
+        // nor are rounds beyond the 256 bits of the challenge accepted
+        let mut oversized = construct(&mut rng, 12);
+        while oversized.proofs.len() <= 256 {
+            oversized.proofs.extend(construct(&mut rng, 12).proofs);
+        }
+        assert_eq!(
+            verify(&mut rng, &oversized, 12),
+            Err("too many repetitions".to_string())
+        );
+    }
+
     #[test]
     fn exp_proof_valid_with_q() {
         let mut rng = StdRng::from_seed([2; 32]);
@@ -0,0 +1,292 @@
+use super::exp::{ExpCommitmentPoints, ExpCommitments, ExpProof, ExpSecrets};
+use super::multiplication::MultiplicationProof;
+use super::point_add::{
+    PointAddCommitmentPoints, PointAddCommitments, PointAddProof, PointAddSecrets,
+};
+use super::reduction::ReductionProof;
+use super::{canonical_signature_point, SEC_PARAM};
+use crate::arithmetic::multimult::MultiMult;
+use crate::arithmetic::{AffinePoint, Modular, Point, Scalar};
+use crate::curve::{Curve, Cycle};
+use crate::parse::Signature;
+use crate::pedersen::{PedersenCommitment, PedersenCycle};
+use crate::rng::CryptoCoreRng;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// ECDSA signature proof that keeps the `R` point of the signature hidden.
+///
+/// Only `B = b·R` is published for a random blinding factor `b`, which is
+/// independent of the signature. With `β = 1/b`, `z1 = z/r` and `e = s1·β`
+/// the proof shows that
+///
+/// - the committed point `R` equals `β·B`,
+/// - the committed points `V` and `W` equal `e·B` and `z1·G`,
+/// - `pk + W = V`, i.e. `pk = s1·R - z1·G`,
+/// - `z1·r = z` for the committed scalar `r`,
+/// - `r` is the x coordinate of `R` reduced modulo `n`, see [`ReductionProof`].
+///
+/// Thus the prover knows the signature `(r, e·r/β)` over `z`, while the only
+/// public data related to it is the message hash.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct HiddenSignatureProof<C: Curve, CC: Cycle<C>> {
+    blinded_r_point: Point<C>,
+    r_commitments: ExpCommitmentPoints<C, CC>, // β, Rx, Ry
+    w_commitments: ExpCommitmentPoints<C, CC>, // z1, Wx, Wy
+    commitment_to_v_x: Point<CC>,
+    commitment_to_v_y: Point<CC>,
+    r_proof: ExpProof<C, CC>,
+    v_proof: ExpProof<C, CC>,
+    w_proof: ExpProof<C, CC>,
+    add_proof: PointAddProof<CC, C>,
+    reduction_proof: ReductionProof<C, CC>,
+    multiplication_proof: MultiplicationProof<C>,
+}
+
+impl<C: Curve, CC: Cycle<C>> HiddenSignatureProof<C, CC> {
+    /// Generates the proof and returns it together with the commitments to
+    /// `e` and the public key coordinates.
+    #[allow(clippy::too_many_arguments)]
+    pub fn construct<R: CryptoCoreRng>(
+        rng: &mut R,
+        pedersen: &PedersenCycle<C, CC>,
+        msg_hash: Scalar<C>,
+        signature: &Signature<C>,
+        pubkey: &AffinePoint<C>,
+        commitment_to_pk_x: PedersenCommitment<CC>,
+        commitment_to_pk_y: PedersenCommitment<CC>,
+    ) -> Result<(Self, ExpCommitmentPoints<C, CC>), String> {
+        let (r_point, s1) = canonical_signature_point(msg_hash, signature, pubkey);
+        let r_point_affine = r_point.to_affine();
+        let blinding = Scalar::<C>::random(rng);
+        let blinded_r_point = &r_point * blinding;
+        let beta = blinding.inverse();
+        let z1 = signature.r.inverse() * msg_hash;
+        let e = s1 * beta;
+        let w_point = (&Point::<C>::GENERATOR * z1).to_affine();
+        let v_point = (&blinded_r_point * e).to_affine();
+
+        // R = β·B with Rx committed through the reduction proof
+        let (reduction_proof, commitment_to_r_x, commitment_to_r) =
+            ReductionProof::construct(rng, pedersen, r_point_affine.x().to_cycle_scalar());
+        let r_commitments = ExpCommitments {
+            px: commitment_to_r_x,
+            py: pedersen
+                .cycle()
+                .commit(rng, r_point_affine.y().to_cycle_scalar()),
+            exp: pedersen
+                .base()
+                .commit_with_generator(rng, beta, &blinded_r_point),
+        };
+        let r_proof = ExpProof::construct(
+            rng,
+            &blinded_r_point,
+            pedersen,
+            &ExpSecrets::new(beta, r_point_affine),
+            &r_commitments,
+            SEC_PARAM,
+            None,
+        )?;
+
+        // V = e·B
+        let v_secrets = ExpSecrets::new(e, v_point.clone());
+        let v_commitments = ExpCommitments {
+            px: pedersen.cycle().commit(rng, v_point.x().to_cycle_scalar()),
+            py: pedersen.cycle().commit(rng, v_point.y().to_cycle_scalar()),
+            exp: pedersen
+                .base()
+                .commit_with_generator(rng, e, &blinded_r_point),
+        };
+        let v_proof = ExpProof::construct(
+            rng,
+            &blinded_r_point,
+            pedersen,
+            &v_secrets,
+            &v_commitments,
+            SEC_PARAM,
+            None,
+        )?;
+
+        // W = z1·G
+        let w_secrets = ExpSecrets::new(z1, w_point.clone());
+        let w_commitments = w_secrets.commit(rng, pedersen);
+        let w_proof = ExpProof::construct(
+            rng,
+            &Point::<C>::GENERATOR,
+            pedersen,
+            &w_secrets,
+            &w_commitments,
+            SEC_PARAM,
+            None,
+        )?;
+
+        // pk + W = V
+        let add_commitments = PointAddCommitments {
+            px: commitment_to_pk_x.clone(),
+            py: commitment_to_pk_y.clone(),
+            qx: w_commitments.px.clone(),
+            qy: w_commitments.py.clone(),
+            rx: v_commitments.px.clone(),
+            ry: v_commitments.py.clone(),
+        };
+        let add_proof = PointAddProof::construct(
+            rng,
+            pedersen.cycle(),
+            &add_commitments,
+            &PointAddSecrets::new(pubkey.clone(), w_point, v_point),
+        );
+
+        // z1·r = z
+        let commitment_to_msg_hash =
+            PedersenCommitment::new(&Point::<C>::GENERATOR * msg_hash, Scalar::<C>::ZERO);
+        let multiplication_proof = MultiplicationProof::construct(
+            rng,
+            pedersen.base(),
+            &w_commitments.exp,
+            &commitment_to_r,
+            &commitment_to_msg_hash,
+            z1,
+            signature.r,
+            msg_hash,
+        );
+
+        let exp_commitments = ExpCommitmentPoints::new(
+            v_commitments.exp.into_commitment(),
+            commitment_to_pk_x.into_commitment(),
+            commitment_to_pk_y.into_commitment(),
+        );
+        let proof = Self {
+            blinded_r_point,
+            r_commitments: r_commitments.into_commitments(),
+            w_commitments: w_commitments.into_commitments(),
+            commitment_to_v_x: v_commitments.px.into_commitment(),
+            commitment_to_v_y: v_commitments.py.into_commitment(),
+            r_proof,
+            v_proof,
+            w_proof,
+            add_proof,
+            reduction_proof,
+            multiplication_proof,
+        };
+        Ok((proof, exp_commitments))
+    }
+
+    pub fn verify<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        pedersen: &PedersenCycle<C, CC>,
+        msg_hash: Scalar<C>,
+        commitments: &ExpCommitmentPoints<C, CC>,
+    ) -> Result<(), String> {
+        if self.blinded_r_point.is_identity() {
+            return Err("blinded R is at infinity".to_string());
+        }
+
+        self.r_proof.verify(
+            rng,
+            &self.blinded_r_point,
+            pedersen,
+            &self.r_commitments,
+            SEC_PARAM,
+            None,
+        )?;
+        let v_commitments = ExpCommitmentPoints::new(
+            commitments.exp.clone(),
+            self.commitment_to_v_x.clone(),
+            self.commitment_to_v_y.clone(),
+        );
+        self.v_proof.verify(
+            rng,
+            &self.blinded_r_point,
+            pedersen,
+            &v_commitments,
+            SEC_PARAM,
+            None,
+        )?;
+        self.w_proof.verify(
+            rng,
+            &Point::<C>::GENERATOR,
+            pedersen,
+            &self.w_commitments,
+            SEC_PARAM,
+            None,
+        )?;
+
+        let mut cycle_multimult = MultiMult::new();
+        let add_commitments = PointAddCommitmentPoints::new(
+            commitments.px.clone(),
+            commitments.py.clone(),
+            self.w_commitments.px.clone(),
+            self.w_commitments.py.clone(),
+            self.commitment_to_v_x.clone(),
+            self.commitment_to_v_y.clone(),
+        );
+        self.add_proof.aggregate(
+            rng,
+            pedersen.cycle(),
+            &add_commitments,
+            &mut cycle_multimult,
+        )?;
+        if !cycle_multimult.evaluate().is_identity() {
+            return Err("point addition proof failed".to_string());
+        }
+
+        let commitment_to_r = self
+            .reduction_proof
+            .verify(rng, pedersen, &self.r_commitments.px)?;
+        let mut base_multimult = MultiMult::new();
+        self.multiplication_proof.aggregate(
+            rng,
+            pedersen.base(),
+            &self.w_commitments.exp,
+            &commitment_to_r,
+            &(&Point::<C>::GENERATOR * msg_hash),
+            &mut base_multimult,
+        )?;
+        if !base_multimult.evaluate().is_identity() {
+            return Err("multiplication proof failed".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curve::{Secp256k1, Tom256k1};
+    use rand::rngs::StdRng;
+    use rand_core::SeedableRng;
+
+    #[test]
+    fn hidden_signature() {
+        let mut rng = StdRng::from_seed([24; 32]);
+        let pedersen = PedersenCycle::<Secp256k1, Tom256k1>::new(&mut rng);
+        let secret_key = Scalar::<Secp256k1>::random(&mut rng);
+        let pubkey = (&Point::<Secp256k1>::GENERATOR * secret_key).to_affine();
+        let msg_hash = Scalar::<Secp256k1>::random(&mut rng);
+        let signature = Signature::sign(&secret_key, &msg_hash);
+
+        let px = pedersen
+            .cycle()
+            .commit(&mut rng, pubkey.x().to_cycle_scalar());
+        let py = pedersen
+            .cycle()
+            .commit(&mut rng, pubkey.y().to_cycle_scalar());
+        let (proof, commitments) = HiddenSignatureProof::construct(
+            &mut rng, &pedersen, msg_hash, &signature, &pubkey, px, py,
+        )
+        .unwrap();
+        assert_eq!(
+            proof.verify(&mut rng, &pedersen, msg_hash, &commitments),
+            Ok(())
+        );
+
+        // the proof is bound to the message hash
+        let other_hash = msg_hash + Scalar::ONE;
+        assert_eq!(
+            proof.verify(&mut rng, &pedersen, other_hash, &commitments),
+            Err("multiplication proof failed".to_string())
+        );
+    }
+}
@@ -0,0 +1,241 @@
+use super::sigma::Transcript;
+use crate::arithmetic::multimult::MultiMult;
+use crate::arithmetic::{Modular, Point, Scalar};
+use crate::curve::Curve;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Argument of knowledge of vectors `a` and `b` such that
+/// `P = <a, G> + <b, H> + <a, b> * Q`.
+///
+/// Each round halves the vectors and the generators, thus the proof consists
+/// of `2 * log2(n)` points and two scalars. The verifier does not fold the
+/// generators itself, it only computes the scalars of the final `G` and `H`
+/// with [`InnerProductProof::verification_scalars`].
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
+pub struct InnerProductProof<C: Curve> {
+    l_vec: Vec<Point<C>>,
+    r_vec: Vec<Point<C>>,
+    a: Scalar<C>,
+    b: Scalar<C>,
+}
+
+/// Scalars of the folded inner product relation, the proof is valid if
+/// `P + sum(u_k^2 * L_k + u_k^-2 * R_k) = a * <s, G> + b * <s^-1, H> + a * b * Q`.
+pub struct VerificationScalars<C: Curve> {
+    pub challenges_sq: Vec<Scalar<C>>,
+    pub challenges_inv_sq: Vec<Scalar<C>>,
+    pub s: Vec<Scalar<C>>,
+}
+
+impl<C: Curve> InnerProductProof<C> {
+    /// Generates the proof, the length of the vectors has to be a power of
+    /// two.
+    pub fn construct(
+        transcript: &mut Transcript,
+        q: &Point<C>,
+        mut g_vec: Vec<Point<C>>,
+        mut h_vec: Vec<Point<C>>,
+        mut a_vec: Vec<Scalar<C>>,
+        mut b_vec: Vec<Scalar<C>>,
+    ) -> Self {
+        let mut n = a_vec.len();
+        debug_assert!(n.is_power_of_two());
+        debug_assert!(g_vec.len() == n && h_vec.len() == n && b_vec.len() == n);
+
+        let rounds = n.trailing_zeros() as usize;
+        let mut l_vec = Vec::with_capacity(rounds);
+        let mut r_vec = Vec::with_capacity(rounds);
+        while n > 1 {
+            n /= 2;
+            let (a_lo, a_hi) = a_vec.split_at(n);
+            let (b_lo, b_hi) = b_vec.split_at(n);
+            let (g_lo, g_hi) = g_vec.split_at(n);
+            let (h_lo, h_hi) = h_vec.split_at(n);
+
+            let c_l = inner_product(a_lo, b_hi);
+            let c_r = inner_product(a_hi, b_lo);
+            let l = multiexp(
+                a_lo.iter()
+                    .zip(g_hi)
+                    .chain(b_hi.iter().zip(h_lo))
+                    .chain(std::iter::once((&c_l, q))),
+            );
+            let r = multiexp(
+                a_hi.iter()
+                    .zip(g_lo)
+                    .chain(b_lo.iter().zip(h_hi))
+                    .chain(std::iter::once((&c_r, q))),
+            );
+
+            transcript.append_point(&l);
+            transcript.append_point(&r);
+            let u = transcript.next_challenge::<C>();
+            let u_inv = u.inverse();
+
+            a_vec = (0..n).map(|i| a_lo[i] * u + a_hi[i] * u_inv).collect();
+            b_vec = (0..n).map(|i| b_lo[i] * u_inv + b_hi[i] * u).collect();
+            g_vec = (0..n)
+                .map(|i| g_lo[i].double_mul(&u_inv, &g_hi[i], &u))
+                .collect();
+            h_vec = (0..n)
+                .map(|i| h_lo[i].double_mul(&u, &h_hi[i], &u_inv))
+                .collect();
+
+            l_vec.push(l);
+            r_vec.push(r);
+        }
+
+        Self {
+            l_vec,
+            r_vec,
+            a: a_vec[0],
+            b: b_vec[0],
+        }
+    }
+
+    /// Replays the transcript and returns the scalars of the folded relation
+    /// for vectors of length `n`.
+    pub fn verification_scalars(
+        &self,
+        n: usize,
+        transcript: &mut Transcript,
+    ) -> Result<VerificationScalars<C>, String> {
+        let rounds = n.trailing_zeros() as usize;
+        if !n.is_power_of_two() || self.l_vec.len() != rounds || self.r_vec.len() != rounds {
+            return Err("invalid inner product proof length".to_string());
+        }
+
+        let mut challenges = Vec::with_capacity(rounds);
+        for (l, r) in self.l_vec.iter().zip(&self.r_vec) {
+            transcript.append_point(l);
+            transcript.append_point(r);
+            challenges.push(transcript.next_challenge::<C>());
+        }
+        let challenges_inv = challenges.iter().map(Modular::inverse).collect::<Vec<_>>();
+        let challenges_sq = challenges.iter().map(|u| *u * *u).collect::<Vec<_>>();
+        let challenges_inv_sq = challenges_inv.iter().map(|u| *u * *u).collect::<Vec<_>>();
+
+        // s_i is the product of u_k if the bit of i halved in round k is set
+        // and u_k^-1 otherwise
+        let mut s = Vec::with_capacity(n);
+        s.push(
+            challenges_inv
+                .iter()
+                .fold(Scalar::<C>::ONE, |acc, u| acc * *u),
+        );
+        for i in 1..n {
+            let log_i = (usize::BITS - 1 - i.leading_zeros()) as usize;
+            let previous = s[i - (1 << log_i)];
+            s.push(previous * challenges_sq[rounds - 1 - log_i]);
+        }
+
+        Ok(VerificationScalars {
+            challenges_sq,
+            challenges_inv_sq,
+            s,
+        })
+    }
+
+    pub fn l_vec(&self) -> &[Point<C>] {
+        &self.l_vec
+    }
+
+    pub fn r_vec(&self) -> &[Point<C>] {
+        &self.r_vec
+    }
+
+    pub fn a(&self) -> &Scalar<C> {
+        &self.a
+    }
+
+    pub fn b(&self) -> &Scalar<C> {
+        &self.b
+    }
+}
+
+pub(super) fn inner_product<C: Curve>(a: &[Scalar<C>], b: &[Scalar<C>]) -> Scalar<C> {
+    a.iter()
+        .zip(b)
+        .fold(Scalar::ZERO, |acc, (x, y)| acc + *x * *y)
+}
+
+// sum of the scalar multiples evaluated with a single multimult
+pub(super) fn multiexp<'a, C: Curve + 'a>(
+    pairs: impl Iterator<Item = (&'a Scalar<C>, &'a Point<C>)>,
+) -> Point<C> {
+    let mut multimult = MultiMult::new();
+    for (scalar, point) in pairs {
+        multimult.insert(point.clone(), *scalar);
+    }
+    multimult.evaluate()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arithmetic::AffinePoint;
+    use crate::curve::Tom256k1;
+    use rand::rngs::StdRng;
+    use rand_core::SeedableRng;
+
+    #[test]
+    fn inner_product_proof() {
+        let mut rng = StdRng::from_seed([45; 32]);
+        let n = 8;
+        let generator = |label: &[u8], i| Point::from(AffinePoint::hash_to_curve(label, i));
+        let g_vec = (0..n as u64)
+            .map(|i| generator(b"ipp-g", i))
+            .collect::<Vec<Point<Tom256k1>>>();
+        let h_vec = (0..n as u64)
+            .map(|i| generator(b"ipp-h", i))
+            .collect::<Vec<_>>();
+        let q = generator(b"ipp-q", 0);
+        let a_vec = (0..n).map(|_| Scalar::random(&mut rng)).collect::<Vec<_>>();
+        let b_vec = (0..n).map(|_| Scalar::random(&mut rng)).collect::<Vec<_>>();
+        let c = inner_product(&a_vec, &b_vec);
+        let p = multiexp(
+            a_vec
+                .iter()
+                .zip(&g_vec)
+                .chain(b_vec.iter().zip(&h_vec))
+                .chain(std::iter::once((&c, &q))),
+        );
+
+        let proof = InnerProductProof::construct(
+            &mut Transcript::new(b"test"),
+            &q,
+            g_vec.clone(),
+            h_vec.clone(),
+            a_vec,
+            b_vec,
+        );
+        assert_eq!(proof.l_vec().len(), 3);
+
+        let check = |p: &Point<Tom256k1>| {
+            let scalars = proof
+                .verification_scalars(n, &mut Transcript::new(b"test"))
+                .unwrap();
+            let mut multimult = MultiMult::new();
+            multimult.insert(p.clone(), Scalar::ONE);
+            for (i, s) in scalars.s.iter().enumerate() {
+                multimult.insert(g_vec[i].clone(), -(*proof.a() * *s));
+                multimult.insert(h_vec[i].clone(), -(*proof.b() * scalars.s[n - 1 - i]));
+            }
+            multimult.insert(q.clone(), -(*proof.a() * *proof.b()));
+            for k in 0..proof.l_vec().len() {
+                multimult.insert(proof.l_vec()[k].clone(), scalars.challenges_sq[k]);
+                multimult.insert(proof.r_vec()[k].clone(), scalars.challenges_inv_sq[k]);
+            }
+            multimult.evaluate().is_identity()
+        };
+        assert!(check(&p));
+        assert!(!check(&(&p + &q)));
+        assert_eq!(
+            proof
+                .verification_scalars(4, &mut Transcript::new(b"test"))
+                .err(),
+            Some("invalid inner product proof length".to_string())
+        );
+    }
+}
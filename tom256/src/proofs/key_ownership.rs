@@ -0,0 +1,113 @@
+use super::exp::{ExpCommitmentPoints, ExpCommitments, ExpProof, ExpSecrets};
+use super::SEC_PARAM;
+use crate::arithmetic::{Point, Scalar};
+use crate::curve::{Curve, Cycle};
+use crate::pedersen::{PedersenCommitment, PedersenCycle};
+use crate::rng::CryptoCoreRng;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Proof of knowledge of the secret key of the public key committed on the
+/// cycle curve.
+///
+/// Proves `pk = (sk + h)·G - h·G` with an [`ExpProof`], where `h` is the
+/// message hash. This mirrors the ECDSA relation `pk = s1·R - z1·G`, thus the
+/// proof is bound to the message through the offset point just like the
+/// signature proof. It has the same size and cost as the signature proof, it
+/// only spares provers holding the secret key from signing the message.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct KeyOwnershipProof<C: Curve, CC: Cycle<C>> {
+    proof: ExpProof<C, CC>,
+}
+
+impl<C: Curve, CC: Cycle<C>> KeyOwnershipProof<C, CC> {
+    /// Generates the proof and returns it together with the commitments to
+    /// the exponent `sk + h` and the public key coordinates.
+    pub fn construct<R: CryptoCoreRng>(
+        rng: &mut R,
+        pedersen: &PedersenCycle<C, CC>,
+        secret_key: Scalar<C>,
+        msg_hash: Scalar<C>,
+        commitment_to_pk_x: PedersenCommitment<CC>,
+        commitment_to_pk_y: PedersenCommitment<CC>,
+    ) -> Result<(Self, ExpCommitmentPoints<C, CC>), String> {
+        let pubkey = (&Point::<C>::GENERATOR * secret_key).to_affine();
+        let exp = secret_key + msg_hash;
+        let q_point = &Point::<C>::GENERATOR * msg_hash;
+
+        let exp_secrets = ExpSecrets::new(exp, pubkey);
+        let exp_commitments = ExpCommitments {
+            px: commitment_to_pk_x,
+            py: commitment_to_pk_y,
+            exp: pedersen
+                .base()
+                .commit_with_generator(rng, exp, &Point::<C>::GENERATOR),
+        };
+
+        let proof = ExpProof::construct(
+            rng,
+            &Point::<C>::GENERATOR,
+            pedersen,
+            &exp_secrets,
+            &exp_commitments,
+            SEC_PARAM,
+            Some(q_point),
+        )?;
+
+        Ok((Self { proof }, exp_commitments.into_commitments()))
+    }
+
+    pub fn verify<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        pedersen: &PedersenCycle<C, CC>,
+        msg_hash: Scalar<C>,
+        commitments: &ExpCommitmentPoints<C, CC>,
+    ) -> Result<(), String> {
+        let q_point = &Point::<C>::GENERATOR * msg_hash;
+        self.proof.verify(
+            rng,
+            &Point::<C>::GENERATOR,
+            pedersen,
+            commitments,
+            SEC_PARAM,
+            Some(q_point),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curve::{Secp256k1, Tom256k1};
+    use rand::rngs::StdRng;
+    use rand_core::SeedableRng;
+
+    #[test]
+    fn key_ownership() {
+        let mut rng = StdRng::from_seed([21; 32]);
+        let pedersen = PedersenCycle::<Secp256k1, Tom256k1>::new(&mut rng);
+        let secret_key = Scalar::<Secp256k1>::random(&mut rng);
+        let pubkey = (&Point::<Secp256k1>::GENERATOR * secret_key).to_affine();
+        let msg_hash = Scalar::<Secp256k1>::random(&mut rng);
+
+        let px = pedersen
+            .cycle()
+            .commit(&mut rng, pubkey.x().to_cycle_scalar());
+        let py = pedersen
+            .cycle()
+            .commit(&mut rng, pubkey.y().to_cycle_scalar());
+        let (proof, commitments) =
+            KeyOwnershipProof::construct(&mut rng, &pedersen, secret_key, msg_hash, px, py)
+                .unwrap();
+        assert!(proof
+            .verify(&mut rng, &pedersen, msg_hash, &commitments)
+            .is_ok());
+
+        // the proof is bound to the message hash
+        let other_hash = msg_hash + Scalar::ONE;
+        assert!(proof
+            .verify(&mut rng, &pedersen, other_hash, &commitments)
+            .is_err());
+    }
+}
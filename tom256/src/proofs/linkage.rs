@@ -0,0 +1,178 @@
+use super::equality::{EqualityCommitment, EqualityResponse, EqualityStatement, EqualityWitness};
+use super::sigma::{SigmaProtocol, Transcript};
+use crate::arithmetic::multimult::MultiMult;
+use crate::arithmetic::{Point, Scalar};
+use crate::curve::Curve;
+use crate::pedersen::*;
+use crate::rng::CryptoCoreRng;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Opening of the commitment to the x coordinate of the prover's public key
+/// in a [`ZkAttestProof`](super::ZkAttestProof).
+///
+/// Anyone holding the opening learns the public key behind the proof, thus
+/// it should be stored like a secret key. It does not implement `Debug` to
+/// keep it out of logs.
+#[derive(Clone, BorshDeserialize, BorshSerialize)]
+pub struct KeyOpening<C: Curve> {
+    pubkey_x: Scalar<C>,
+    randomness: Scalar<C>,
+}
+
+impl<C: Curve> KeyOpening<C> {
+    pub(super) fn new(pubkey_x: Scalar<C>, commitment_to_pk_x: &PedersenCommitment<C>) -> Self {
+        Self {
+            pubkey_x,
+            randomness: *commitment_to_pk_x.randomness(),
+        }
+    }
+
+    /// Checks that the opening belongs to the commitment.
+    pub fn check(
+        &self,
+        pedersen_generator: &PedersenGenerator<C>,
+        commitment: &Point<C>,
+    ) -> Result<(), String> {
+        if self.open(pedersen_generator).commitment() == commitment {
+            Ok(())
+        } else {
+            Err("opening does not match the commitment".to_string())
+        }
+    }
+
+    fn open(&self, pedersen_generator: &PedersenGenerator<C>) -> PedersenCommitment<C> {
+        pedersen_generator.commit_with_randomness(self.pubkey_x, self.randomness)
+    }
+}
+
+/// Proof that two commitments to public keys, e.g. in two
+/// [`ZkAttestProof`](super::ZkAttestProof)s, hide the same key.
+///
+/// The prover needs the [`KeyOpening`]s of both commitments, the verifier
+/// learns nothing about the key besides the linkage.
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
+pub struct LinkageProof<C: Curve> {
+    commitment: EqualityCommitment<C>,
+    response: EqualityResponse<C>,
+}
+
+impl<C: Curve> LinkageProof<C> {
+    const HASH_ID: &'static [u8] = b"linkage-proof";
+
+    pub fn construct<R: CryptoCoreRng>(
+        rng: &mut R,
+        statement: EqualityStatement<C>,
+        opening_1: &KeyOpening<C>,
+        opening_2: &KeyOpening<C>,
+    ) -> Result<Self, String> {
+        opening_1.check(statement.pedersen_generator_1, statement.commitment_1)?;
+        opening_2.check(statement.pedersen_generator_2, statement.commitment_2)?;
+        if opening_1.pubkey_x != opening_2.pubkey_x {
+            return Err("openings are of different keys".to_string());
+        }
+
+        let witness = EqualityWitness::new(
+            opening_1.pubkey_x,
+            &opening_1.open(statement.pedersen_generator_1),
+            &opening_2.open(statement.pedersen_generator_2),
+        );
+        let (commitment, response) = statement.prove(rng, &witness, Transcript::new(Self::HASH_ID));
+
+        Ok(Self {
+            commitment,
+            response,
+        })
+    }
+
+    pub fn verify<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        statement: EqualityStatement<C>,
+    ) -> Result<(), String> {
+        let mut multimult = MultiMult::new();
+        statement.verify(
+            rng,
+            &self.commitment,
+            &self.response,
+            Transcript::new(Self::HASH_ID),
+            &mut multimult,
+        )?;
+
+        if multimult.evaluate().is_identity() {
+            Ok(())
+        } else {
+            Err("failed to verify linkage".to_owned())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curve::Tom256k1;
+    use rand::rngs::StdRng;
+    use rand_core::SeedableRng;
+
+    #[test]
+    fn linkage_proof() {
+        let mut rng = StdRng::from_seed([50; 32]);
+        let pedersen_generator_1 = PedersenGenerator::<Tom256k1>::new(&mut rng);
+        let pedersen_generator_2 = PedersenGenerator::<Tom256k1>::new(&mut rng);
+        let key = Scalar::<Tom256k1>::random(&mut rng);
+        let commitment_1 = pedersen_generator_1.commit(&mut rng, key);
+        let commitment_2 = pedersen_generator_2.commit(&mut rng, key);
+        let opening_1 = KeyOpening::new(key, &commitment_1);
+        let opening_2 = KeyOpening::new(key, &commitment_2);
+        let statement = EqualityStatement {
+            pedersen_generator_1: &pedersen_generator_1,
+            commitment_1: commitment_1.commitment(),
+            pedersen_generator_2: &pedersen_generator_2,
+            commitment_2: commitment_2.commitment(),
+        };
+
+        let proof = LinkageProof::construct(&mut rng, statement, &opening_1, &opening_2).unwrap();
+        assert_eq!(proof.verify(&mut rng, statement), Ok(()));
+
+        // the proof is bound to the commitments
+        let other_commitment = pedersen_generator_2.commit(&mut rng, key);
+        assert_eq!(
+            proof.verify(
+                &mut rng,
+                EqualityStatement {
+                    commitment_2: other_commitment.commitment(),
+                    ..statement
+                },
+            ),
+            Err("failed to verify linkage".to_string())
+        );
+
+        // the opening survives serialization
+        let bytes = opening_2.try_to_vec().unwrap();
+        let restored = KeyOpening::<Tom256k1>::try_from_slice(&bytes).unwrap();
+        assert!(restored
+            .check(&pedersen_generator_2, commitment_2.commitment())
+            .is_ok());
+        assert_eq!(
+            restored.check(&pedersen_generator_1, commitment_1.commitment()),
+            Err("opening does not match the commitment".to_string())
+        );
+
+        let other_key = Scalar::<Tom256k1>::random(&mut rng);
+        let other_commitment = pedersen_generator_2.commit(&mut rng, other_key);
+        let other_opening = KeyOpening::new(other_key, &other_commitment);
+        assert_eq!(
+            LinkageProof::construct(
+                &mut rng,
+                EqualityStatement {
+                    commitment_2: other_commitment.commitment(),
+                    ..statement
+                },
+                &opening_1,
+                &other_opening,
+            )
+            .err(),
+            Some("openings are of different keys".to_string())
+        );
+    }
+}
@@ -1,53 +1,116 @@
+use super::session::Interactive;
+use super::sigma::{AllOf, AnyOf, AnyOfWitness, SigmaProtocol, Transcript};
 use super::utils::*;
 use crate::arithmetic::multimult::*;
 use crate::arithmetic::{Modular, Point, Scalar};
 use crate::curve::Curve;
-use crate::hasher::PointHasher;
 use crate::pedersen::*;
+use crate::ring::{padded_ring_log2, ring_digest, RingView};
 use crate::rng::CryptoCoreRng;
 use crate::U256;
 
 use borsh::{BorshDeserialize, BorshSerialize};
 
+/// Statement that the committed key is a member of the ring.
+///
+/// The ring is padded implicitly by reading the first member for indices out
+/// of its bounds, so it is never copied.
+pub struct MembershipStatement<'a, C: Curve, V: RingView<C> + ?Sized> {
+    pub pedersen_generator: &'a PedersenGenerator<C>,
+    pub commitment_to_key: &'a Point<C>,
+    pub ring: &'a V,
+    // log2(padded ring length)
+    n: usize,
+    ring_digest: [u8; 32],
+}
+
+impl<'a, C: Curve, V: RingView<C> + ?Sized> MembershipStatement<'a, C, V> {
+    pub fn new(
+        pedersen_generator: &'a PedersenGenerator<C>,
+        commitment_to_key: &'a Point<C>,
+        ring: &'a V,
+    ) -> Result<Self, String> {
+        Ok(Self {
+            pedersen_generator,
+            commitment_to_key,
+            ring,
+            n: padded_ring_log2(ring.len())?,
+            ring_digest: ring_digest(ring)?,
+        })
+    }
+
+    fn padded_member(&self, index: usize) -> Scalar<C> {
+        self.ring
+            .member(if index < self.ring.len() { index } else { 0 })
+    }
+}
+
+pub struct MembershipWitness<C: Curve> {
+    index: usize,
+    randomness: Scalar<C>,
+}
+
+impl<C: Curve> MembershipWitness<C> {
+    pub fn new(index: usize, commitment_to_key: &PedersenCommitment<C>) -> Self {
+        Self {
+            index,
+            randomness: *commitment_to_key.randomness(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
-pub struct MembershipProof<C: Curve> {
+pub struct MembershipCommitment<C: Curve> {
     cl: Vec<Point<C>>,
     ca: Vec<Point<C>>,
     cb: Vec<Point<C>>,
     cd: Vec<Point<C>>,
+}
+
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
+pub struct MembershipResponse<C: Curve> {
     fi: Vec<Scalar<C>>,
     za: Vec<Scalar<C>>,
     zb: Vec<Scalar<C>>,
     zd: Scalar<C>,
 }
 
-impl<C: Curve> MembershipProof<C> {
-    const HASH_ID: &'static [u8] = b"membership-proof";
+pub struct MembershipNonce<C: Curve> {
+    l_vec: Vec<Scalar<C>>,
+    a_vec: Vec<Scalar<C>>,
+    r_vec: Vec<Scalar<C>>,
+    s_vec: Vec<Scalar<C>>,
+    t_vec: Vec<Scalar<C>>,
+    rho_vec: Vec<Scalar<C>>,
+}
 
-    pub fn construct<R: CryptoCoreRng>(
-        rng: &mut R,
-        pedersen_generator: &PedersenGenerator<C>,
-        commitment_to_key: &PedersenCommitment<C>,
-        index: usize,
-        // NOTE this is just the public address represented as a scalar (only
-        // 160 bit, so it should fit unless C::PRIME_MODULUS is less than
-        // 2^160)
-        ring: &[Scalar<C>],
-    ) -> Result<Self, String> {
-        if index >= ring.len() {
-            return Err("invalid index".to_string());
-        }
+fn power<C: Curve>(base: Scalar<C>, exp: usize) -> Scalar<C> {
+    base.pow(&Scalar::new(U256::from_u64(exp as u64)))
+}
+
+impl<'a, C: Curve, V: RingView<C> + ?Sized> SigmaProtocol<C> for MembershipStatement<'a, C, V> {
+    type Witness = MembershipWitness<C>;
+    type Commitment = MembershipCommitment<C>;
+    type Response = MembershipResponse<C>;
+    type Nonce = MembershipNonce<C>;
 
-        let mut ring = ring.to_vec();
-        let n = pad_ring_to_2n(&mut ring)?; // log2(ring.len())
+    fn commit<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        witness: &Self::Witness,
+    ) -> (Self::Commitment, Self::Nonce) {
+        let n = self.n;
+        let pedersen_generator = self.pedersen_generator;
 
         // random scalar storages
-        let mut a_vec = Vec::<Scalar<C>>::with_capacity(n);
-        let mut l_vec = Vec::<Scalar<C>>::with_capacity(n);
-        let mut r_vec = Vec::<Scalar<C>>::with_capacity(n);
-        let mut s_vec = Vec::<Scalar<C>>::with_capacity(n);
-        let mut t_vec = Vec::<Scalar<C>>::with_capacity(n);
-        let mut rho_vec = Vec::<Scalar<C>>::with_capacity(n);
+        let mut nonce = MembershipNonce {
+            l_vec: Vec::with_capacity(n),
+            a_vec: Vec::with_capacity(n),
+            r_vec: Vec::with_capacity(n),
+            s_vec: Vec::with_capacity(n),
+            t_vec: Vec::with_capacity(n),
+            rho_vec: Vec::with_capacity(n),
+        };
 
         // commitment storages
         let mut ca = Vec::<Point<C>>::with_capacity(n);
@@ -57,35 +120,38 @@ impl<C: Curve> MembershipProof<C> {
 
         let mut omegas = Vec::<Scalar<C>>::with_capacity(n);
 
-        let mut tmp_index = index;
+        let mut tmp_index = witness.index;
         for i in 0..n {
-            l_vec.push(Scalar::new(U256::from_u64((tmp_index % 2) as u64)));
+            nonce
+                .l_vec
+                .push(Scalar::new(U256::from_u64((tmp_index % 2) as u64)));
             tmp_index /= 2;
-            a_vec.push(Scalar::random(rng));
-            r_vec.push(Scalar::random(rng));
-            s_vec.push(Scalar::random(rng));
-            t_vec.push(Scalar::random(rng));
-            rho_vec.push(Scalar::random(rng));
+            nonce.a_vec.push(Scalar::random(rng));
+            nonce.r_vec.push(Scalar::random(rng));
+            nonce.s_vec.push(Scalar::random(rng));
+            nonce.t_vec.push(Scalar::random(rng));
+            nonce.rho_vec.push(Scalar::random(rng));
 
             cl.push(
                 pedersen_generator
-                    .commit_with_randomness(l_vec[i], r_vec[i])
+                    .commit_with_randomness(nonce.l_vec[i], nonce.r_vec[i])
                     .into_commitment(),
             );
             ca.push(
                 pedersen_generator
-                    .commit_with_randomness(a_vec[i], s_vec[i])
+                    .commit_with_randomness(nonce.a_vec[i], nonce.s_vec[i])
                     .into_commitment(),
             );
             cb.push(
                 pedersen_generator
-                    .commit_with_randomness(l_vec[i] * a_vec[i], t_vec[i])
+                    .commit_with_randomness(nonce.l_vec[i] * nonce.a_vec[i], nonce.t_vec[i])
                     .into_commitment(),
             );
 
             omegas.push(Scalar::new(U256::from_u64(i as u64)));
         }
 
+        let key = self.padded_member(witness.index);
         let mut poly_vals = Vec::<Scalar<C>>::new();
         for omega in omegas.iter() {
             let mut f0j = Vec::<Scalar<C>>::with_capacity(n);
@@ -94,8 +160,8 @@ impl<C: Curve> MembershipProof<C> {
 
             let mut product = Scalar::ONE;
             for j in 0..n {
-                f0j.push(&(Scalar::ONE - l_vec[j]) * omega - a_vec[j]);
-                f1j.push(&l_vec[j] * omega + a_vec[j]);
+                f0j.push(&(Scalar::ONE - nonce.l_vec[j]) * omega - nonce.a_vec[j]);
+                f1j.push(&nonce.l_vec[j] * omega + nonce.a_vec[j]);
                 ratio.push(f1j[j] * f0j[j].inverse());
                 product *= f0j[j];
             }
@@ -110,118 +176,319 @@ impl<C: Curve> MembershipProof<C> {
             }
 
             let mut poly_val = Scalar::ZERO;
-            for i in 0..ring.len() {
-                poly_val += (ring[index] - ring[i]) * prod_vec[i];
+            for (i, prod) in prod_vec.iter().enumerate() {
+                poly_val += (key - self.padded_member(i)) * *prod;
             }
             poly_vals.push(poly_val);
         }
 
-        let coeffs = interpolate(&omegas, &poly_vals)?;
-        for i in 0..n {
+        // NOTE the omegas are distinct, thus interpolation never fails
+        let coeffs = interpolate(&omegas, &poly_vals).unwrap();
+        for (coeff, rho) in coeffs.iter().zip(&nonce.rho_vec) {
             cd.push(
                 pedersen_generator
-                    .commit_with_randomness(coeffs[i], rho_vec[i])
+                    .commit_with_randomness(*coeff, *rho)
                     .into_commitment(),
             );
         }
 
-        if cl.len() != n || ca.len() != n || cb.len() != n || cd.len() != n {
-            return Err("invalid commitment lengths".to_owned());
+        (MembershipCommitment { cl, ca, cb, cd }, nonce)
+    }
+
+    // NOTE the ring digest is part of the transcript, thus the challenge
+    // depends on the exact ring the proof is generated on
+    fn absorb(&self, commitment: &Self::Commitment, transcript: &mut Transcript) {
+        transcript.append_bytes(&self.ring_digest);
+        transcript.append_point(self.commitment_to_key);
+        // NOTE commitment lengths are checked before the relations are
+        // evaluated, a malformed proof only changes the challenge here
+        for i in 0..commitment.ca.len() {
+            for points in [
+                &commitment.ca,
+                &commitment.cb,
+                &commitment.cd,
+                &commitment.cl,
+            ] {
+                if let Some(point) = points.get(i) {
+                    transcript.append_point(point);
+                }
+            }
         }
+    }
 
-        let challenge = Self::hash_commitments(&ca, &cb, &cd, &cl);
+    fn respond(
+        &self,
+        witness: &Self::Witness,
+        nonce: Self::Nonce,
+        challenge: Scalar<C>,
+    ) -> Self::Response {
+        let n = self.n;
         let mut fi = Vec::<Scalar<C>>::with_capacity(n);
         let mut za = Vec::<Scalar<C>>::with_capacity(n);
         let mut zb = Vec::<Scalar<C>>::with_capacity(n);
-        let mut zd =
-            commitment_to_key.randomness() * &challenge.pow(&Scalar::new(U256::from_u64(n as u64)));
+        let mut zd = witness.randomness * power(challenge, n);
 
         for i in 0..n {
-            fi.push(l_vec[i] * challenge + a_vec[i]);
-            za.push(r_vec[i] * challenge + s_vec[i]);
-            zb.push(r_vec[i] * (challenge - fi[i]) + t_vec[i]);
-            zd -= rho_vec[i] * challenge.pow(&Scalar::new(U256::from_u64(i as u64)));
+            fi.push(nonce.l_vec[i] * challenge + nonce.a_vec[i]);
+            za.push(nonce.r_vec[i] * challenge + nonce.s_vec[i]);
+            zb.push(nonce.r_vec[i] * (challenge - fi[i]) + nonce.t_vec[i]);
+            zd -= nonce.rho_vec[i] * power(challenge, i);
         }
 
-        if fi.len() != n || za.len() != n || zb.len() != n {
-            return Err("invalid proof lengths".to_owned());
+        MembershipResponse { fi, za, zb, zd }
+    }
+
+    fn simulate<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        challenge: Scalar<C>,
+    ) -> (Self::Commitment, Self::Response) {
+        let n = self.n;
+        let pedersen_generator = self.pedersen_generator;
+        let mut commitment = MembershipCommitment {
+            cl: Vec::with_capacity(n),
+            ca: Vec::with_capacity(n),
+            cb: Vec::with_capacity(n),
+            cd: Vec::with_capacity(n),
+        };
+        let mut response = MembershipResponse {
+            fi: Vec::with_capacity(n),
+            za: Vec::with_capacity(n),
+            zb: Vec::with_capacity(n),
+            zd: Scalar::random(rng),
+        };
+
+        // ca and cb are solved from random responses
+        for _ in 0..n {
+            let cl = pedersen_generator
+                .commit_with_randomness(Scalar::random(rng), Scalar::random(rng))
+                .into_commitment();
+            let fi = Scalar::random(rng);
+            let za = Scalar::random(rng);
+            let zb = Scalar::random(rng);
+            commitment.ca.push(
+                &pedersen_generator
+                    .commit_with_randomness(fi, za)
+                    .into_commitment()
+                    - &(&cl * challenge),
+            );
+            commitment
+                .cb
+                .push(&(pedersen_generator.generator() * zb) - &(&cl * (challenge - fi)));
+            commitment.cl.push(cl);
+            response.fi.push(fi);
+            response.za.push(za);
+            response.zb.push(zb);
         }
 
-        Ok(Self {
-            cl,
-            ca,
-            cb,
-            cd,
-            fi,
-            za,
-            zb,
-            zd,
-        })
+        // cd_0 is solved from random cd_1, ..., cd_(n-1)
+        let total = self.weighted_ring_sum(challenge, &response.fi);
+        let mut cd_0 = &(self.commitment_to_key * power(challenge, n))
+            - &pedersen_generator
+                .commit_with_randomness(total, response.zd)
+                .into_commitment();
+        for i in 1..n {
+            let cd = pedersen_generator
+                .commit_with_randomness(Scalar::random(rng), Scalar::random(rng))
+                .into_commitment();
+            cd_0 = &cd_0 - &(&cd * power(challenge, i));
+            commitment.cd.push(cd);
+        }
+        commitment.cd.insert(0, cd_0);
+
+        (commitment, response)
     }
 
-    pub fn verify<R: CryptoCoreRng>(
+    fn verify_into<R: CryptoCoreRng>(
         &self,
         rng: &mut R,
-        pedersen_generator: &PedersenGenerator<C>,
-        commitment_to_key: &Point<C>,
-        ring: &[Scalar<C>],
+        commitment: &Self::Commitment,
+        challenge: Scalar<C>,
+        response: &Self::Response,
+        multimult: &mut MultiMult<C>,
     ) -> Result<(), String> {
-        let mut ring = ring.to_vec();
-        let n = pad_ring_to_2n(&mut ring)?; // log2(ring.len())
-
-        let challenge = Self::hash_commitments(&self.ca, &self.cb, &self.cd, &self.cl);
+        let n = self.n;
+        if [
+            &commitment.cl,
+            &commitment.ca,
+            &commitment.cb,
+            &commitment.cd,
+        ]
+        .iter()
+        .any(|points| points.len() != n)
+        {
+            return Err("invalid commitment lengths".to_owned());
+        }
+        if [&response.fi, &response.za, &response.zb]
+            .iter()
+            .any(|scalars| scalars.len() != n)
+        {
+            return Err("invalid proof lengths".to_owned());
+        }
 
-        let mut multimult = MultiMult::new();
+        let pedersen_generator = self.pedersen_generator;
         multimult.add_known(Point::<C>::GENERATOR);
         multimult.add_known(pedersen_generator.generator().clone());
 
-        // NOTE unwraps here are fine because of length checks
-        // at proof construction
         for i in 0..n {
             let mut rel_0 = Relation::new();
             let mut rel_1 = Relation::new();
 
-            rel_0.insert(self.cl.get(i).unwrap().clone(), challenge);
-            rel_0.insert(self.ca.get(i).unwrap().clone(), Scalar::ONE);
-            rel_0.insert(Point::<C>::GENERATOR, -self.fi[i]);
-            rel_0.insert(pedersen_generator.generator().clone(), -self.za[i]);
+            rel_0.insert(commitment.cl[i].clone(), challenge);
+            rel_0.insert(commitment.ca[i].clone(), Scalar::ONE);
+            rel_0.insert(Point::<C>::GENERATOR, -response.fi[i]);
+            rel_0.insert(pedersen_generator.generator().clone(), -response.za[i]);
 
-            rel_1.insert(self.cl.get(i).unwrap().clone(), challenge - self.fi[i]);
-            rel_1.insert(self.cb.get(i).unwrap().clone(), Scalar::ONE);
-            rel_1.insert(pedersen_generator.generator().clone(), -self.zb[i]);
+            rel_1.insert(commitment.cl[i].clone(), challenge - response.fi[i]);
+            rel_1.insert(commitment.cb[i].clone(), Scalar::ONE);
+            rel_1.insert(pedersen_generator.generator().clone(), -response.zb[i]);
 
-            rel_0.drain(rng, &mut multimult);
-            rel_1.drain(rng, &mut multimult);
+            rel_0.drain(rng, multimult);
+            rel_1.drain(rng, multimult);
         }
 
+        let total = self.weighted_ring_sum(challenge, &response.fi);
+
+        let mut rel_final = Relation::new();
+        for (i, cd_elem) in commitment.cd.iter().enumerate() {
+            rel_final.insert(cd_elem.clone(), -power(challenge, i));
+        }
+
+        rel_final.insert(self.commitment_to_key.clone(), power(challenge, n));
+        rel_final.insert(Point::<C>::GENERATOR, -total);
+        rel_final.insert(pedersen_generator.generator().clone(), -response.zd);
+        rel_final.drain(rng, multimult);
+
+        Ok(())
+    }
+}
+
+impl<'a, C: Curve, V: RingView<C> + ?Sized> MembershipStatement<'a, C, V> {
+    // sum of the ring members weighted by the polynomials p_i(x) evaluated at
+    // the challenge
+    fn weighted_ring_sum(&self, challenge: Scalar<C>, fi: &[Scalar<C>]) -> Scalar<C> {
         let mut total = Scalar::ZERO;
-        for (i, key) in ring.iter().enumerate() {
+        for i in 0..1 << self.n {
             let mut pix = Scalar::ONE;
-            for j in 0..n {
+            for (j, f) in fi.iter().enumerate() {
                 if i & (1 << j) == 0 {
-                    pix *= challenge - self.fi[j];
+                    pix *= challenge - *f;
                 } else {
-                    pix *= self.fi[j];
+                    pix *= *f;
                 }
             }
-            total += key * &pix;
+            total += self.padded_member(i) * pix;
+        }
+        total
+    }
+}
+
+impl<'a, C: Curve, V: RingView<C> + ?Sized> Interactive<C> for MembershipStatement<'a, C, V> {
+    type Witness = MembershipWitness<C>;
+    type Commitment = MembershipCommitment<C>;
+    type Challenge = Scalar<C>;
+    type Response = MembershipResponse<C>;
+    type State = MembershipNonce<C>;
+
+    fn commit_message<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        witness: &Self::Witness,
+    ) -> Result<(Self::Commitment, Self::State), String> {
+        if witness.index >= self.ring.len() {
+            return Err("invalid index".to_string());
         }
+        Ok(self.commit(rng, witness))
+    }
 
-        let mut rel_final = Relation::new();
-        for (i, cd_elem) in self.cd.iter().enumerate() {
-            rel_final.insert(
-                cd_elem.clone(),
-                -challenge.pow(&Scalar::new(U256::from_u64(i as u64))),
-            );
+    fn challenge_message<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        _commitment: &Self::Commitment,
+    ) -> Self::Challenge {
+        Scalar::random(rng)
+    }
+
+    fn respond_message<R: CryptoCoreRng>(
+        &self,
+        _rng: &mut R,
+        witness: &Self::Witness,
+        state: Self::State,
+        challenge: &Self::Challenge,
+    ) -> Result<Self::Response, String> {
+        Ok(self.respond(witness, state, *challenge))
+    }
+
+    fn check<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        commitment: &Self::Commitment,
+        challenge: &Self::Challenge,
+        response: &Self::Response,
+    ) -> Result<(), String> {
+        let mut multimult = MultiMult::new();
+        self.verify_into(rng, commitment, *challenge, response, &mut multimult)?;
+        if multimult.evaluate().is_identity() {
+            Ok(())
+        } else {
+            Err("failed to verify membership".to_owned())
+        }
+    }
+}
+
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
+pub struct MembershipProof<C: Curve> {
+    commitment: MembershipCommitment<C>,
+    response: MembershipResponse<C>,
+}
+
+impl<C: Curve> MembershipProof<C> {
+    const HASH_ID: &'static [u8] = b"membership-proof";
+
+    pub fn construct<R: CryptoCoreRng>(
+        rng: &mut R,
+        pedersen_generator: &PedersenGenerator<C>,
+        commitment_to_key: &PedersenCommitment<C>,
+        index: usize,
+        // NOTE this is just the public address represented as a scalar (only
+        // 160 bit, so it should fit unless C::PRIME_MODULUS is less than
+        // 2^160)
+        ring: &[Scalar<C>],
+    ) -> Result<Self, String> {
+        if index >= ring.len() {
+            return Err("invalid index".to_string());
         }
 
-        rel_final.insert(
-            commitment_to_key.clone(),
-            challenge.pow(&Scalar::new(U256::from_u64(n as u64))),
+        let statement =
+            MembershipStatement::new(pedersen_generator, commitment_to_key.commitment(), ring)?;
+        let (commitment, response) = statement.prove(
+            rng,
+            &MembershipWitness::new(index, commitment_to_key),
+            Transcript::new(Self::HASH_ID),
         );
-        rel_final.insert(Point::<C>::GENERATOR, -total);
-        rel_final.insert(pedersen_generator.generator().clone(), -self.zd);
-        rel_final.drain(rng, &mut multimult);
+
+        Ok(Self {
+            commitment,
+            response,
+        })
+    }
+
+    pub fn verify<R: CryptoCoreRng, V: RingView<C> + ?Sized>(
+        &self,
+        rng: &mut R,
+        pedersen_generator: &PedersenGenerator<C>,
+        commitment_to_key: &Point<C>,
+        ring: &V,
+    ) -> Result<(), String> {
+        let statement = MembershipStatement::new(pedersen_generator, commitment_to_key, ring)?;
+        let mut multimult = MultiMult::new();
+        statement.verify(
+            rng,
+            &self.commitment,
+            &self.response,
+            Transcript::new(Self::HASH_ID),
+            &mut multimult,
+        )?;
 
         if multimult.evaluate().is_identity() {
             Ok(())
@@ -229,24 +496,173 @@ impl<C: Curve> MembershipProof<C> {
             Err("failed to verify membership".to_owned())
         }
     }
+}
 
-    fn hash_commitments(
-        ca: &[Point<C>],
-        cb: &[Point<C>],
-        cd: &[Point<C>],
-        cl: &[Point<C>],
-    ) -> Scalar<C> {
-        let mut hasher = PointHasher::new(Self::HASH_ID);
-        // NOTE we are assuming that all input slices have the same length
-        // it is important to use this function in both `contruct` and `verify`
-        for i in 0..ca.len() {
-            hasher.insert_point(ca.get(i).unwrap());
-            hasher.insert_point(cb.get(i).unwrap());
-            hasher.insert_point(cd.get(i).unwrap());
-            hasher.insert_point(cl.get(i).unwrap());
+/// Proof that the committed key is a member of at least one of several rings
+/// without revealing which one.
+///
+/// It is an OR composition of [`MembershipProof`]s on the same commitment,
+/// the membership proofs of the other rings are simulated.
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
+pub struct AnyMembershipProof<C: Curve> {
+    commitment: Vec<MembershipCommitment<C>>,
+    // challenges of all but the last ring and the responses
+    response: (Vec<Scalar<C>>, Vec<MembershipResponse<C>>),
+}
+
+impl<C: Curve> AnyMembershipProof<C> {
+    const HASH_ID: &'static [u8] = b"any-membership-proof";
+
+    pub fn construct<R: CryptoCoreRng>(
+        rng: &mut R,
+        pedersen_generator: &PedersenGenerator<C>,
+        commitment_to_key: &PedersenCommitment<C>,
+        ring_index: usize,
+        index: usize,
+        rings: &[&[Scalar<C>]],
+    ) -> Result<Self, String> {
+        match rings.get(ring_index) {
+            Some(ring) if index < ring.len() => {}
+            Some(_) => return Err("invalid index".to_string()),
+            None => return Err("invalid ring index".to_string()),
+        }
+
+        let statement = Self::statement(pedersen_generator, commitment_to_key.commitment(), rings)?;
+        let witness = AnyOfWitness {
+            index: ring_index,
+            witness: MembershipWitness::new(index, commitment_to_key),
+        };
+        let (commitment, response) = statement.prove(rng, &witness, Transcript::new(Self::HASH_ID));
+
+        Ok(Self {
+            commitment,
+            response,
+        })
+    }
+
+    pub fn verify<R: CryptoCoreRng, V: RingView<C> + ?Sized>(
+        &self,
+        rng: &mut R,
+        pedersen_generator: &PedersenGenerator<C>,
+        commitment_to_key: &Point<C>,
+        rings: &[&V],
+    ) -> Result<(), String> {
+        let statement = Self::statement(pedersen_generator, commitment_to_key, rings)?;
+        let mut multimult = MultiMult::new();
+        statement.verify(
+            rng,
+            &self.commitment,
+            &self.response,
+            Transcript::new(Self::HASH_ID),
+            &mut multimult,
+        )?;
+
+        if multimult.evaluate().is_identity() {
+            Ok(())
+        } else {
+            Err("failed to verify membership".to_owned())
+        }
+    }
+
+    fn statement<'a, V: RingView<C> + ?Sized>(
+        pedersen_generator: &'a PedersenGenerator<C>,
+        commitment_to_key: &'a Point<C>,
+        rings: &[&'a V],
+    ) -> Result<AnyOf<MembershipStatement<'a, C, V>>, String> {
+        if rings.is_empty() {
+            return Err("no rings given".to_string());
+        }
+        rings
+            .iter()
+            .map(|ring| MembershipStatement::new(pedersen_generator, commitment_to_key, *ring))
+            .collect::<Result<_, _>>()
+            .map(AnyOf)
+    }
+}
+
+/// Proof that the committed key is a member of every ring.
+///
+/// It is an AND composition of [`MembershipProof`]s on the same commitment,
+/// the transcript binds every ring, thus the proofs share a single
+/// challenge.
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
+pub struct AllMembershipProof<C: Curve> {
+    commitment: Vec<MembershipCommitment<C>>,
+    response: Vec<MembershipResponse<C>>,
+}
+
+impl<C: Curve> AllMembershipProof<C> {
+    const HASH_ID: &'static [u8] = b"all-membership-proof";
+
+    /// Generates the proof given the index of the key in each ring.
+    pub fn construct<R: CryptoCoreRng>(
+        rng: &mut R,
+        pedersen_generator: &PedersenGenerator<C>,
+        commitment_to_key: &PedersenCommitment<C>,
+        indices: &[usize],
+        rings: &[&[Scalar<C>]],
+    ) -> Result<Self, String> {
+        if indices.len() != rings.len() {
+            return Err("invalid number of indices".to_string());
+        }
+        if indices
+            .iter()
+            .zip(rings)
+            .any(|(index, ring)| *index >= ring.len())
+        {
+            return Err("invalid index".to_string());
         }
 
-        Scalar::<C>::new(hasher.finalize())
+        let statement = Self::statement(pedersen_generator, commitment_to_key.commitment(), rings)?;
+        let witness = indices
+            .iter()
+            .map(|index| MembershipWitness::new(*index, commitment_to_key))
+            .collect();
+        let (commitment, response) = statement.prove(rng, &witness, Transcript::new(Self::HASH_ID));
+
+        Ok(Self {
+            commitment,
+            response,
+        })
+    }
+
+    pub fn verify<R: CryptoCoreRng, V: RingView<C> + ?Sized>(
+        &self,
+        rng: &mut R,
+        pedersen_generator: &PedersenGenerator<C>,
+        commitment_to_key: &Point<C>,
+        rings: &[&V],
+    ) -> Result<(), String> {
+        let statement = Self::statement(pedersen_generator, commitment_to_key, rings)?;
+        let mut multimult = MultiMult::new();
+        statement.verify(
+            rng,
+            &self.commitment,
+            &self.response,
+            Transcript::new(Self::HASH_ID),
+            &mut multimult,
+        )?;
+
+        if multimult.evaluate().is_identity() {
+            Ok(())
+        } else {
+            Err("failed to verify membership".to_owned())
+        }
+    }
+
+    fn statement<'a, V: RingView<C> + ?Sized>(
+        pedersen_generator: &'a PedersenGenerator<C>,
+        commitment_to_key: &'a Point<C>,
+        rings: &[&'a V],
+    ) -> Result<AllOf<MembershipStatement<'a, C, V>>, String> {
+        if rings.is_empty() {
+            return Err("no rings given".to_string());
+        }
+        rings
+            .iter()
+            .map(|ring| MembershipStatement::new(pedersen_generator, commitment_to_key, *ring))
+            .collect::<Result<_, _>>()
+            .map(AllOf)
     }
 }
 
@@ -360,4 +776,135 @@ mod test {
             Err("failed to verify membership".to_string())
         );
     }
+
+    #[test]
+    fn any_membership_proof() {
+        let mut rng = StdRng::from_seed([41; 32]);
+        let pedersen_generator = PedersenGenerator::<Tom256k1>::new(&mut rng);
+        let rings = [(0..5u8), (5..8u8), (8..16u8)]
+            .into_iter()
+            .map(|range| {
+                range
+                    .map(|i| Scalar::<Tom256k1>::new(U256::from_u8(i)))
+                    .collect()
+            })
+            .collect::<Vec<Vec<_>>>();
+        let ring_refs = rings.iter().map(Vec::as_slice).collect::<Vec<_>>();
+
+        let commitment_to_key = pedersen_generator.commit(&mut rng, rings[1][2]);
+        let proof = AnyMembershipProof::construct(
+            &mut rng,
+            &pedersen_generator,
+            &commitment_to_key,
+            1,
+            2,
+            &ring_refs,
+        )
+        .unwrap();
+        assert!(proof
+            .verify(
+                &mut rng,
+                &pedersen_generator,
+                commitment_to_key.commitment(),
+                &ring_refs,
+            )
+            .is_ok());
+
+        // the key is not in any of the rings
+        let other_rings = [ring_refs[0], &rings[2][..3], ring_refs[2]];
+        assert_eq!(
+            proof.verify(
+                &mut rng,
+                &pedersen_generator,
+                commitment_to_key.commitment(),
+                &other_rings,
+            ),
+            Err("failed to verify membership".to_string())
+        );
+        assert_eq!(
+            proof.verify(
+                &mut rng,
+                &pedersen_generator,
+                commitment_to_key.commitment(),
+                &ring_refs[..2],
+            ),
+            Err("invalid number of branches".to_string())
+        );
+
+        assert_eq!(
+            AnyMembershipProof::construct(
+                &mut rng,
+                &pedersen_generator,
+                &commitment_to_key,
+                3,
+                0,
+                &ring_refs,
+            )
+            .err(),
+            Some("invalid ring index".to_string())
+        );
+    }
+
+    #[test]
+    fn all_membership_proof() {
+        let mut rng = StdRng::from_seed([42; 32]);
+        let pedersen_generator = PedersenGenerator::<Tom256k1>::new(&mut rng);
+        let rings = [(0..5u8), (3..6u8), (1..9u8)]
+            .into_iter()
+            .map(|range| {
+                range
+                    .map(|i| Scalar::<Tom256k1>::new(U256::from_u8(i)))
+                    .collect()
+            })
+            .collect::<Vec<Vec<_>>>();
+        let ring_refs = rings.iter().map(Vec::as_slice).collect::<Vec<_>>();
+
+        let key = Scalar::<Tom256k1>::new(U256::from_u8(4));
+        let commitment_to_key = pedersen_generator.commit(&mut rng, key);
+        let proof = AllMembershipProof::construct(
+            &mut rng,
+            &pedersen_generator,
+            &commitment_to_key,
+            &[4, 1, 3],
+            &ring_refs,
+        )
+        .unwrap();
+        assert!(proof
+            .verify(
+                &mut rng,
+                &pedersen_generator,
+                commitment_to_key.commitment(),
+                &ring_refs,
+            )
+            .is_ok());
+
+        // the key has to be in every ring
+        let other_rings = [ring_refs[0], &rings[0][..2], ring_refs[2]];
+        let proof = AllMembershipProof::construct(
+            &mut rng,
+            &pedersen_generator,
+            &commitment_to_key,
+            &[4, 0, 3],
+            &other_rings,
+        )
+        .unwrap();
+        assert_eq!(
+            proof.verify(
+                &mut rng,
+                &pedersen_generator,
+                commitment_to_key.commitment(),
+                &other_rings,
+            ),
+            Err("failed to verify membership".to_string())
+        );
+        assert_eq!(
+            proof.verify(
+                &mut rng,
+                &pedersen_generator,
+                commitment_to_key.commitment(),
+                &other_rings[1..],
+            ),
+            Err("invalid number of proofs".to_string())
+        );
+    }
 }
@@ -1,19 +1,64 @@
+mod balance;
+mod designated;
+mod distinctness;
 mod equality;
 mod exp;
+mod hidden_signature;
+mod inner_product;
+mod key_ownership;
+mod linkage;
 mod membership;
 mod multiplication;
+mod non_membership;
+mod nullifier;
 mod point_add;
+mod range;
+mod reduction;
+mod session;
+mod sigma;
+mod threshold;
 mod utils;
+mod weighted;
 
 // TODO these does not need to be public
-pub use exp::{ExpCommitmentPoints, ExpCommitments, ExpProof, ExpSecrets};
-pub use membership::MembershipProof;
+pub use balance::{BalanceAttestProof, BalanceMember};
+pub use designated::{DesignatedAttestProof, SchnorrStatement};
+pub use distinctness::DistinctnessProof;
+pub use equality::EqualityStatement;
+pub use exp::{
+    ExpChallenge, ExpCommitmentPoints, ExpCommitments, ExpProof, ExpSecrets, ExpStatement,
+    ExpWitness,
+};
+pub use hidden_signature::HiddenSignatureProof;
+pub use inner_product::InnerProductProof;
+pub use key_ownership::KeyOwnershipProof;
+pub use linkage::{KeyOpening, LinkageProof};
+pub use membership::{
+    AllMembershipProof, AnyMembershipProof, MembershipProof, MembershipStatement, MembershipWitness,
+};
+pub use non_membership::NonMembershipProof;
+pub use nullifier::{NullifierProof, NullifierStatement};
+pub use range::{BulletproofGenerators, RangeProof};
+pub use reduction::ReductionProof;
+pub use session::{Interactive, ProverSession, VerifierSession};
+pub use sigma::{AllOf, And, AnyOf, AnyOfWitness, Or, OrWitness, SigmaProtocol, Transcript};
+pub use threshold::{ThresholdAttestProof, ThresholdMember};
+pub use weighted::{
+    BalanceThresholdProof, WeightedAttestProof, WeightedMembershipProof, BALANCE_BITS,
+};
 
-use crate::arithmetic::{Modular, Point, Scalar};
+use exp::ChallengeShare;
+
+use crate::arithmetic::{AffinePoint, FieldElement, Modular, Point, Scalar};
+use crate::challenge::{ChallengeStore, JoinChallenge};
+use crate::claims::ProofClaims;
 use crate::curve::{Curve, Cycle};
-use crate::hasher::PointHasher;
-use crate::parse::{ParsedProofInput, ParsedRing};
-use crate::pedersen::PedersenCycle;
+use crate::message::MessageScheme;
+use crate::parse::{
+    locate_signer, ParsedProofInput, ParsedRing, Signature, SiweMessage, SiwePolicy, Witness,
+};
+use crate::pedersen::{PedersenCommitment, PedersenCycle};
+use crate::ring::{ring_digest, RingStore, RingView};
 use crate::rng::CryptoCoreRng;
 
 use borsh::{BorshDeserialize, BorshSerialize};
@@ -24,180 +69,1661 @@ const SEC_PARAM: usize = 60;
 #[cfg(test)]
 const SEC_PARAM: usize = 10;
 
-const MSG_PREFIX: &str = "\x19Ethereum Signed Message:\n";
-const JOIN_GUILD_MSG: &str = "#zkp/join.guild.xyz/";
+const NULLIFIER_MSG: &str = "#zkp/nullifier.guild.xyz/";
 
-/// Zero-knowledge proof consisting of an ECDSA and a Groth-Kohlweiss
-/// membership proof.
+/// Returns the `R` point of an ECDSA signature and `s1 = s / r`.
 ///
-/// Note, that the ring on which the membership proof is generated is not
-/// explicitly part of this proof because the backend does additional checks on
-/// its integrity before passing it to the veriication function.
+/// Since `(r, n - s)` is also a valid signature with `R` negated, proofs
+/// always commit to the `R` point with an even y coordinate. Thus a signature
+/// has a single public `R` regardless of the sign of `s` and proofs cannot be
+/// mauled into a second valid form.
+fn canonical_signature_point<C: Curve>(
+    msg_hash: Scalar<C>,
+    signature: &Signature<C>,
+    pubkey: &AffinePoint<C>,
+) -> (Point<C>, Scalar<C>) {
+    let s_inv = signature.s.inverse();
+    let u1 = s_inv * msg_hash;
+    let u2 = s_inv * signature.r;
+    let r_point = Point::<C>::GENERATOR.double_mul(&u1, &Point::from(pubkey), &u2);
+    let s1 = signature.r.inverse() * signature.s;
+    if r_point.to_affine().y().is_odd() {
+        (-r_point, -s1)
+    } else {
+        (r_point, s1)
+    }
+}
+
+/// Proof that the prover controls the public key committed in the
+/// [`ZkAttestProof`].
+#[allow(clippy::large_enum_variant)]
+#[derive(BorshDeserialize, BorshSerialize)]
+pub enum OwnershipProof<C: Curve, CC: Cycle<C>> {
+    /// ECDSA signature over the message hash with its public `R` point.
+    Signature {
+        r_point: Point<C>,
+        proof: ExpProof<C, CC>,
+    },
+    /// ECDSA signature over the message hash with its `R` point committed.
+    HiddenSignature(HiddenSignatureProof<C, CC>),
+    /// Knowledge of the secret key, bound to the message hash.
+    SecretKey(KeyOwnershipProof<C, CC>),
+}
+
+/// Checks that the message hash belongs to the canonical encoding of the
+/// challenge, or to the SIWE message embedding it.
+///
+/// A SIWE message is only accepted together with the verifier's policy and
+/// the current time.
+fn verify_message<C: Curve>(
+    msg_hash: Scalar<C>,
+    challenge: &JoinChallenge,
+    message_scheme: MessageScheme,
+    siwe: Option<&SiweMessage>,
+    siwe_policy: Option<(&SiwePolicy, u64)>,
+) -> Result<(), String> {
+    challenge.validate()?;
+    let expected_hash = match (siwe, siwe_policy) {
+        (Some(siwe), Some((policy, now))) => {
+            siwe.check_policy(policy, now)?;
+            siwe.check_challenge(challenge)?;
+            message_scheme.hash::<C>(&siwe.to_string())
+        }
+        (Some(_), None) => return Err("SIWE proofs require a SIWE policy".to_string()),
+        (None, Some(_)) => return Err("proof is not bound to a SIWE message".to_string()),
+        (None, None) => challenge.msg_hash::<C>(message_scheme)?,
+    };
+    if expected_hash != msg_hash {
+        return Err("Signed message hash mismatch".to_string());
+    }
+    Ok(())
+}
+
+/// Returns the index of the signer in the ring, checking the index given in
+/// the input.
+fn signer_index<C: Curve, CC: Cycle<C>>(
+    ring: &ParsedRing<CC>,
+    input: &ParsedProofInput<C>,
+) -> Result<usize, String> {
+    let pubkey_x = input.pubkey.x().to_cycle_scalar::<CC>();
+    match input.index {
+        Some(index) if ring.get(index) == Some(&pubkey_x) => Ok(index),
+        Some(_) => Err("signer is not at the given ring index".to_string()),
+        None => locate_signer(ring, &input.pubkey),
+    }
+}
+
+impl<C: Curve, CC: Cycle<C>> OwnershipProof<C, CC> {
+    /// Generates the ownership proof of the committed public key and returns
+    /// it together with the commitments it is stated on.
+    #[allow(clippy::too_many_arguments)]
+    fn construct<R: CryptoCoreRng>(
+        rng: &mut R,
+        pedersen: &PedersenCycle<C, CC>,
+        msg_hash: Scalar<C>,
+        witness: Witness<C>,
+        pubkey: AffinePoint<C>,
+        hide_r_point: bool,
+        commitment_to_pk_x: PedersenCommitment<CC>,
+        commitment_to_pk_y: PedersenCommitment<CC>,
+    ) -> Result<(Self, ExpCommitmentPoints<C, CC>), String> {
+        Self::construct_shared(
+            rng,
+            pedersen,
+            msg_hash,
+            witness,
+            pubkey,
+            hide_r_point,
+            commitment_to_pk_x,
+            commitment_to_pk_y,
+            None,
+        )
+    }
+
+    /// Generates the ownership proof with its challenge shared with another
+    /// proof, which is only supported for signatures with a public `R`.
+    #[allow(clippy::too_many_arguments)]
+    fn construct_shared<R: CryptoCoreRng>(
+        rng: &mut R,
+        pedersen: &PedersenCycle<C, CC>,
+        msg_hash: Scalar<C>,
+        witness: Witness<C>,
+        pubkey: AffinePoint<C>,
+        hide_r_point: bool,
+        commitment_to_pk_x: PedersenCommitment<CC>,
+        commitment_to_pk_y: PedersenCommitment<CC>,
+        share: Option<&ChallengeShare<C>>,
+    ) -> Result<(Self, ExpCommitmentPoints<C, CC>), String> {
+        if share.is_some() && (hide_r_point || matches!(witness, Witness::SecretKey(_))) {
+            return Err("designated proofs require a signature with a public R".to_string());
+        }
+        Ok(match witness {
+            Witness::Signature(signature) if hide_r_point => {
+                let (proof, exp_commitments) = HiddenSignatureProof::construct(
+                    rng,
+                    pedersen,
+                    msg_hash,
+                    &signature,
+                    &pubkey,
+                    commitment_to_pk_x,
+                    commitment_to_pk_y,
+                )?;
+                (OwnershipProof::HiddenSignature(proof), exp_commitments)
+            }
+            Witness::Signature(signature) => {
+                // generate ECDSA proof on signature
+                let (r_point, s1) = canonical_signature_point(msg_hash, &signature, &pubkey);
+                let r_inv = signature.r.inverse();
+                let z1 = r_inv * msg_hash;
+                let q_point = &Point::<C>::GENERATOR * z1;
+
+                let exp_secrets = ExpSecrets::new(s1, pubkey);
+                let exp_commitments = ExpCommitments {
+                    px: commitment_to_pk_x,
+                    py: commitment_to_pk_y,
+                    exp: pedersen.base().commit_with_generator(rng, s1, &r_point),
+                };
+
+                let proof = ExpProof::construct_shared(
+                    rng,
+                    &r_point,
+                    pedersen,
+                    &exp_secrets,
+                    &exp_commitments,
+                    SEC_PARAM,
+                    Some(q_point),
+                    share,
+                )?;
+                (
+                    OwnershipProof::Signature { r_point, proof },
+                    exp_commitments.into_commitments(),
+                )
+            }
+            Witness::SecretKey(secret_key) => {
+                let (proof, exp_commitments) = KeyOwnershipProof::construct(
+                    rng,
+                    pedersen,
+                    secret_key,
+                    msg_hash,
+                    commitment_to_pk_x,
+                    commitment_to_pk_y,
+                )?;
+                (OwnershipProof::SecretKey(proof), exp_commitments)
+            }
+        })
+    }
+
+    fn verify<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        pedersen: &PedersenCycle<C, CC>,
+        msg_hash: Scalar<C>,
+        exp_commitments: &ExpCommitmentPoints<C, CC>,
+    ) -> Result<(), String> {
+        self.verify_shared(rng, pedersen, msg_hash, exp_commitments, None)
+    }
+
+    fn verify_shared<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        pedersen: &PedersenCycle<C, CC>,
+        msg_hash: Scalar<C>,
+        exp_commitments: &ExpCommitmentPoints<C, CC>,
+        share: Option<&ChallengeShare<C>>,
+    ) -> Result<(), String> {
+        if share.is_some() && !matches!(self, OwnershipProof::Signature { .. }) {
+            return Err("designated proofs require a signature with a public R".to_string());
+        }
+        match self {
+            OwnershipProof::Signature { r_point, proof } => {
+                let r_point_affine = r_point.to_affine();
+                if r_point_affine.is_identity() {
+                    return Err("R is at infinity".to_string());
+                }
+                if r_point_affine.y().is_odd() {
+                    return Err("non-canonical R".to_string());
+                }
+
+                // NOTE the field element Rx is reduced modulo the curve order,
+                // just like r is derived in ECDSA (Rx >= n happens with
+                // negligible probability, see `Point::recover_pubkey`)
+                let r_inv = Scalar::<C>::new(*r_point_affine.x().inner()).inverse();
+                let z1 = r_inv * msg_hash;
+                let q_point = &Point::<C>::GENERATOR * z1;
+
+                proof.verify_shared(
+                    rng,
+                    r_point,
+                    pedersen,
+                    exp_commitments,
+                    SEC_PARAM,
+                    Some(q_point),
+                    share,
+                )
+            }
+            OwnershipProof::HiddenSignature(proof) => {
+                proof.verify(rng, pedersen, msg_hash, exp_commitments)
+            }
+            OwnershipProof::SecretKey(proof) => {
+                proof.verify(rng, pedersen, msg_hash, exp_commitments)
+            }
+        }
+    }
+}
+
+/// Proof that the committed public key is a member of the ring(s) the
+/// [`ZkAttestProof`] is generated on.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub enum RingProof<C: Curve> {
+    /// Membership in a single ring.
+    Single(MembershipProof<C>),
+    /// Membership in at least one of several rings, see
+    /// [`AnyMembershipProof`].
+    Any(AnyMembershipProof<C>),
+    /// Membership in every one of several rings, see
+    /// [`AllMembershipProof`].
+    All(AllMembershipProof<C>),
+}
+
+/// How the membership proofs on several rings are composed.
+///
+/// The verifier chooses the composition required by its policy, a proof
+/// composed differently is rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RingComposition {
+    /// Membership in a single ring.
+    Single,
+    /// Membership in at least one of the rings.
+    Any,
+    /// Membership in every ring.
+    All,
+}
+
+/// Zero-knowledge proof consisting of an ECDSA (or key ownership) and a
+/// Groth-Kohlweiss membership proof.
+///
+/// The signed message is the canonical encoding of the join challenge,
+/// including the application claims, either as plain text or as EIP-712 typed
+/// data. The challenge of the ECDSA proof depends
+/// on the message hash, thus the claims are bound both by the signature and
+/// the Fiat-Shamir transcript. Provers holding the secret key may prove its
+/// knowledge instead of a signature, see [`KeyOwnershipProof`]. Provers may
+/// also hide the `R` point of their signature, see [`HiddenSignatureProof`],
+/// the verifier accepts any of these.
+///
+/// The proof contains the canonical digests of the rings it was generated on,
+/// which are also part of the membership proof's transcript. Verifiers may use
+/// them to look up the matching ring snapshots, see [`ZkAttestProof::verify_snapshot`].
+/// A proof generated on several rings shows that the prover is a member of one
+/// of them or of all of them, see [`ZkAttestProof::construct_any`] and
+/// [`ZkAttestProof::construct_all`].
+///
+/// The proof may also show that the prover's key is not on a blocklist, see
+/// [`ZkAttestProof::construct_excluding`].
+///
+/// The commitments are made with the generators fixed by the protocol, see
+/// [`PedersenCycle::fixed`], the proof does not contain them.
 #[derive(BorshDeserialize, BorshSerialize)]
 pub struct ZkAttestProof<C: Curve, CC: Cycle<C>> {
-    pub pedersen: PedersenCycle<C, CC>,
     pub msg_hash: Scalar<C>,
-    pub r_point: Point<C>,
-    pub exp_commitments: ExpCommitmentPoints<C, CC>, // s1, pkx, pxy
-    pub signature_proof: ExpProof<C, CC>,
-    pub membership_proof: MembershipProof<CC>,
-    pub guild_id: String,
+    pub exp_commitments: ExpCommitmentPoints<C, CC>, // exp, pkx, pky
+    pub ownership_proof: OwnershipProof<C, CC>,
+    pub ring_digests: Vec<[u8; 32]>,
+    pub membership_proof: RingProof<CC>,
+    pub non_membership_proof: Option<NonMembershipProof<CC>>,
+    pub nullifier_proof: Option<NullifierProof<C, CC>>,
+    pub challenge: JoinChallenge,
+    pub message_scheme: MessageScheme,
+    pub siwe: Option<SiweMessage>,
 }
 
 impl<C: Curve, CC: Cycle<C>> ZkAttestProof<C, CC> {
     pub fn construct<R: CryptoCoreRng>(
         rng: &mut R,
-        pedersen: PedersenCycle<C, CC>,
         input: ParsedProofInput<C>,
         ring: &ParsedRing<CC>,
     ) -> Result<Self, String> {
-        let s_inv = input.signature.s.inverse();
-        let r_inv = input.signature.r.inverse();
-        let u1 = s_inv * input.msg_hash;
-        let u2 = s_inv * input.signature.r;
-        let r_point = Point::<C>::GENERATOR.double_mul(&u1, &Point::from(&input.pubkey), &u2);
-        let s1 = r_inv * input.signature.s;
-        let z1 = r_inv * input.msg_hash;
-        let q_point = &Point::<C>::GENERATOR * z1;
-
-        let commitment_to_s1 = pedersen.base().commit_with_generator(rng, s1, &r_point);
-        let commitment_to_pk_x = pedersen
-            .cycle()
-            .commit(rng, input.pubkey.x().to_cycle_scalar());
+        Self::construct_rings(rng, input, &[ring], RingComposition::Single, None, None)
+            .map(|(proof, _)| proof)
+    }
+
+    /// Generates the proof and returns it with the opening of the commitment
+    /// to the public key, which is needed to link it to other proofs later,
+    /// see [`ZkAttestProof::link`].
+    pub fn construct_with_opening<R: CryptoCoreRng>(
+        rng: &mut R,
+        input: ParsedProofInput<C>,
+        ring: &ParsedRing<CC>,
+    ) -> Result<(Self, KeyOpening<CC>), String> {
+        Self::construct_rings(rng, input, &[ring], RingComposition::Single, None, None)
+    }
+
+    /// Generates a proof of membership in the ring that also shows that the
+    /// signer is not on the blocklist, see [`NonMembershipProof`].
+    pub fn construct_excluding<R: CryptoCoreRng>(
+        rng: &mut R,
+        input: ParsedProofInput<C>,
+        ring: &ParsedRing<CC>,
+        blocklist: &ParsedRing<CC>,
+    ) -> Result<Self, String> {
+        Self::construct_rings(
+            rng,
+            input,
+            &[ring],
+            RingComposition::Single,
+            Some(blocklist),
+            None,
+        )
+        .map(|(proof, _)| proof)
+    }
+
+    /// Generates a proof of membership in at least one of the rings without
+    /// revealing which one.
+    ///
+    /// The signer is looked up in the rings in order, thus the ring index of
+    /// the input is not supported.
+    pub fn construct_any<R: CryptoCoreRng>(
+        rng: &mut R,
+        input: ParsedProofInput<C>,
+        rings: &[ParsedRing<CC>],
+    ) -> Result<Self, String> {
+        if input.index.is_some() {
+            return Err("ring index is not supported for multiple rings".to_string());
+        }
+        let rings = rings.iter().collect::<Vec<_>>();
+        Self::construct_rings(rng, input, &rings, RingComposition::Any, None, None)
+            .map(|(proof, _)| proof)
+    }
+
+    /// Generates a proof of membership in every ring.
+    ///
+    /// The ownership proof is shared by the membership proofs, which are
+    /// bound to the same commitment to the public key. The signer is looked
+    /// up in each ring, thus the ring index of the input is not supported.
+    pub fn construct_all<R: CryptoCoreRng>(
+        rng: &mut R,
+        input: ParsedProofInput<C>,
+        rings: &[ParsedRing<CC>],
+    ) -> Result<Self, String> {
+        if input.index.is_some() {
+            return Err("ring index is not supported for multiple rings".to_string());
+        }
+        let rings = rings.iter().collect::<Vec<_>>();
+        Self::construct_rings(rng, input, &rings, RingComposition::All, None, None)
+            .map(|(proof, _)| proof)
+    }
+
+    fn construct_rings<R: CryptoCoreRng>(
+        rng: &mut R,
+        input: ParsedProofInput<C>,
+        rings: &[&ParsedRing<CC>],
+        composition: RingComposition,
+        blocklist: Option<&ParsedRing<CC>>,
+        share: Option<&ChallengeShare<C>>,
+    ) -> Result<(Self, KeyOpening<CC>), String> {
+        let pedersen = PedersenCycle::fixed();
+        if let Some(siwe) = input.siwe.as_ref() {
+            // NOTE only single ring proofs have a verifier checking the SIWE
+            // policy, see `verify_siwe` and `verify_fresh`
+            if composition != RingComposition::Single || blocklist.is_some() || share.is_some() {
+                return Err("SIWE messages are not supported for this proof".to_string());
+            }
+            siwe.check_challenge(&input.challenge)?;
+        }
+
+        let pubkey_x = input.pubkey.x().to_cycle_scalar();
+        let commitment_to_pk_x = pedersen.cycle().commit(rng, pubkey_x);
+        let opening = KeyOpening::new(pubkey_x, &commitment_to_pk_x);
         let commitment_to_pk_y = pedersen
             .cycle()
             .commit(rng, input.pubkey.y().to_cycle_scalar());
 
         // generate membership proof on pubkey x coordinate
-        let membership_proof = MembershipProof::construct(
-            rng,
-            pedersen.cycle(),
-            &commitment_to_pk_x,
-            input.index,
-            ring,
-        )?;
+        let membership_proof = match composition {
+            RingComposition::Single => {
+                let ring = rings[0];
+                RingProof::Single(MembershipProof::construct(
+                    rng,
+                    pedersen.cycle(),
+                    &commitment_to_pk_x,
+                    signer_index(ring, &input)?,
+                    ring,
+                )?)
+            }
+            RingComposition::Any => {
+                let (ring_index, index) = rings
+                    .iter()
+                    .enumerate()
+                    .find_map(|(ring_index, ring)| {
+                        locate_signer(ring, &input.pubkey)
+                            .ok()
+                            .map(|index| (ring_index, index))
+                    })
+                    .ok_or_else(|| "signer is not in any of the rings".to_string())?;
+                let rings = rings.iter().map(|ring| ring.as_slice()).collect::<Vec<_>>();
+                RingProof::Any(AnyMembershipProof::construct(
+                    rng,
+                    pedersen.cycle(),
+                    &commitment_to_pk_x,
+                    ring_index,
+                    index,
+                    &rings,
+                )?)
+            }
+            RingComposition::All => {
+                let indices = rings
+                    .iter()
+                    .enumerate()
+                    .map(|(ring_index, ring)| {
+                        locate_signer(ring, &input.pubkey)
+                            .map_err(|_| format!("signer is not in ring {}", ring_index))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let rings = rings.iter().map(|ring| ring.as_slice()).collect::<Vec<_>>();
+                RingProof::All(AllMembershipProof::construct(
+                    rng,
+                    pedersen.cycle(),
+                    &commitment_to_pk_x,
+                    &indices,
+                    &rings,
+                )?)
+            }
+        };
+        let ring_digests = rings
+            .iter()
+            .map(|ring| ring_digest(*ring))
+            .collect::<Result<_, _>>()?;
+
+        let non_membership_proof = match blocklist {
+            Some(blocklist) => Some(NonMembershipProof::construct(
+                rng,
+                pedersen.cycle(),
+                &commitment_to_pk_x,
+                pubkey_x,
+                blocklist,
+            )?),
+            None => None,
+        };
 
-        // generate ECDSA proof on signature
-        let exp_secrets = ExpSecrets::new(s1, input.pubkey);
-        let exp_commitments = ExpCommitments {
-            px: commitment_to_pk_x,
-            py: commitment_to_pk_y,
-            exp: commitment_to_s1,
+        // generate optional linkability tag bound to the same pubkey commitments
+        let nullifier_proof = if input.nullifier {
+            let secret_key = match &input.witness {
+                Witness::SecretKey(secret_key) => *secret_key,
+                Witness::Signature(_) => {
+                    return Err("nullifiers require the secret key".to_string())
+                }
+            };
+            Some(NullifierProof::construct(
+                rng,
+                &pedersen,
+                secret_key,
+                &commitment_to_pk_x,
+                &commitment_to_pk_y,
+                &input.challenge.claims.guild_id,
+            )?)
+        } else {
+            None
         };
 
-        let signature_proof = ExpProof::construct(
+        let (ownership_proof, exp_commitments) = OwnershipProof::construct_shared(
             rng,
-            &r_point,
             &pedersen,
-            &exp_secrets,
-            &exp_commitments,
-            SEC_PARAM,
-            Some(q_point),
+            input.msg_hash,
+            input.witness,
+            input.pubkey,
+            input.hide_r_point,
+            commitment_to_pk_x,
+            commitment_to_pk_y,
+            share,
         )?;
 
-        Ok(Self {
-            pedersen,
+        let proof = Self {
             msg_hash: input.msg_hash,
-            r_point,
-            exp_commitments: exp_commitments.into_commitments(),
-            signature_proof,
+            exp_commitments,
+            ownership_proof,
+            ring_digests,
             membership_proof,
-            guild_id: input.guild_id,
-        })
+            non_membership_proof,
+            nullifier_proof,
+            challenge: input.challenge,
+            message_scheme: input.message_scheme,
+            siwe: input.siwe,
+        };
+        Ok((proof, opening))
     }
 
-    pub fn verify<R: CryptoCoreRng>(
+    /// Proves that this proof and the other one were generated with the same
+    /// key, given the openings returned by
+    /// [`ZkAttestProof::construct_with_opening`].
+    ///
+    /// The proofs themselves are not verified, see
+    /// [`ZkAttestProof::verify_link`].
+    pub fn link<R: CryptoCoreRng>(
         &self,
         rng: &mut R,
-        ring: &ParsedRing<CC>,
+        opening: &KeyOpening<CC>,
+        other: &Self,
+        other_opening: &KeyOpening<CC>,
+    ) -> Result<LinkageProof<CC>, String> {
+        let pedersen = PedersenCycle::fixed();
+        LinkageProof::construct(
+            rng,
+            self.linkage_statement(&pedersen, other),
+            opening,
+            other_opening,
+        )
+    }
+
+    /// Verifies that this proof and the other one were generated with the
+    /// same key.
+    ///
+    /// Only the linkage is checked, both proofs have to be verified on their
+    /// own rings as well.
+    pub fn verify_link<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        other: &Self,
+        linkage_proof: &LinkageProof<CC>,
     ) -> Result<(), String> {
-        let r_point_affine = self.r_point.to_affine();
-        if r_point_affine.is_identity() {
-            return Err("R is at infinity".to_string());
-        }
+        let pedersen = PedersenCycle::fixed();
+        linkage_proof.verify(rng, self.linkage_statement(&pedersen, other))
+    }
 
-        let expected_msg = JOIN_GUILD_MSG.to_string() + &self.guild_id;
-        let expected_msg_len = expected_msg.as_bytes().len().to_string();
-        let preimage = format!("{}{}{}", MSG_PREFIX, expected_msg_len, expected_msg);
-        let hasher = PointHasher::new(preimage.as_bytes());
-        let expected_hash = Scalar::<C>::new(hasher.finalize());
-        if expected_hash != self.msg_hash {
-            return Err("Signed message hash mismatch".to_string());
+    fn linkage_statement<'a>(
+        &'a self,
+        pedersen: &'a PedersenCycle<C, CC>,
+        other: &'a Self,
+    ) -> EqualityStatement<'a, CC> {
+        EqualityStatement {
+            pedersen_generator_1: pedersen.cycle(),
+            commitment_1: &self.exp_commitments.px,
+            pedersen_generator_2: pedersen.cycle(),
+            commitment_2: &other.exp_commitments.px,
         }
+    }
+
+    /// Verifies the proof and returns the claims bound to it.
+    ///
+    /// The ring may be any [`RingView`], e.g. a memory-mapped binary ring
+    /// file. Proofs on a SIWE message are rejected, they have to be checked
+    /// against the verifier's policy with [`Self::verify_siwe`].
+    pub fn verify<R: CryptoCoreRng, V: RingView<CC> + ?Sized>(
+        &self,
+        rng: &mut R,
+        ring: &V,
+    ) -> Result<&ProofClaims, String> {
+        self.verify_rings(rng, &[ring], RingComposition::Single)
+    }
+
+    /// Verifies a proof of membership in at least one of several rings,
+    /// given in the same order as at construction, and returns the claims
+    /// bound to it.
+    pub fn verify_any<R: CryptoCoreRng, V: RingView<CC> + ?Sized>(
+        &self,
+        rng: &mut R,
+        rings: &[&V],
+    ) -> Result<&ProofClaims, String> {
+        self.verify_rings(rng, rings, RingComposition::Any)
+    }
+
+    /// Verifies a proof of membership in every one of several rings, given
+    /// in the same order as at construction, and returns the claims bound to
+    /// it.
+    pub fn verify_all<R: CryptoCoreRng, V: RingView<CC> + ?Sized>(
+        &self,
+        rng: &mut R,
+        rings: &[&V],
+    ) -> Result<&ProofClaims, String> {
+        self.verify_rings(rng, rings, RingComposition::All)
+    }
 
-        // NOTE weird: a field element Rx is converted
-        // directly into a scalar
-        let r_inv = Scalar::<C>::new(*r_point_affine.x().inner()).inverse();
-        let z1 = r_inv * self.msg_hash;
-        let q_point = &Point::<C>::GENERATOR * z1;
+    fn verify_rings<R: CryptoCoreRng, V: RingView<CC> + ?Sized>(
+        &self,
+        rng: &mut R,
+        rings: &[&V],
+        composition: RingComposition,
+    ) -> Result<&ProofClaims, String> {
+        self.verify_shared(rng, rings, composition, None, None)
+    }
 
-        self.membership_proof
-            .verify(rng, self.pedersen.cycle(), &self.exp_commitments.px, ring)?;
+    fn verify_shared<R: CryptoCoreRng, V: RingView<CC> + ?Sized>(
+        &self,
+        rng: &mut R,
+        rings: &[&V],
+        composition: RingComposition,
+        siwe_policy: Option<(&SiwePolicy, u64)>,
+        share: Option<&ChallengeShare<C>>,
+    ) -> Result<&ProofClaims, String> {
+        if self.composition() != composition {
+            return Err("ring composition mismatch".to_string());
+        }
+        if rings.len() != self.ring_digests.len() {
+            return Err("ring count mismatch".to_string());
+        }
+        for (ring, digest) in rings.iter().zip(&self.ring_digests) {
+            if &ring_digest(*ring)? != digest {
+                return Err("ring digest mismatch".to_string());
+            }
+        }
 
-        self.signature_proof.verify(
+        verify_message(
+            self.msg_hash,
+            &self.challenge,
+            self.message_scheme,
+            self.siwe.as_ref(),
+            siwe_policy,
+        )?;
+
+        let pedersen = PedersenCycle::fixed();
+        self.ownership_proof.verify_shared(
             rng,
-            &self.r_point,
-            &self.pedersen,
+            &pedersen,
+            self.msg_hash,
             &self.exp_commitments,
-            SEC_PARAM,
-            Some(q_point),
+            share,
         )?;
 
-        Ok(())
+        match (&self.membership_proof, rings) {
+            (RingProof::Single(proof), [ring]) => {
+                proof.verify(rng, pedersen.cycle(), &self.exp_commitments.px, *ring)?
+            }
+            (RingProof::Single(_), _) => return Err("ring count mismatch".to_string()),
+            (RingProof::Any(proof), _) => {
+                proof.verify(rng, pedersen.cycle(), &self.exp_commitments.px, rings)?
+            }
+            (RingProof::All(proof), _) => {
+                proof.verify(rng, pedersen.cycle(), &self.exp_commitments.px, rings)?
+            }
+        }
+
+        if let Some(nullifier_proof) = self.nullifier_proof.as_ref() {
+            nullifier_proof.verify(
+                rng,
+                &pedersen,
+                &self.exp_commitments.px,
+                &self.exp_commitments.py,
+                &self.challenge.claims.guild_id,
+            )?;
+        }
+
+        Ok(&self.challenge.claims)
+    }
+
+    /// Verifies the proof and checks that the signer is not on the
+    /// blocklist.
+    ///
+    /// Proofs without a non-membership proof are rejected. Other verifiers
+    /// ignore the non-membership proof.
+    pub fn verify_excluding<
+        R: CryptoCoreRng,
+        V: RingView<CC> + ?Sized,
+        B: RingView<CC> + ?Sized,
+    >(
+        &self,
+        rng: &mut R,
+        ring: &V,
+        blocklist: &B,
+    ) -> Result<&ProofClaims, String> {
+        let claims = self.verify(rng, ring)?;
+        self.non_membership_proof
+            .as_ref()
+            .ok_or_else(|| "proof does not exclude a blocklist".to_string())?
+            .verify(
+                rng,
+                PedersenCycle::<C, CC>::fixed().cycle(),
+                &self.exp_commitments.px,
+                blocklist,
+            )?;
+        Ok(claims)
+    }
+
+    /// Verifies the proof and returns the claims together with the
+    /// guild-scoped linkability tag.
+    ///
+    /// Proofs without a tag are rejected, thus the verifier can require every
+    /// member to be linkable. Tags are only produced by provers holding the
+    /// secret key, see [`Self::nullifier`].
+    pub fn verify_nullifier<R: CryptoCoreRng, V: RingView<CC> + ?Sized>(
+        &self,
+        rng: &mut R,
+        ring: &V,
+    ) -> Result<(&ProofClaims, FieldElement<C>), String> {
+        let tag = self
+            .nullifier()
+            .ok_or_else(|| "proof does not contain a nullifier".to_string())?;
+        let claims = self.verify(rng, ring)?;
+        Ok((claims, tag))
+    }
+
+    /// Verifies the proof and checks that it answers an unexpired challenge
+    /// previously issued by the verifier.
+    ///
+    /// The issued challenge is removed from the store before any other check,
+    /// thus a challenge is consumed by the first verification attempt
+    /// regardless of its outcome. Proofs on a SIWE message are checked
+    /// against the given policy like in [`Self::verify_siwe`], they are
+    /// rejected without one.
+    pub fn verify_fresh<R: CryptoCoreRng, V: RingView<CC> + ?Sized, S: ChallengeStore>(
+        &self,
+        rng: &mut R,
+        ring: &V,
+        store: &mut S,
+        now: u64,
+        siwe_policy: Option<&SiwePolicy>,
+    ) -> Result<&ProofClaims, String> {
+        let issued = store
+            .take(&self.challenge.nonce)
+            .ok_or_else(|| "unknown or already used challenge".to_string())?;
+        if issued != self.challenge {
+            return Err("challenge mismatch".to_string());
+        }
+        self.challenge.check_expiry(now)?;
+        self.verify_shared(
+            rng,
+            &[ring],
+            RingComposition::Single,
+            siwe_policy.map(|policy| (policy, now)),
+            None,
+        )
+    }
+
+    /// Verifies a proof on a SIWE message and checks the message against the
+    /// verifier's policy at time `now`.
+    ///
+    /// The challenge is not consumed, see [`Self::verify_fresh`] for
+    /// single-use verification.
+    pub fn verify_siwe<R: CryptoCoreRng, V: RingView<CC> + ?Sized>(
+        &self,
+        rng: &mut R,
+        ring: &V,
+        policy: &SiwePolicy,
+        now: u64,
+    ) -> Result<&ProofClaims, String> {
+        self.verify_shared(
+            rng,
+            &[ring],
+            RingComposition::Single,
+            Some((policy, now)),
+            None,
+        )
+    }
+
+    /// Verifies the proof against the ring snapshots it was generated on.
+    ///
+    /// The composition of the membership proofs is required by the verifier,
+    /// it is not taken from the proof.
+    pub fn verify_snapshot<R: CryptoCoreRng, S: RingStore<CC>>(
+        &self,
+        rng: &mut R,
+        store: &S,
+        composition: RingComposition,
+    ) -> Result<&ProofClaims, String> {
+        let rings = self
+            .ring_digests
+            .iter()
+            .map(|digest| {
+                store
+                    .ring(digest)
+                    .ok_or_else(|| "unknown ring snapshot".to_string())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.verify_rings(rng, &rings, composition)
+    }
+
+    fn composition(&self) -> RingComposition {
+        match self.membership_proof {
+            RingProof::Single(_) => RingComposition::Single,
+            RingProof::Any(_) => RingComposition::Any,
+            RingProof::All(_) => RingComposition::All,
+        }
+    }
+
+    /// Returns the public `R` point of the signature if the proof is based on
+    /// one and it is not hidden.
+    pub fn r_point(&self) -> Option<&Point<C>> {
+        match &self.ownership_proof {
+            OwnershipProof::Signature { r_point, .. } => Some(r_point),
+            OwnershipProof::HiddenSignature(_) | OwnershipProof::SecretKey(_) => None,
+        }
+    }
+
+    /// Returns the guild-scoped linkability tag if the proof contains one.
+    ///
+    /// The backend may store these tags to detect the same key joining a
+    /// guild multiple times. Only proofs constructed with the secret key
+    /// carry a tag, signatures cannot produce one.
+    pub fn nullifier(&self) -> Option<FieldElement<C>> {
+        self.nullifier_proof.as_ref().map(NullifierProof::tag)
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::ZkAttestProof;
+    use super::*;
 
+    use crate::arithmetic::AffinePoint;
     use crate::curve::{Secp256k1, Tom256k1};
-    use crate::parse::{parse_ring, ParsedProofInput, ProofInput};
+    use crate::eip712::Eip712Domain;
+    use crate::parse::{claims_resource, parse_ring, ParsedProofInput, ProofInput, Signature};
     use crate::pedersen::PedersenCycle;
+    use crate::ring::{write_ring, RingSlice};
+    use crate::U256;
 
     use rand::rngs::StdRng;
     use rand_core::SeedableRng;
 
+    use std::collections::HashMap;
+
+    fn test_challenge() -> JoinChallenge {
+        let mut claims = ProofClaims::new("almafa".to_string());
+        claims.platform = Some("discord".to_string());
+        claims.platform_user_id = Some("1234".to_string());
+        JoinChallenge {
+            claims,
+            nonce: "9f3c2a71d04b5e86".to_string(),
+            expires_at: 1_700_000_000,
+            domain: None,
+        }
+    }
+
+    fn test_ring(pubkey: &AffinePoint<Secp256k1>, index: usize) -> ParsedRing<Tom256k1> {
+        let mut ring = (0..5u8)
+            .map(|i| Scalar::<Tom256k1>::new(U256::from_u8(i)))
+            .collect::<Vec<_>>();
+        ring[index] = pubkey.x().to_cycle_scalar();
+        ring
+    }
+
+    #[test]
+    fn canonical_r_point() {
+        let mut rng = StdRng::from_seed([19; 32]);
+        let secret = Scalar::<Secp256k1>::random(&mut rng);
+        let pubkey = (&Point::<Secp256k1>::GENERATOR * secret).to_affine();
+        let msg_hash = Scalar::<Secp256k1>::random(&mut rng);
+        let mut signature = Signature::sign(&secret, &msg_hash);
+
+        let (r_point, s1) = canonical_signature_point(msg_hash, &signature, &pubkey);
+        assert!(!r_point.to_affine().y().is_odd());
+        signature.s = -signature.s;
+        assert_eq!(
+            canonical_signature_point(msg_hash, &signature, &pubkey),
+            (r_point.clone(), s1)
+        );
+
+        // pk = s1 * R - z / r * G
+        let z1 = signature.r.inverse() * msg_hash;
+        assert_eq!(
+            r_point.double_mul(&s1, &Point::GENERATOR, &-z1),
+            Point::from(pubkey)
+        );
+    }
+
     #[test]
     fn zkp_attest_valid() {
         let mut rng = StdRng::from_seed([14; 32]);
-        let pedersen_cycle = PedersenCycle::<Secp256k1, Tom256k1>::new(&mut rng);
 
-        let msg_hash =
-            "0x9788117298a1450f6002d25f0c21d83bc6001681a2e5e31c748c0f55504b11e9".to_string();
-        let pubkey = "0454e32170dd5a0b7b641aa77daa1f3f31b8df17e51aaba6cfcb310848d26351180b6ac0399d21460443d10072700b64b454d70bfba5e93601536c740bbd099682".to_string();
-        let signature = "0xd2943d5fa0ba2733bcbbd58853c6c1be65388d9198dcb5228e117f49409612a46394afb97a7610d16e7bea0062e71afc2a3039324c80df8ef38d3668164fad2c1c".to_string();
+        let challenge = test_challenge();
+        let secret = Scalar::<Secp256k1>::random(&mut rng);
+        let pubkey = (&Point::<Secp256k1>::GENERATOR * secret).to_affine();
+        let msg_hash = MessageScheme::default().hash(&challenge.message());
+        let signature = Signature::sign(&secret, &msg_hash);
 
         let ring = vec![
             "c2ef144b59081382387f0ebf5d96b3a194f8c28961fa443000ea793ce534dac2".to_string(),
-            "54e32170dd5a0b7b641aa77daa1f3f31b8df17e51aaba6cfcb310848d2635118".to_string(), // our pubkey x
+            pubkey.x().to_string(), // our pubkey x
             "ddd40afe39c280d2f43f05c070988dae7fbae9cdfd5fb6461acd7657e765e172".to_string(),
-            "ccc50afe39c280d2f43f05c070988dae7fbae9cdfd5fb6461acd7657e765e172".to_string(),
+            "e493dbf1c10d80f3581e4904930b1404cc6c13900ee0758474fa94abe8c4cd13".to_string(),
             "1296d6ed4e96bc378b8a460de783cdfbf58afbe04b355f1c225fb3e0b92cdc6e".to_string(),
-            "aaa70afe39c280d2f43f05c070988dae7fbae9cdfd5fb6461acd7657e765e172".to_string(),
-            "bbb80afe39c280d2f43f05c070988dae7fbae9cdfd5fb6461acd7657e765e172".to_string(),
+            "2f8bde4d1a07209355b4a7250a5c5128e88b84bddc619ab7cba8d569b240efe4".to_string(),
+            "fff97bd5755eeea420453a14355235d382f6472f8568a18b2f057a1460297556".to_string(),
         ];
 
         let index = 1;
 
         let proof_input = ProofInput {
+            msg_hash: format!("0x{}", msg_hash),
+            pubkey: Some(format!("04{}{}", pubkey.x(), pubkey.y())),
+            signature: Some(format!(
+                "0x{}{}{:02x}",
+                signature.r,
+                signature.s,
+                27 + signature.recovery_id
+            )),
+            secret_key: None,
+            index: Some(index),
+            challenge,
+            nullifier: false,
+            message_scheme: MessageScheme::default(),
+            siwe_message: None,
+            strict_low_s: false,
+            hide_r_point: false,
+        };
+
+        let parsed_input: ParsedProofInput<Secp256k1> = proof_input.try_into().unwrap();
+        let parsed_ring = parse_ring(ring).unwrap();
+
+        let zkattest_proof =
+            ZkAttestProof::<Secp256k1, Tom256k1>::construct(&mut rng, parsed_input, &parsed_ring)
+                .unwrap();
+        assert_eq!(
+            zkattest_proof.verify(&mut rng, &parsed_ring),
+            Ok(&test_challenge().claims)
+        );
+
+        // the same ring in the binary format
+        let mut ring_file = std::io::Cursor::new(Vec::new());
+        write_ring(&parsed_ring, &mut ring_file).unwrap();
+        let ring_bytes = ring_file.into_inner();
+        let ring_slice = RingSlice::<Tom256k1>::new(&ring_bytes).unwrap();
+        assert!(zkattest_proof.verify(&mut rng, &ring_slice).is_ok());
+
+        // the proof is bound to the ring snapshot
+        let mut other_ring = parsed_ring.clone();
+        other_ring.swap(0, 2);
+        assert_eq!(
+            zkattest_proof.verify(&mut rng, &other_ring),
+            Err("ring digest mismatch".to_string())
+        );
+        let mut snapshots = HashMap::new();
+        snapshots.insert(ring_digest(&other_ring).unwrap(), other_ring);
+        assert_eq!(
+            zkattest_proof.verify_snapshot(&mut rng, &snapshots, RingComposition::Single),
+            Err("unknown ring snapshot".to_string())
+        );
+        snapshots.insert(ring_slice.header().digest, parsed_ring.clone());
+        assert!(zkattest_proof
+            .verify_snapshot(&mut rng, &snapshots, RingComposition::Single)
+            .is_ok());
+
+        // the signed message is bound to the claims
+        let mut tampered = zkattest_proof;
+        tampered.challenge.claims.platform_user_id = Some("4321".to_string());
+        assert_eq!(
+            tampered.verify(&mut rng, &parsed_ring),
+            Err("Signed message hash mismatch".to_string())
+        );
+
+        // and to the message scheme
+        tampered.challenge = test_challenge();
+        tampered.message_scheme = MessageScheme::Raw;
+        assert_eq!(
+            tampered.verify(&mut rng, &parsed_ring),
+            Err("Signed message hash mismatch".to_string())
+        );
+
+        // R is committed in its canonical form
+        tampered.message_scheme = MessageScheme::default();
+        if let OwnershipProof::Signature { r_point, .. } = &mut tampered.ownership_proof {
+            *r_point = -r_point.clone();
+        }
+        assert_eq!(
+            tampered.verify(&mut rng, &parsed_ring),
+            Err("non-canonical R".to_string())
+        );
+    }
+
+    #[test]
+    fn zkp_attest_fresh() {
+        let mut rng = StdRng::from_seed([16; 32]);
+
+        let challenge = test_challenge();
+        let secret = Scalar::<Secp256k1>::random(&mut rng);
+        let pubkey = (&Point::<Secp256k1>::GENERATOR * secret).to_affine();
+        let msg_hash = MessageScheme::default().hash(&challenge.message());
+        let ring = test_ring(&pubkey, 2);
+
+        let input = |index| ParsedProofInput {
             msg_hash,
-            pubkey,
-            signature,
+            pubkey: pubkey.clone(),
+            witness: Witness::Signature(Signature::sign(&secret, &msg_hash)),
             index,
-            guild_id: "almafa".to_string(),
+            challenge: challenge.clone(),
+            nullifier: false,
+            message_scheme: MessageScheme::default(),
+            siwe: None,
+            hide_r_point: false,
         };
 
+        assert_eq!(
+            ZkAttestProof::<Secp256k1, Tom256k1>::construct(&mut rng, input(Some(0)), &ring).err(),
+            Some("signer is not at the given ring index".to_string())
+        );
+
+        let zkattest_proof =
+            ZkAttestProof::<Secp256k1, Tom256k1>::construct(&mut rng, input(None), &ring).unwrap();
+
+        let mut store = HashMap::new();
+        let now = challenge.expires_at - 60;
+        assert_eq!(
+            zkattest_proof.verify_fresh(&mut rng, &ring, &mut store, now, None),
+            Err("unknown or already used challenge".to_string())
+        );
+
+        store.insert(challenge.nonce.clone(), challenge.clone());
+        assert_eq!(
+            zkattest_proof.verify_fresh(
+                &mut rng,
+                &ring,
+                &mut store,
+                challenge.expires_at + 1,
+                None
+            ),
+            Err("challenge expired".to_string())
+        );
+
+        let mut other = challenge.clone();
+        other.claims.role = Some("admin".to_string());
+        store.insert(challenge.nonce.clone(), other);
+        assert_eq!(
+            zkattest_proof.verify_fresh(&mut rng, &ring, &mut store, now, None),
+            Err("challenge mismatch".to_string())
+        );
+
+        store.insert(challenge.nonce.clone(), challenge.clone());
+        assert!(zkattest_proof
+            .verify_fresh(&mut rng, &ring, &mut store, now, None)
+            .is_ok());
+        assert_eq!(
+            zkattest_proof.verify_fresh(&mut rng, &ring, &mut store, now, None),
+            Err("unknown or already used challenge".to_string())
+        );
+    }
+
+    #[test]
+    fn zkp_attest_secret_key() {
+        let mut rng = StdRng::from_seed([19; 32]);
+
+        let challenge = test_challenge();
+        let secret = Scalar::<Secp256k1>::random(&mut rng);
+        let pubkey = (&Point::<Secp256k1>::GENERATOR * secret).to_affine();
+        let msg_hash: Scalar<Secp256k1> = MessageScheme::default().hash(&challenge.message());
+        let ring = test_ring(&pubkey, 3);
+
+        let proof_input = ProofInput {
+            msg_hash: format!("0x{}", msg_hash),
+            pubkey: Some(format!("04{}{}", pubkey.x(), pubkey.y())),
+            signature: None,
+            secret_key: Some(format!("0x{}", secret)),
+            index: Some(3),
+            challenge: challenge.clone(),
+            nullifier: false,
+            message_scheme: MessageScheme::default(),
+            siwe_message: None,
+            strict_low_s: false,
+            hide_r_point: false,
+        };
+
+        // the secret key has to belong to the given pubkey
+        let mut wrong_key = proof_input.clone();
+        wrong_key.secret_key = Some(format!("0x{}", secret + Scalar::ONE));
+        assert_eq!(
+            ParsedProofInput::<Secp256k1>::try_from(wrong_key).err(),
+            Some("pubkey does not match the secret key".to_string())
+        );
+
+        // there is no R point to hide
+        let mut hidden = proof_input.clone();
+        hidden.hide_r_point = true;
+        assert_eq!(
+            ParsedProofInput::<Secp256k1>::try_from(hidden).err(),
+            Some("R can only be hidden for signatures".to_string())
+        );
+
         let parsed_input: ParsedProofInput<Secp256k1> = proof_input.try_into().unwrap();
-        let parsed_ring = parse_ring(ring).unwrap();
+        assert!(matches!(parsed_input.witness, Witness::SecretKey(_)));
+
+        let zkattest_proof =
+            ZkAttestProof::<Secp256k1, Tom256k1>::construct(&mut rng, parsed_input, &ring).unwrap();
+        assert!(zkattest_proof.r_point().is_none());
+        assert_eq!(
+            zkattest_proof.verify(&mut rng, &ring),
+            Ok(&challenge.claims)
+        );
+    }
+
+    #[test]
+    fn zkp_attest_hidden_r() {
+        let mut rng = StdRng::from_seed([25; 32]);
+
+        let challenge = test_challenge();
+        let secret = Scalar::<Secp256k1>::random(&mut rng);
+        let pubkey = (&Point::<Secp256k1>::GENERATOR * secret).to_affine();
+        let msg_hash = MessageScheme::default().hash(&challenge.message());
+        let ring = test_ring(&pubkey, 1);
+
+        let input = ParsedProofInput {
+            msg_hash,
+            pubkey: pubkey.clone(),
+            witness: Witness::Signature(Signature::sign(&secret, &msg_hash)),
+            index: None,
+            challenge: challenge.clone(),
+            nullifier: false,
+            message_scheme: MessageScheme::default(),
+            siwe: None,
+            hide_r_point: true,
+        };
+
+        let zkattest_proof =
+            ZkAttestProof::<Secp256k1, Tom256k1>::construct(&mut rng, input, &ring).unwrap();
+        assert!(matches!(
+            zkattest_proof.ownership_proof,
+            OwnershipProof::HiddenSignature(_)
+        ));
+        assert!(zkattest_proof.r_point().is_none());
+        assert_eq!(
+            zkattest_proof.verify(&mut rng, &ring),
+            Ok(&challenge.claims)
+        );
+
+        // the proof survives serialization
+        let bytes = zkattest_proof.try_to_vec().unwrap();
+        let decoded = ZkAttestProof::<Secp256k1, Tom256k1>::try_from_slice(&bytes).unwrap();
+        assert!(decoded.verify(&mut rng, &ring).is_ok());
+    }
+
+    #[test]
+    fn zkp_attest_typed() {
+        let mut rng = StdRng::from_seed([17; 32]);
+
+        let mut challenge = test_challenge();
+        challenge.domain = Some(Eip712Domain {
+            name: "Guild".to_string(),
+            version: "1".to_string(),
+            chain_id: 1,
+            verifying_contract: "0x1679349AeA848f928cE886fbAE10a85660CBFecE".to_string(),
+        });
+        let secret = Scalar::<Secp256k1>::random(&mut rng);
+        let pubkey = (&Point::<Secp256k1>::GENERATOR * secret).to_affine();
+        let msg_hash = challenge.msg_hash(MessageScheme::default()).unwrap();
+        assert_ne!(
+            msg_hash,
+            MessageScheme::default().hash(&challenge.message())
+        );
+        let ring = test_ring(&pubkey, 0);
+
+        let input = ParsedProofInput {
+            msg_hash,
+            pubkey,
+            witness: Witness::Signature(Signature::sign(&secret, &msg_hash)),
+            index: Some(0),
+            challenge: challenge.clone(),
+            nullifier: false,
+            message_scheme: MessageScheme::default(),
+            siwe: None,
+            hide_r_point: false,
+        };
+
+        let zkattest_proof =
+            ZkAttestProof::<Secp256k1, Tom256k1>::construct(&mut rng, input, &ring).unwrap();
+        assert_eq!(
+            zkattest_proof.verify(&mut rng, &ring),
+            Ok(&challenge.claims)
+        );
+
+        // the typed data hash is bound to the domain
+        let mut tampered = zkattest_proof;
+        tampered.challenge.domain.as_mut().unwrap().chain_id = 5;
+        assert_eq!(
+            tampered.verify(&mut rng, &ring),
+            Err("Signed message hash mismatch".to_string())
+        );
+    }
+
+    #[test]
+    fn zkp_attest_siwe() {
+        let mut rng = StdRng::from_seed([18; 32]);
+
+        let challenge = test_challenge();
+        let siwe_message = format!(
+            "guild.xyz wants you to sign in with your Ethereum account:\n\n\n\
+            URI: https://guild.xyz/almafa\nVersion: 1\nChain ID: 1\nNonce: {}\n\
+            Issued At: 2023-11-14T21:13:20Z\nExpiration Time: 2023-11-14T22:13:20Z\n\
+            Resources:\n- {}",
+            challenge.nonce,
+            claims_resource(&challenge.claims)
+        );
+        let secret = Scalar::<Secp256k1>::random(&mut rng);
+        let pubkey = (&Point::<Secp256k1>::GENERATOR * secret).to_affine();
+        let msg_hash = MessageScheme::default().hash(&siwe_message);
+        let signature = Signature::sign(&secret, &msg_hash);
+        let ring = test_ring(&pubkey, 4);
+
+        let proof_input = ProofInput {
+            msg_hash: format!("0x{}", msg_hash),
+            pubkey: None,
+            signature: Some(format!(
+                "0x{}{}{:02x}",
+                signature.r,
+                signature.s,
+                27 + signature.recovery_id
+            )),
+            secret_key: None,
+            index: Some(4),
+            challenge: challenge.clone(),
+            nullifier: false,
+            message_scheme: MessageScheme::default(),
+            siwe_message: Some(siwe_message),
+            strict_low_s: false,
+            hide_r_point: false,
+        };
+        let input: ParsedProofInput<Secp256k1> = proof_input.clone().try_into().unwrap();
+
+        let zkattest_proof =
+            ZkAttestProof::<Secp256k1, Tom256k1>::construct(&mut rng, input, &ring).unwrap();
+
+        // proofs without a SIWE-aware verifier cannot be generated
+        let mut input: ParsedProofInput<Secp256k1> = proof_input.try_into().unwrap();
+        input.index = None;
+        assert_eq!(
+            ZkAttestProof::<Secp256k1, Tom256k1>::construct_any(&mut rng, input, &[ring.clone()])
+                .err(),
+            Some("SIWE messages are not supported for this proof".to_string())
+        );
+
+        let policy = SiwePolicy {
+            domain: "guild.xyz".to_string(),
+            chain_id: Some(1),
+        };
+        let now = challenge.expires_at - 60;
+        assert_eq!(
+            zkattest_proof.verify_siwe(&mut rng, &ring, &policy, now),
+            Ok(&challenge.claims)
+        );
+        assert_eq!(
+            zkattest_proof.verify_siwe(&mut rng, &ring, &policy, challenge.expires_at + 1),
+            Err("SIWE message expired".to_string())
+        );
+
+        // the policy cannot be skipped
+        assert_eq!(
+            zkattest_proof.verify(&mut rng, &ring),
+            Err("SIWE proofs require a SIWE policy".to_string())
+        );
+
+        // the challenge of the SIWE message can only be used once
+        let mut store = HashMap::new();
+        store.insert(challenge.nonce.clone(), challenge.clone());
+        assert_eq!(
+            zkattest_proof.verify_fresh(&mut rng, &ring, &mut store, now, Some(&policy)),
+            Ok(&challenge.claims)
+        );
+        assert_eq!(
+            zkattest_proof.verify_fresh(&mut rng, &ring, &mut store, now, Some(&policy)),
+            Err("unknown or already used challenge".to_string())
+        );
+        store.insert(challenge.nonce.clone(), challenge.clone());
+        assert_eq!(
+            zkattest_proof.verify_fresh(&mut rng, &ring, &mut store, now, None),
+            Err("SIWE proofs require a SIWE policy".to_string())
+        );
+
+        // the SIWE message is bound to the claims
+        let mut tampered = zkattest_proof;
+        tampered.challenge.claims.role = Some("admin".to_string());
+        assert_eq!(
+            tampered.verify_siwe(&mut rng, &ring, &policy, now),
+            Err("SIWE message does not bind the claims".to_string())
+        );
+
+        // and cannot be altered
+        tampered.challenge = challenge;
+        tampered.siwe.as_mut().unwrap().uri = "https://guild.xyz/kortefa".to_string();
+        assert_eq!(
+            tampered.verify_siwe(&mut rng, &ring, &policy, now),
+            Err("Signed message hash mismatch".to_string())
+        );
+    }
+
+    #[test]
+    fn zkp_attest_with_nullifier() {
+        let mut rng = StdRng::from_seed([15; 32]);
+
+        let challenge = test_challenge();
+        let secret = Scalar::<Secp256k1>::random(&mut rng);
+        let pubkey = (&Point::<Secp256k1>::GENERATOR * secret).to_affine();
+        let scheme = MessageScheme::Bitcoin;
+        let msg_hash = scheme.hash(&challenge.message());
+        let ring = test_ring(&pubkey, 3);
+
+        let input = |witness| ParsedProofInput {
+            msg_hash,
+            pubkey: pubkey.clone(),
+            witness,
+            index: Some(3),
+            challenge: challenge.clone(),
+            nullifier: true,
+            message_scheme: scheme,
+            siwe: None,
+            hide_r_point: false,
+        };
 
         let zkattest_proof = ZkAttestProof::<Secp256k1, Tom256k1>::construct(
             &mut rng,
-            pedersen_cycle,
-            parsed_input,
-            &parsed_ring,
+            input(Witness::SecretKey(secret)),
+            &ring,
+        )
+        .unwrap();
+        assert!(zkattest_proof.verify(&mut rng, &ring).is_ok());
+
+        // the tag is the secret key times the guild generator
+        let guild_generator = nullifier::guild_generator::<Secp256k1>(&challenge.claims.guild_id);
+        let expected_tag = *(&guild_generator * secret).to_affine().x();
+        assert_eq!(zkattest_proof.nullifier(), Some(expected_tag));
+        assert_eq!(
+            zkattest_proof.verify_nullifier(&mut rng, &ring),
+            Ok((&challenge.claims, expected_tag))
+        );
+
+        // verifiers may require the tag
+        let mut untagged = input(Witness::SecretKey(secret));
+        untagged.nullifier = false;
+        let untagged_proof =
+            ZkAttestProof::<Secp256k1, Tom256k1>::construct(&mut rng, untagged, &ring).unwrap();
+        assert!(untagged_proof.verify(&mut rng, &ring).is_ok());
+        assert_eq!(
+            untagged_proof.verify_nullifier(&mut rng, &ring),
+            Err("proof does not contain a nullifier".to_string())
+        );
+
+        // and it does not depend on the proof randomness
+        let other_proof = ZkAttestProof::<Secp256k1, Tom256k1>::construct(
+            &mut rng,
+            input(Witness::SecretKey(secret)),
+            &ring,
+        )
+        .unwrap();
+        assert_eq!(other_proof.nullifier(), Some(expected_tag));
+
+        // the tag is bound to the guild id
+        let proof = zkattest_proof;
+        assert_eq!(
+            proof.nullifier_proof.as_ref().unwrap().verify(
+                &mut rng,
+                &PedersenCycle::fixed(),
+                &proof.exp_commitments.px,
+                &proof.exp_commitments.py,
+                "kortefa",
+            ),
+            Err("failed to verify nullifier".to_string())
+        );
+
+        // and to the committed key
+        assert!(proof
+            .nullifier_proof
+            .as_ref()
+            .unwrap()
+            .verify(
+                &mut rng,
+                &PedersenCycle::fixed(),
+                &other_proof.exp_commitments.px,
+                &other_proof.exp_commitments.py,
+                &challenge.claims.guild_id,
+            )
+            .is_err());
+
+        // signatures cannot produce a tag
+        assert_eq!(
+            ZkAttestProof::<Secp256k1, Tom256k1>::construct(
+                &mut rng,
+                input(Witness::Signature(Signature::sign(&secret, &msg_hash))),
+                &ring,
+            )
+            .err(),
+            Some("nullifiers require the secret key".to_string())
+        );
+    }
+
+    #[test]
+    fn zkp_attest_any_ring() {
+        let mut rng = StdRng::from_seed([41; 32]);
+
+        let challenge = test_challenge();
+        let secret = Scalar::<Secp256k1>::random(&mut rng);
+        let pubkey = (&Point::<Secp256k1>::GENERATOR * secret).to_affine();
+        let msg_hash = MessageScheme::default().hash(&challenge.message());
+        let other_ring = (5..8u8)
+            .map(|i| Scalar::<Tom256k1>::new(U256::from_u8(i)))
+            .collect::<Vec<_>>();
+        let rings = vec![other_ring.clone(), test_ring(&pubkey, 4), other_ring];
+
+        let input = |index| ParsedProofInput {
+            msg_hash,
+            pubkey: pubkey.clone(),
+            witness: Witness::Signature(Signature::sign(&secret, &msg_hash)),
+            index,
+            challenge: challenge.clone(),
+            nullifier: false,
+            message_scheme: MessageScheme::default(),
+            siwe: None,
+            hide_r_point: false,
+        };
+
+        assert_eq!(
+            ZkAttestProof::<Secp256k1, Tom256k1>::construct_any(&mut rng, input(Some(4)), &rings)
+                .err(),
+            Some("ring index is not supported for multiple rings".to_string())
+        );
+        assert_eq!(
+            ZkAttestProof::<Secp256k1, Tom256k1>::construct_any(
+                &mut rng,
+                input(None),
+                &[rings[0].clone()]
+            )
+            .err(),
+            Some("signer is not in any of the rings".to_string())
+        );
+
+        let zkattest_proof =
+            ZkAttestProof::<Secp256k1, Tom256k1>::construct_any(&mut rng, input(None), &rings)
+                .unwrap();
+        assert!(matches!(zkattest_proof.membership_proof, RingProof::Any(_)));
+        let ring_refs = rings.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        assert_eq!(
+            zkattest_proof.verify_any(&mut rng, &ring_refs),
+            Ok(&challenge.claims)
+        );
+        assert_eq!(
+            zkattest_proof.verify(&mut rng, &rings[1]),
+            Err("ring composition mismatch".to_string())
+        );
+        assert_eq!(
+            zkattest_proof.verify_any(&mut rng, &[ring_refs[1], ring_refs[0], ring_refs[2]]),
+            Err("ring digest mismatch".to_string())
+        );
+
+        let mut snapshots = HashMap::new();
+        for ring in rings.iter() {
+            snapshots.insert(ring_digest(ring).unwrap(), ring.clone());
+        }
+        assert!(zkattest_proof
+            .verify_snapshot(&mut rng, &snapshots, RingComposition::Any)
+            .is_ok());
+        // an OR proof does not satisfy a verifier requiring every ring
+        assert_eq!(
+            zkattest_proof.verify_all(&mut rng, &ring_refs),
+            Err("ring composition mismatch".to_string())
+        );
+        assert_eq!(
+            zkattest_proof.verify_snapshot(&mut rng, &snapshots, RingComposition::All),
+            Err("ring composition mismatch".to_string())
+        );
+
+        let bytes = zkattest_proof.try_to_vec().unwrap();
+        let decoded = ZkAttestProof::<Secp256k1, Tom256k1>::try_from_slice(&bytes).unwrap();
+        assert!(decoded.verify_any(&mut rng, &ring_refs).is_ok());
+    }
+
+    #[test]
+    fn zkp_attest_all_rings() {
+        let mut rng = StdRng::from_seed([42; 32]);
+
+        let challenge = test_challenge();
+        let secret = Scalar::<Secp256k1>::random(&mut rng);
+        let pubkey = (&Point::<Secp256k1>::GENERATOR * secret).to_affine();
+        let msg_hash = MessageScheme::default().hash(&challenge.message());
+        let rings = vec![test_ring(&pubkey, 0), test_ring(&pubkey, 3)];
+
+        let input = || ParsedProofInput {
+            msg_hash,
+            pubkey: pubkey.clone(),
+            witness: Witness::Signature(Signature::sign(&secret, &msg_hash)),
+            index: None,
+            challenge: challenge.clone(),
+            nullifier: false,
+            message_scheme: MessageScheme::default(),
+            siwe: None,
+            hide_r_point: false,
+        };
+
+        let mut other_rings = rings.clone();
+        other_rings[1][3] = Scalar::ZERO;
+        assert_eq!(
+            ZkAttestProof::<Secp256k1, Tom256k1>::construct_all(&mut rng, input(), &other_rings)
+                .err(),
+            Some("signer is not in ring 1".to_string())
+        );
+
+        let zkattest_proof =
+            ZkAttestProof::<Secp256k1, Tom256k1>::construct_all(&mut rng, input(), &rings).unwrap();
+        assert!(matches!(zkattest_proof.membership_proof, RingProof::All(_)));
+        let ring_refs = rings.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        assert_eq!(
+            zkattest_proof.verify_all(&mut rng, &ring_refs),
+            Ok(&challenge.claims)
+        );
+        assert_eq!(
+            zkattest_proof.verify_all(&mut rng, &ring_refs[..1]),
+            Err("ring count mismatch".to_string())
+        );
+        assert_eq!(
+            zkattest_proof.verify_any(&mut rng, &ring_refs),
+            Err("ring composition mismatch".to_string())
+        );
+    }
+
+    #[test]
+    fn zkp_attest_excluding() {
+        let mut rng = StdRng::from_seed([43; 32]);
+
+        let challenge = test_challenge();
+        let secret = Scalar::<Secp256k1>::random(&mut rng);
+        let pubkey = (&Point::<Secp256k1>::GENERATOR * secret).to_affine();
+        let msg_hash = MessageScheme::default().hash(&challenge.message());
+        let ring = test_ring(&pubkey, 2);
+        let blocklist = (5..11u8)
+            .map(|i| Scalar::<Tom256k1>::new(U256::from_u8(i)))
+            .collect::<Vec<_>>();
+
+        let input = || ParsedProofInput {
+            msg_hash,
+            pubkey: pubkey.clone(),
+            witness: Witness::Signature(Signature::sign(&secret, &msg_hash)),
+            index: None,
+            challenge: challenge.clone(),
+            nullifier: false,
+            message_scheme: MessageScheme::default(),
+            siwe: None,
+            hide_r_point: false,
+        };
+
+        let mut banned = blocklist.clone();
+        banned[4] = pubkey.x().to_cycle_scalar();
+        assert_eq!(
+            ZkAttestProof::<Secp256k1, Tom256k1>::construct_excluding(
+                &mut rng,
+                input(),
+                &ring,
+                &banned,
+            )
+            .err(),
+            Some("key is on the blocklist".to_string())
+        );
+
+        let zkattest_proof = ZkAttestProof::<Secp256k1, Tom256k1>::construct_excluding(
+            &mut rng,
+            input(),
+            &ring,
+            &blocklist,
         )
         .unwrap();
-        assert!(zkattest_proof.verify(&mut rng, &parsed_ring).is_ok());
+        assert_eq!(
+            zkattest_proof.verify_excluding(&mut rng, &ring, &blocklist),
+            Ok(&challenge.claims)
+        );
+        assert_eq!(
+            zkattest_proof.verify_excluding(&mut rng, &ring, &banned),
+            Err("blocklist digest mismatch".to_string())
+        );
+        assert!(zkattest_proof.verify(&mut rng, &ring).is_ok());
+
+        let zkattest_proof =
+            ZkAttestProof::<Secp256k1, Tom256k1>::construct(&mut rng, input(), &ring).unwrap();
+        assert_eq!(
+            zkattest_proof.verify_excluding(&mut rng, &ring, &blocklist),
+            Err("proof does not exclude a blocklist".to_string())
+        );
+    }
+
+    #[test]
+    fn zkp_attest_link() {
+        let mut rng = StdRng::from_seed([50; 32]);
+        let challenge = test_challenge();
+        let msg_hash = MessageScheme::default().hash(&challenge.message());
+        let secrets = (0..2)
+            .map(|_| Scalar::<Secp256k1>::random(&mut rng))
+            .collect::<Vec<_>>();
+        let pubkeys = secrets
+            .iter()
+            .map(|secret| (&Point::<Secp256k1>::GENERATOR * *secret).to_affine())
+            .collect::<Vec<_>>();
+        let input = |i: usize| ParsedProofInput {
+            msg_hash,
+            pubkey: pubkeys[i].clone(),
+            witness: Witness::Signature(Signature::sign(&secrets[i], &msg_hash)),
+            index: None,
+            challenge: challenge.clone(),
+            nullifier: false,
+            message_scheme: MessageScheme::default(),
+            siwe: None,
+            hide_r_point: false,
+        };
+
+        // memberships of the same key in two guilds, with separate generators
+        let mut prove = |i: usize, ring: &ParsedRing<Tom256k1>| {
+            ZkAttestProof::construct_with_opening(&mut rng, input(i), ring).unwrap()
+        };
+        let ring_1 = test_ring(&pubkeys[0], 1);
+        let mut ring_2 = test_ring(&pubkeys[0], 3);
+        ring_2[0] = pubkeys[1].x().to_cycle_scalar();
+        let (proof_1, opening_1) = prove(0, &ring_1);
+        let (proof_2, opening_2) = prove(0, &ring_2);
+        let (other_proof, other_opening) = prove(1, &ring_2);
+
+        let linkage_proof = proof_1
+            .link(&mut rng, &opening_1, &proof_2, &opening_2)
+            .unwrap();
+        assert_eq!(
+            proof_1.verify_link(&mut rng, &proof_2, &linkage_proof),
+            Ok(())
+        );
+        assert_eq!(
+            proof_1.verify_link(&mut rng, &other_proof, &linkage_proof),
+            Err("failed to verify linkage".to_string())
+        );
+        assert!(proof_1.verify(&mut rng, &ring_1).is_ok());
+        assert!(proof_2.verify(&mut rng, &ring_2).is_ok());
+
+        assert_eq!(
+            proof_1
+                .link(&mut rng, &opening_1, &other_proof, &other_opening)
+                .err(),
+            Some("openings are of different keys".to_string())
+        );
+        assert_eq!(
+            proof_1
+                .link(&mut rng, &opening_2, &proof_2, &opening_2)
+                .err(),
+            Some("opening does not match the commitment".to_string())
+        );
     }
 }
@@ -1,7 +1,7 @@
+use super::sigma::{SigmaProtocol, Transcript};
 use crate::arithmetic::multimult::{MultiMult, Relation};
-use crate::arithmetic::{Modular, Point, Scalar};
+use crate::arithmetic::{Point, Scalar};
 use crate::curve::Curve;
-use crate::hasher::PointHasher;
 use crate::pedersen::*;
 use crate::rng::CryptoCoreRng;
 
@@ -9,14 +9,56 @@ use borsh::{BorshDeserialize, BorshSerialize};
 
 use std::ops::Neg;
 
+/// Statement that the Pedersen commitments open to `x`, `y` and `z = x·y`.
+#[derive(Clone, Copy)]
+pub struct MultiplicationStatement<'a, C: Curve> {
+    pub pedersen_generator: &'a PedersenGenerator<C>,
+    pub commitment_to_x: &'a Point<C>,
+    pub commitment_to_y: &'a Point<C>,
+    pub commitment_to_z: &'a Point<C>,
+}
+
+pub struct MultiplicationWitness<C: Curve> {
+    x: Scalar<C>,
+    y: Scalar<C>,
+    z: Scalar<C>,
+    randomness_x: Scalar<C>,
+    randomness_y: Scalar<C>,
+    randomness_z: Scalar<C>,
+}
+
+impl<C: Curve> MultiplicationWitness<C> {
+    pub fn new(
+        commitment_to_x: &PedersenCommitment<C>,
+        commitment_to_y: &PedersenCommitment<C>,
+        commitment_to_z: &PedersenCommitment<C>,
+        x: Scalar<C>,
+        y: Scalar<C>,
+        z: Scalar<C>,
+    ) -> Self {
+        Self {
+            x,
+            y,
+            z,
+            randomness_x: *commitment_to_x.randomness(),
+            randomness_y: *commitment_to_y.randomness(),
+            randomness_z: *commitment_to_z.randomness(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, BorshDeserialize, BorshSerialize)]
-pub struct MultiplicationProof<C: Curve> {
+pub struct MultiplicationCommitment<C: Curve> {
     c4: Point<C>,
     commitment_to_random_1: Point<C>,
     commitment_to_random_2: Point<C>,
     commitment_to_random_3: Point<C>,
     a4_1: Point<C>,
     a4_2: Point<C>,
+}
+
+#[derive(Clone, Debug, BorshDeserialize, BorshSerialize)]
+pub struct MultiplicationResponse<C: Curve> {
     mask_x: Scalar<C>,
     mask_y: Scalar<C>,
     mask_z: Scalar<C>,
@@ -26,140 +68,242 @@ pub struct MultiplicationProof<C: Curve> {
     mask_r4: Scalar<C>,
 }
 
-impl<C: Curve> MultiplicationProof<C> {
-    const HASH_ID: &'static [u8] = b"multiplication-proof";
+pub struct MultiplicationNonce<C: Curve> {
+    random_scalars: [Scalar<C>; 3],
+    random_randomness: [Scalar<C>; 3],
+    a4_randomness: Scalar<C>,
+}
 
-    #[allow(clippy::too_many_arguments)]
-    pub fn construct<R: CryptoCoreRng>(
+impl<'a, C: Curve> SigmaProtocol<C> for MultiplicationStatement<'a, C> {
+    type Witness = MultiplicationWitness<C>;
+    type Commitment = MultiplicationCommitment<C>;
+    type Response = MultiplicationResponse<C>;
+    type Nonce = MultiplicationNonce<C>;
+
+    fn commit<R: CryptoCoreRng>(
+        &self,
         rng: &mut R,
-        pedersen_generator: &PedersenGenerator<C>,
-        commitment_to_x: &PedersenCommitment<C>,
-        commitment_to_y: &PedersenCommitment<C>,
-        commitment_to_z: &PedersenCommitment<C>,
-        x: Scalar<C>,
-        y: Scalar<C>,
-        z: Scalar<C>,
-    ) -> Self {
-        let c4 = commitment_to_y.commitment() * x;
-        let r4 = commitment_to_y.randomness() * &x;
+        witness: &Self::Witness,
+    ) -> (Self::Commitment, Self::Nonce) {
+        let c4 = self.commitment_to_y * witness.x;
 
         let random_scalar_1 = Scalar::random(rng);
         let random_scalar_2 = Scalar::random(rng);
         let random_scalar_3 = Scalar::random(rng);
 
-        let commitment_to_random_1 = pedersen_generator.commit(rng, random_scalar_1);
-        let commitment_to_random_2 = pedersen_generator.commit(rng, random_scalar_2);
-        let commitment_to_random_3 = pedersen_generator.commit(rng, random_scalar_3);
-
-        let a4_1 = pedersen_generator.commit(rng, random_scalar_3);
-        let a4_2 = commitment_to_y.commitment() * random_scalar_1;
-
-        let mut hasher = PointHasher::new(Self::HASH_ID);
-        hasher.insert_points(&[
-            commitment_to_x.commitment(),
-            commitment_to_y.commitment(),
-            commitment_to_z.commitment(),
-            &c4,
-            commitment_to_random_1.commitment(),
-            commitment_to_random_2.commitment(),
-            commitment_to_random_3.commitment(),
-            a4_1.commitment(),
-            &a4_2,
-        ]);
-        let challenge = hasher.finalize();
-        let challenge_scalar = Scalar::new(challenge);
-
-        let mask_x = random_scalar_1 - challenge_scalar * x;
-        let mask_y = random_scalar_2 - challenge_scalar * y;
-        let mask_z = random_scalar_3 - challenge_scalar * z;
-
-        let mask_random_x = commitment_to_random_1
-            .randomness()
-            .sub(&(&challenge_scalar * commitment_to_x.randomness()));
-        let mask_random_y = commitment_to_random_2
-            .randomness()
-            .sub(&(&challenge_scalar * commitment_to_y.randomness()));
-        let mask_random_z = commitment_to_random_3
-            .randomness()
-            .sub(&(&challenge_scalar * commitment_to_z.randomness()));
-        let mask_r4 = a4_1.randomness().sub(&(challenge_scalar * r4));
-
-        Self {
+        let commitment_to_random_1 = self.pedersen_generator.commit(rng, random_scalar_1);
+        let commitment_to_random_2 = self.pedersen_generator.commit(rng, random_scalar_2);
+        let commitment_to_random_3 = self.pedersen_generator.commit(rng, random_scalar_3);
+
+        let a4_1 = self.pedersen_generator.commit(rng, random_scalar_3);
+        let a4_2 = self.commitment_to_y * random_scalar_1;
+
+        let nonce = MultiplicationNonce {
+            random_scalars: [random_scalar_1, random_scalar_2, random_scalar_3],
+            random_randomness: [
+                *commitment_to_random_1.randomness(),
+                *commitment_to_random_2.randomness(),
+                *commitment_to_random_3.randomness(),
+            ],
+            a4_randomness: *a4_1.randomness(),
+        };
+        let commitment = MultiplicationCommitment {
             c4,
             commitment_to_random_1: commitment_to_random_1.into_commitment(),
             commitment_to_random_2: commitment_to_random_2.into_commitment(),
             commitment_to_random_3: commitment_to_random_3.into_commitment(),
             a4_1: a4_1.into_commitment(),
             a4_2,
-            mask_x,
-            mask_y,
-            mask_z,
-            mask_random_x,
-            mask_random_y,
-            mask_random_z,
-            mask_r4,
+        };
+        (commitment, nonce)
+    }
+
+    fn absorb(&self, commitment: &Self::Commitment, transcript: &mut Transcript) {
+        transcript.append_points(&[
+            self.commitment_to_x,
+            self.commitment_to_y,
+            self.commitment_to_z,
+            &commitment.c4,
+            &commitment.commitment_to_random_1,
+            &commitment.commitment_to_random_2,
+            &commitment.commitment_to_random_3,
+            &commitment.a4_1,
+            &commitment.a4_2,
+        ]);
+    }
+
+    fn respond(
+        &self,
+        witness: &Self::Witness,
+        nonce: Self::Nonce,
+        challenge: Scalar<C>,
+    ) -> Self::Response {
+        let [random_scalar_1, random_scalar_2, random_scalar_3] = nonce.random_scalars;
+        let [random_1, random_2, random_3] = nonce.random_randomness;
+        let r4 = witness.randomness_y * witness.x;
+        MultiplicationResponse {
+            mask_x: random_scalar_1 - challenge * witness.x,
+            mask_y: random_scalar_2 - challenge * witness.y,
+            mask_z: random_scalar_3 - challenge * witness.z,
+            mask_random_x: random_1 - challenge * witness.randomness_x,
+            mask_random_y: random_2 - challenge * witness.randomness_y,
+            mask_random_z: random_3 - challenge * witness.randomness_z,
+            mask_r4: nonce.a4_randomness - challenge * r4,
         }
     }
 
-    pub fn aggregate<R: CryptoCoreRng>(
+    fn simulate<R: CryptoCoreRng>(
         &self,
         rng: &mut R,
-        pedersen_generator: &PedersenGenerator<C>,
-        commitment_to_x: &Point<C>,
-        commitment_to_y: &Point<C>,
-        commitment_to_z: &Point<C>,
-        multimult: &mut MultiMult<C>,
-    ) {
-        let mut hasher = PointHasher::new(Self::HASH_ID);
-        hasher.insert_points(&[
-            commitment_to_x,
-            commitment_to_y,
-            commitment_to_z,
-            &self.c4,
-            &self.commitment_to_random_1,
-            &self.commitment_to_random_2,
-            &self.commitment_to_random_3,
-            &self.a4_1,
-            &self.a4_2,
-        ]);
-        let challenge = hasher.finalize();
-        let challenge_scalar = Scalar::new(challenge);
+        challenge: Scalar<C>,
+    ) -> (Self::Commitment, Self::Response) {
+        let response = MultiplicationResponse {
+            mask_x: Scalar::random(rng),
+            mask_y: Scalar::random(rng),
+            mask_z: Scalar::random(rng),
+            mask_random_x: Scalar::random(rng),
+            mask_random_y: Scalar::random(rng),
+            mask_random_z: Scalar::random(rng),
+            mask_r4: Scalar::random(rng),
+        };
+        let generator = self.pedersen_generator.generator();
+        let masked = |mask: &Scalar<C>, mask_random: &Scalar<C>, point: &Point<C>| {
+            &generator.double_mul(mask_random, &Point::<C>::GENERATOR, mask) + &(point * challenge)
+        };
+        let c4 = self
+            .pedersen_generator
+            .commit_with_randomness(Scalar::random(rng), Scalar::random(rng))
+            .into_commitment();
+        let commitment = MultiplicationCommitment {
+            commitment_to_random_1: masked(
+                &response.mask_x,
+                &response.mask_random_x,
+                self.commitment_to_x,
+            ),
+            commitment_to_random_2: masked(
+                &response.mask_y,
+                &response.mask_random_y,
+                self.commitment_to_y,
+            ),
+            commitment_to_random_3: masked(
+                &response.mask_z,
+                &response.mask_random_z,
+                self.commitment_to_z,
+            ),
+            a4_1: masked(&response.mask_z, &response.mask_r4, &c4),
+            a4_2: self
+                .commitment_to_y
+                .double_mul(&response.mask_x, &c4, &challenge),
+            c4,
+        };
+        (commitment, response)
+    }
 
+    fn verify_into<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        commitment: &Self::Commitment,
+        challenge: Scalar<C>,
+        response: &Self::Response,
+        multimult: &mut MultiMult<C>,
+    ) -> Result<(), String> {
+        let generator = self.pedersen_generator.generator();
         let mut relation_x = Relation::new();
         let mut relation_y = Relation::new();
         let mut relation_z = Relation::new();
         let mut relation_a4_1 = Relation::<C>::new();
         let mut relation_a4_2 = Relation::<C>::new();
 
-        relation_x.insert(Point::<C>::GENERATOR, self.mask_x);
-        relation_x.insert(pedersen_generator.generator().clone(), self.mask_random_x);
-        relation_x.insert(commitment_to_x.clone(), challenge_scalar);
-        relation_x.insert((&self.commitment_to_random_1).neg(), Scalar::<C>::ONE);
+        relation_x.insert(Point::<C>::GENERATOR, response.mask_x);
+        relation_x.insert(generator.clone(), response.mask_random_x);
+        relation_x.insert(self.commitment_to_x.clone(), challenge);
+        relation_x.insert((&commitment.commitment_to_random_1).neg(), Scalar::<C>::ONE);
 
-        relation_y.insert(Point::<C>::GENERATOR, self.mask_y);
-        relation_y.insert(pedersen_generator.generator().clone(), self.mask_random_y);
-        relation_y.insert(commitment_to_y.clone(), challenge_scalar);
-        relation_y.insert((&self.commitment_to_random_2).neg(), Scalar::<C>::ONE);
+        relation_y.insert(Point::<C>::GENERATOR, response.mask_y);
+        relation_y.insert(generator.clone(), response.mask_random_y);
+        relation_y.insert(self.commitment_to_y.clone(), challenge);
+        relation_y.insert((&commitment.commitment_to_random_2).neg(), Scalar::<C>::ONE);
 
-        relation_z.insert(Point::<C>::GENERATOR, self.mask_z);
-        relation_z.insert(pedersen_generator.generator().clone(), self.mask_random_z);
-        relation_z.insert(commitment_to_z.clone(), challenge_scalar);
-        relation_z.insert((&self.commitment_to_random_3).neg(), Scalar::<C>::ONE);
+        relation_z.insert(Point::<C>::GENERATOR, response.mask_z);
+        relation_z.insert(generator.clone(), response.mask_random_z);
+        relation_z.insert(self.commitment_to_z.clone(), challenge);
+        relation_z.insert((&commitment.commitment_to_random_3).neg(), Scalar::<C>::ONE);
 
-        relation_a4_1.insert(Point::<C>::GENERATOR, self.mask_z);
-        relation_a4_1.insert(pedersen_generator.generator().clone(), self.mask_r4);
-        relation_a4_1.insert(self.c4.clone(), challenge_scalar);
-        relation_a4_1.insert((&self.a4_1).neg(), Scalar::<C>::ONE);
+        relation_a4_1.insert(Point::<C>::GENERATOR, response.mask_z);
+        relation_a4_1.insert(generator.clone(), response.mask_r4);
+        relation_a4_1.insert(commitment.c4.clone(), challenge);
+        relation_a4_1.insert((&commitment.a4_1).neg(), Scalar::<C>::ONE);
 
-        relation_a4_2.insert(commitment_to_y.clone(), self.mask_x);
-        relation_a4_2.insert(self.c4.clone(), challenge_scalar);
-        relation_a4_2.insert((&self.a4_2).neg(), Scalar::<C>::ONE);
+        relation_a4_2.insert(self.commitment_to_y.clone(), response.mask_x);
+        relation_a4_2.insert(commitment.c4.clone(), challenge);
+        relation_a4_2.insert((&commitment.a4_2).neg(), Scalar::<C>::ONE);
 
         relation_x.drain(rng, multimult);
         relation_y.drain(rng, multimult);
         relation_z.drain(rng, multimult);
         relation_a4_1.drain(rng, multimult);
         relation_a4_2.drain(rng, multimult);
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, BorshDeserialize, BorshSerialize)]
+pub struct MultiplicationProof<C: Curve> {
+    commitment: MultiplicationCommitment<C>,
+    response: MultiplicationResponse<C>,
+}
+
+impl<C: Curve> MultiplicationProof<C> {
+    const HASH_ID: &'static [u8] = b"multiplication-proof";
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn construct<R: CryptoCoreRng>(
+        rng: &mut R,
+        pedersen_generator: &PedersenGenerator<C>,
+        commitment_to_x: &PedersenCommitment<C>,
+        commitment_to_y: &PedersenCommitment<C>,
+        commitment_to_z: &PedersenCommitment<C>,
+        x: Scalar<C>,
+        y: Scalar<C>,
+        z: Scalar<C>,
+    ) -> Self {
+        let statement = MultiplicationStatement {
+            pedersen_generator,
+            commitment_to_x: commitment_to_x.commitment(),
+            commitment_to_y: commitment_to_y.commitment(),
+            commitment_to_z: commitment_to_z.commitment(),
+        };
+        let witness =
+            MultiplicationWitness::new(commitment_to_x, commitment_to_y, commitment_to_z, x, y, z);
+        let (commitment, response) = statement.prove(rng, &witness, Transcript::new(Self::HASH_ID));
+        Self {
+            commitment,
+            response,
+        }
+    }
+
+    pub fn aggregate<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        pedersen_generator: &PedersenGenerator<C>,
+        commitment_to_x: &Point<C>,
+        commitment_to_y: &Point<C>,
+        commitment_to_z: &Point<C>,
+        multimult: &mut MultiMult<C>,
+    ) -> Result<(), String> {
+        let statement = MultiplicationStatement {
+            pedersen_generator,
+            commitment_to_x,
+            commitment_to_y,
+            commitment_to_z,
+        };
+        statement.verify(
+            rng,
+            &self.commitment,
+            &self.response,
+            Transcript::new(Self::HASH_ID),
+            multimult,
+        )
     }
 
     #[cfg(test)]
@@ -179,8 +323,9 @@ impl<C: Curve> MultiplicationProof<C> {
             commitment_to_y,
             commitment_to_z,
             &mut multimult,
-        );
-        multimult.evaluate().is_identity()
+        )
+        .is_ok()
+            && multimult.evaluate().is_identity()
     }
 }
 
@@ -0,0 +1,238 @@
+use super::multiplication::{
+    MultiplicationCommitment, MultiplicationResponse, MultiplicationStatement,
+    MultiplicationWitness,
+};
+use super::sigma::{AllOf, SigmaProtocol, Transcript};
+use crate::arithmetic::multimult::MultiMult;
+use crate::arithmetic::{Modular, Point, Scalar};
+use crate::curve::Curve;
+use crate::pedersen::*;
+use crate::ring::{ring_digest, RingView};
+use crate::rng::CryptoCoreRng;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Proof that the committed key differs from every member of a blocklist.
+///
+/// For each blocklisted `b` the commitment to `key - b` is derived from the
+/// commitment to the key and the prover commits to its inverse, then shows
+/// that their product is one. The difference has no inverse if it is zero,
+/// thus the key cannot be on the blocklist. The multiplication proofs share
+/// a single challenge that also binds the digest of the blocklist.
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
+pub struct NonMembershipProof<C: Curve> {
+    blocklist_digest: [u8; 32],
+    commitments_to_inverses: Vec<Point<C>>,
+    commitment: Vec<MultiplicationCommitment<C>>,
+    response: Vec<MultiplicationResponse<C>>,
+}
+
+impl<C: Curve> NonMembershipProof<C> {
+    const HASH_ID: &'static [u8] = b"non-membership-proof";
+
+    pub fn construct<R: CryptoCoreRng>(
+        rng: &mut R,
+        pedersen_generator: &PedersenGenerator<C>,
+        commitment_to_key: &PedersenCommitment<C>,
+        key: Scalar<C>,
+        blocklist: &[Scalar<C>],
+    ) -> Result<Self, String> {
+        let blocklist_digest = ring_digest(blocklist)?;
+        // commitment to one with zero randomness
+        let commitment_to_one = PedersenCommitment::new(Point::<C>::GENERATOR, Scalar::<C>::ZERO);
+
+        let mut commitments_to_differences = Vec::with_capacity(blocklist.len());
+        let mut commitments_to_inverses = Vec::with_capacity(blocklist.len());
+        let mut witness = Vec::with_capacity(blocklist.len());
+        for member in blocklist {
+            let difference = key - *member;
+            if difference == Scalar::ZERO {
+                return Err("key is on the blocklist".to_string());
+            }
+            let inverse = difference.inverse();
+            let commitment_to_difference = PedersenCommitment::new(
+                Self::difference(commitment_to_key.commitment(), member),
+                *commitment_to_key.randomness(),
+            );
+            let commitment_to_inverse = pedersen_generator.commit(rng, inverse);
+            witness.push(MultiplicationWitness::new(
+                &commitment_to_difference,
+                &commitment_to_inverse,
+                &commitment_to_one,
+                difference,
+                inverse,
+                Scalar::ONE,
+            ));
+            commitments_to_differences.push(commitment_to_difference.into_commitment());
+            commitments_to_inverses.push(commitment_to_inverse.into_commitment());
+        }
+
+        let statement = inverse_statement(
+            pedersen_generator,
+            &commitments_to_differences,
+            &commitments_to_inverses,
+            commitment_to_one.commitment(),
+        );
+        let (commitment, response) =
+            statement.prove(rng, &witness, Self::transcript(&blocklist_digest));
+
+        Ok(Self {
+            blocklist_digest,
+            commitments_to_inverses,
+            commitment,
+            response,
+        })
+    }
+
+    pub fn verify<R: CryptoCoreRng, V: RingView<C> + ?Sized>(
+        &self,
+        rng: &mut R,
+        pedersen_generator: &PedersenGenerator<C>,
+        commitment_to_key: &Point<C>,
+        blocklist: &V,
+    ) -> Result<(), String> {
+        if ring_digest(blocklist)? != self.blocklist_digest {
+            return Err("blocklist digest mismatch".to_string());
+        }
+        if self.commitments_to_inverses.len() != blocklist.len() {
+            return Err("invalid number of inverses".to_string());
+        }
+
+        let commitments_to_differences = (0..blocklist.len())
+            .map(|i| Self::difference(commitment_to_key, &blocklist.member(i)))
+            .collect::<Vec<_>>();
+        let commitment_to_one = Point::<C>::GENERATOR;
+        let statement = inverse_statement(
+            pedersen_generator,
+            &commitments_to_differences,
+            &self.commitments_to_inverses,
+            &commitment_to_one,
+        );
+        let mut multimult = MultiMult::new();
+        statement.verify(
+            rng,
+            &self.commitment,
+            &self.response,
+            Self::transcript(&self.blocklist_digest),
+            &mut multimult,
+        )?;
+
+        if multimult.evaluate().is_identity() {
+            Ok(())
+        } else {
+            Err("failed to verify non-membership".to_owned())
+        }
+    }
+
+    /// Returns the canonical digest of the blocklist the proof is generated
+    /// on.
+    pub fn blocklist_digest(&self) -> &[u8; 32] {
+        &self.blocklist_digest
+    }
+
+    // commitment to `key - member` with the randomness of the key
+    fn difference(commitment_to_key: &Point<C>, member: &Scalar<C>) -> Point<C> {
+        commitment_to_key - &(&Point::<C>::GENERATOR * *member)
+    }
+
+    fn transcript(blocklist_digest: &[u8; 32]) -> Transcript {
+        let mut transcript = Transcript::new(Self::HASH_ID);
+        transcript.append_bytes(blocklist_digest);
+        transcript
+    }
+}
+
+/// Returns the statement that each committed difference times the committed
+/// inverse is one, i.e. that the differences are not zero.
+pub(super) fn inverse_statement<'a, C: Curve>(
+    pedersen_generator: &'a PedersenGenerator<C>,
+    commitments_to_differences: &'a [Point<C>],
+    commitments_to_inverses: &'a [Point<C>],
+    commitment_to_one: &'a Point<C>,
+) -> AllOf<MultiplicationStatement<'a, C>> {
+    AllOf(
+        commitments_to_differences
+            .iter()
+            .zip(commitments_to_inverses)
+            .map(
+                |(commitment_to_x, commitment_to_y)| MultiplicationStatement {
+                    pedersen_generator,
+                    commitment_to_x,
+                    commitment_to_y,
+                    commitment_to_z: commitment_to_one,
+                },
+            )
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curve::Tom256k1;
+    use crate::U256;
+    use rand::rngs::StdRng;
+    use rand_core::SeedableRng;
+
+    #[test]
+    fn non_membership_proof() {
+        let mut rng = StdRng::from_seed([43; 32]);
+        let pedersen_generator = PedersenGenerator::<Tom256k1>::new(&mut rng);
+        let blocklist = (0..5u8)
+            .map(|i| Scalar::<Tom256k1>::new(U256::from_u8(i)))
+            .collect::<Vec<_>>();
+
+        let key = Scalar::<Tom256k1>::new(U256::from_u8(7));
+        let commitment_to_key = pedersen_generator.commit(&mut rng, key);
+        let proof = NonMembershipProof::construct(
+            &mut rng,
+            &pedersen_generator,
+            &commitment_to_key,
+            key,
+            &blocklist,
+        )
+        .unwrap();
+        assert!(proof
+            .verify(
+                &mut rng,
+                &pedersen_generator,
+                commitment_to_key.commitment(),
+                &blocklist,
+            )
+            .is_ok());
+
+        // the proof is bound to the committed key and the blocklist
+        let other_commitment = pedersen_generator.commit(&mut rng, key);
+        assert_eq!(
+            proof.verify(
+                &mut rng,
+                &pedersen_generator,
+                other_commitment.commitment(),
+                &blocklist,
+            ),
+            Err("failed to verify non-membership".to_string())
+        );
+        assert_eq!(
+            proof.verify(
+                &mut rng,
+                &pedersen_generator,
+                commitment_to_key.commitment(),
+                &blocklist[1..],
+            ),
+            Err("blocklist digest mismatch".to_string())
+        );
+
+        let blocked_key = pedersen_generator.commit(&mut rng, blocklist[3]);
+        assert_eq!(
+            NonMembershipProof::construct(
+                &mut rng,
+                &pedersen_generator,
+                &blocked_key,
+                blocklist[3],
+                &blocklist,
+            )
+            .err(),
+            Some("key is on the blocklist".to_string())
+        );
+    }
+}
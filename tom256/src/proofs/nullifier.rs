@@ -0,0 +1,398 @@
+use super::exp::{ExpCommitmentPoints, ExpCommitments, ExpProof, ExpSecrets};
+use super::sigma::{SigmaProtocol, Transcript};
+use super::{NULLIFIER_MSG, SEC_PARAM};
+use crate::arithmetic::multimult::{MultiMult, Relation};
+use crate::arithmetic::{AffinePoint, FieldElement, Point, Scalar};
+use crate::curve::{Curve, Cycle};
+use crate::pedersen::{PedersenCommitment, PedersenCycle, PedersenGenerator};
+use crate::rng::CryptoCoreRng;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use std::ops::Neg;
+
+/// Guild-scoped linkability tag with a proof that it belongs to the committed
+/// public key.
+///
+/// The tag is `sk·H`, where `H` is hashed to the curve from
+/// `NULLIFIER_MSG + guild_id`, thus a key has exactly one tag per guild and
+/// it is infeasible to compute it from the public key alone. An [`ExpProof`]
+/// shows that the commitment to `sk` opens to the secret key of the public
+/// key committed in `px` and `py` of the main proof, while a
+/// [`NullifierStatement`] shows that the tag has the same discrete logarithm
+/// with respect to `H`.
+///
+/// The tag requires the secret key, signatures are not sufficient.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct NullifierProof<C: Curve, CC: Cycle<C>> {
+    tag: Point<C>,
+    commitment_to_sk: Point<C>,
+    exp_proof: ExpProof<C, CC>,
+    commitment: NullifierCommitment<C>,
+    response: NullifierResponse<C>,
+}
+
+impl<C: Curve, CC: Cycle<C>> NullifierProof<C, CC> {
+    const HASH_ID: &'static [u8] = b"nullifier-proof";
+
+    pub fn construct<R: CryptoCoreRng>(
+        rng: &mut R,
+        pedersen: &PedersenCycle<C, CC>,
+        secret_key: Scalar<C>,
+        commitment_to_pk_x: &PedersenCommitment<CC>,
+        commitment_to_pk_y: &PedersenCommitment<CC>,
+        guild_id: &str,
+    ) -> Result<Self, String> {
+        let guild_generator = guild_generator::<C>(guild_id);
+        let tag = &guild_generator * secret_key;
+        let pubkey = (&Point::<C>::GENERATOR * secret_key).to_affine();
+
+        let commitment_to_sk =
+            pedersen
+                .base()
+                .commit_with_generator(rng, secret_key, &Point::<C>::GENERATOR);
+        let witness = NullifierWitness::new(secret_key, &commitment_to_sk);
+        let exp_secrets = ExpSecrets::new(secret_key, pubkey);
+        let exp_commitments = ExpCommitments {
+            px: commitment_to_pk_x.clone(),
+            py: commitment_to_pk_y.clone(),
+            exp: commitment_to_sk,
+        };
+
+        let exp_proof = ExpProof::construct(
+            rng,
+            &Point::<C>::GENERATOR,
+            pedersen,
+            &exp_secrets,
+            &exp_commitments,
+            SEC_PARAM,
+            None,
+        )?;
+
+        let commitment_to_sk = exp_commitments.exp.into_commitment();
+        let statement = NullifierStatement {
+            pedersen_generator: pedersen.base(),
+            guild_generator: &guild_generator,
+            commitment_to_sk: &commitment_to_sk,
+            tag: &tag,
+        };
+        let (commitment, response) = statement.prove(rng, &witness, Transcript::new(Self::HASH_ID));
+
+        Ok(Self {
+            tag,
+            commitment_to_sk,
+            exp_proof,
+            commitment,
+            response,
+        })
+    }
+
+    pub fn verify<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        pedersen: &PedersenCycle<C, CC>,
+        commitment_to_pk_x: &Point<CC>,
+        commitment_to_pk_y: &Point<CC>,
+        guild_id: &str,
+    ) -> Result<(), String> {
+        if self.tag.is_identity() {
+            return Err("nullifier tag is at infinity".to_string());
+        }
+
+        let commitments = ExpCommitmentPoints::new(
+            self.commitment_to_sk.clone(),
+            commitment_to_pk_x.clone(),
+            commitment_to_pk_y.clone(),
+        );
+        self.exp_proof.verify(
+            rng,
+            &Point::<C>::GENERATOR,
+            pedersen,
+            &commitments,
+            SEC_PARAM,
+            None,
+        )?;
+
+        let guild_generator = guild_generator::<C>(guild_id);
+        let statement = NullifierStatement {
+            pedersen_generator: pedersen.base(),
+            guild_generator: &guild_generator,
+            commitment_to_sk: &self.commitment_to_sk,
+            tag: &self.tag,
+        };
+        let mut multimult = MultiMult::new();
+        statement.verify(
+            rng,
+            &self.commitment,
+            &self.response,
+            Transcript::new(Self::HASH_ID),
+            &mut multimult,
+        )?;
+
+        if multimult.evaluate().is_identity() {
+            Ok(())
+        } else {
+            Err("failed to verify nullifier".to_owned())
+        }
+    }
+
+    /// Returns the linkability tag that is unique per key and guild.
+    pub fn tag(&self) -> FieldElement<C> {
+        // NOTE ring members are identified by their x coordinate as well
+        *self.tag.to_affine().x()
+    }
+}
+
+/// Returns the point of unknown discrete logarithm the tag is computed on.
+pub(super) fn guild_generator<C: Curve>(guild_id: &str) -> Point<C> {
+    let label = NULLIFIER_MSG.to_string() + guild_id;
+    Point::from(AffinePoint::hash_to_curve(label.as_bytes(), 0))
+}
+
+/// Statement that the tag is the committed secret times the guild generator,
+/// i.e. a proof of discrete logarithm equality.
+#[derive(Clone, Copy)]
+pub struct NullifierStatement<'a, C: Curve> {
+    pub pedersen_generator: &'a PedersenGenerator<C>,
+    pub guild_generator: &'a Point<C>,
+    /// Commitment to the secret with the curve generator as value generator.
+    pub commitment_to_sk: &'a Point<C>,
+    pub tag: &'a Point<C>,
+}
+
+pub struct NullifierWitness<C: Curve> {
+    secret: Scalar<C>,
+    randomness: Scalar<C>,
+}
+
+impl<C: Curve> NullifierWitness<C> {
+    pub fn new(secret: Scalar<C>, commitment_to_sk: &PedersenCommitment<C>) -> Self {
+        Self {
+            secret,
+            randomness: *commitment_to_sk.randomness(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, BorshDeserialize, BorshSerialize)]
+pub struct NullifierCommitment<C: Curve> {
+    commitment_to_random: Point<C>,
+    tag_of_random: Point<C>,
+}
+
+#[derive(Clone, Debug, BorshDeserialize, BorshSerialize)]
+pub struct NullifierResponse<C: Curve> {
+    mask_secret: Scalar<C>,
+    mask_random: Scalar<C>,
+}
+
+impl<'a, C: Curve> SigmaProtocol<C> for NullifierStatement<'a, C> {
+    type Witness = NullifierWitness<C>;
+    type Commitment = NullifierCommitment<C>;
+    type Response = NullifierResponse<C>;
+    type Nonce = (Scalar<C>, Scalar<C>);
+
+    fn commit<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        _witness: &Self::Witness,
+    ) -> (Self::Commitment, Self::Nonce) {
+        let random_scalar = Scalar::random(rng);
+        let commitment_to_random = self.pedersen_generator.commit(rng, random_scalar);
+        let nonce = (random_scalar, *commitment_to_random.randomness());
+        let commitment = NullifierCommitment {
+            commitment_to_random: commitment_to_random.into_commitment(),
+            tag_of_random: self.guild_generator * random_scalar,
+        };
+        (commitment, nonce)
+    }
+
+    fn absorb(&self, commitment: &Self::Commitment, transcript: &mut Transcript) {
+        transcript.append_points(&[
+            self.guild_generator,
+            self.commitment_to_sk,
+            self.tag,
+            &commitment.commitment_to_random,
+            &commitment.tag_of_random,
+        ]);
+    }
+
+    fn respond(
+        &self,
+        witness: &Self::Witness,
+        nonce: Self::Nonce,
+        challenge: Scalar<C>,
+    ) -> Self::Response {
+        NullifierResponse {
+            mask_secret: nonce.0 - challenge * witness.secret,
+            mask_random: nonce.1 - challenge * witness.randomness,
+        }
+    }
+
+    fn simulate<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        challenge: Scalar<C>,
+    ) -> (Self::Commitment, Self::Response) {
+        let response = NullifierResponse {
+            mask_secret: Scalar::random(rng),
+            mask_random: Scalar::random(rng),
+        };
+        let commitment = NullifierCommitment {
+            commitment_to_random: &self.pedersen_generator.generator().double_mul(
+                &response.mask_random,
+                &Point::<C>::GENERATOR,
+                &response.mask_secret,
+            ) + &(self.commitment_to_sk * challenge),
+            tag_of_random: self.guild_generator.double_mul(
+                &response.mask_secret,
+                self.tag,
+                &challenge,
+            ),
+        };
+        (commitment, response)
+    }
+
+    fn verify_into<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        commitment: &Self::Commitment,
+        challenge: Scalar<C>,
+        response: &Self::Response,
+        multimult: &mut MultiMult<C>,
+    ) -> Result<(), String> {
+        let mut relation_1 = Relation::new();
+        let mut relation_2 = Relation::new();
+        relation_1.insert(Point::<C>::GENERATOR, response.mask_secret);
+        relation_1.insert(
+            self.pedersen_generator.generator().clone(),
+            response.mask_random,
+        );
+        relation_1.insert(self.commitment_to_sk.clone(), challenge);
+        relation_1.insert((&commitment.commitment_to_random).neg(), Scalar::ONE);
+
+        relation_2.insert(self.guild_generator.clone(), response.mask_secret);
+        relation_2.insert(self.tag.clone(), challenge);
+        relation_2.insert((&commitment.tag_of_random).neg(), Scalar::ONE);
+
+        relation_1.drain(rng, multimult);
+        relation_2.drain(rng, multimult);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arithmetic::Modular;
+    use crate::curve::{Secp256k1, Tom256k1};
+    use rand::rngs::StdRng;
+    use rand_core::SeedableRng;
+
+    #[test]
+    fn trapdoor_generator() {
+        let mut rng = StdRng::from_seed([52; 32]);
+        let secret_key = Scalar::<Secp256k1>::random(&mut rng);
+        let pubkey = (&Point::<Secp256k1>::GENERATOR * secret_key).to_affine();
+        let guild_generator = guild_generator::<Secp256k1>("almafa");
+        let honest_tag = *(&guild_generator * secret_key).to_affine().x();
+
+        let commit_to_pubkey = |rng: &mut StdRng, pedersen: &PedersenCycle<_, Tom256k1>| {
+            (
+                pedersen.cycle().commit(rng, pubkey.x().to_cycle_scalar()),
+                pedersen.cycle().commit(rng, pubkey.y().to_cycle_scalar()),
+            )
+        };
+
+        let pedersen = PedersenCycle::fixed();
+        let (px, py) = commit_to_pubkey(&mut rng, &pedersen);
+        let proof =
+            NullifierProof::construct(&mut rng, &pedersen, secret_key, &px, &py, "almafa").unwrap();
+        assert!(proof
+            .verify(
+                &mut rng,
+                &pedersen,
+                px.commitment(),
+                py.commitment(),
+                "almafa"
+            )
+            .is_ok());
+        assert_eq!(proof.tag(), honest_tag);
+
+        // a prover choosing its own generators knows their discrete logarithms
+        let trapdoor = Scalar::<Secp256k1>::random(&mut rng.clone());
+        let pedersen = PedersenCycle::<Secp256k1, Tom256k1>::new(&mut rng);
+        assert_eq!(
+            pedersen.base().generator(),
+            &(&Point::<Secp256k1>::GENERATOR * trapdoor)
+        );
+        let (px, py) = commit_to_pubkey(&mut rng, &pedersen);
+        let commitment_to_sk = pedersen.base().commit_with_generator(
+            &mut rng,
+            secret_key,
+            &Point::<Secp256k1>::GENERATOR,
+        );
+        let exp_proof = ExpProof::construct(
+            &mut rng,
+            &Point::<Secp256k1>::GENERATOR,
+            &pedersen,
+            &ExpSecrets::new(secret_key, pubkey.clone()),
+            &ExpCommitments {
+                px: px.clone(),
+                py: py.clone(),
+                exp: commitment_to_sk.clone(),
+            },
+            SEC_PARAM,
+            None,
+        )
+        .unwrap();
+
+        // thus it can open the commitment to the secret key to another secret
+        // and mint a second tag for the same key
+        let other_secret = Scalar::<Secp256k1>::random(&mut rng);
+        let forged_commitment = PedersenCommitment::new(
+            commitment_to_sk.commitment().clone(),
+            (secret_key - other_secret) * trapdoor.inverse() + *commitment_to_sk.randomness(),
+        );
+        let tag = &guild_generator * other_secret;
+        let statement = NullifierStatement {
+            pedersen_generator: pedersen.base(),
+            guild_generator: &guild_generator,
+            commitment_to_sk: forged_commitment.commitment(),
+            tag: &tag,
+        };
+        let (commitment, response) = statement.prove(
+            &mut rng,
+            &NullifierWitness::new(other_secret, &forged_commitment),
+            Transcript::new(NullifierProof::<Secp256k1, Tom256k1>::HASH_ID),
+        );
+        let forged = NullifierProof {
+            tag,
+            commitment_to_sk: forged_commitment.into_commitment(),
+            exp_proof,
+            commitment,
+            response,
+        };
+        assert_ne!(forged.tag(), honest_tag);
+        assert!(forged
+            .verify(
+                &mut rng,
+                &pedersen,
+                px.commitment(),
+                py.commitment(),
+                "almafa"
+            )
+            .is_ok());
+
+        // which is rejected with the generators fixed by the verifier
+        assert!(forged
+            .verify(
+                &mut rng,
+                &PedersenCycle::fixed(),
+                px.commitment(),
+                py.commitment(),
+                "almafa",
+            )
+            .is_err());
+    }
+}
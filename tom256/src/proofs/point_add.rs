@@ -1,13 +1,16 @@
+use super::equality::{EqualityCommitment, EqualityResponse, EqualityStatement, EqualityWitness};
+use super::multiplication::{
+    MultiplicationCommitment, MultiplicationResponse, MultiplicationStatement,
+    MultiplicationWitness,
+};
+use super::sigma::{And, SigmaProtocol, Transcript};
 use crate::arithmetic::multimult::MultiMult;
 use crate::arithmetic::AffinePoint;
-use crate::arithmetic::{Modular, Point, Scalar};
+use crate::arithmetic::{FieldElement, Modular, Point, Scalar};
 use crate::curve::{Curve, Cycle};
 use crate::pedersen::*;
 use crate::rng::CryptoCoreRng;
 
-use super::equality::EqualityProof;
-use super::multiplication::MultiplicationProof;
-
 use borsh::{BorshDeserialize, BorshSerialize};
 use std::marker::PhantomData;
 
@@ -78,8 +81,19 @@ pub struct PointAddCommitments<C: Curve> {
     pub(crate) ry: PedersenCommitment<C>,
 }
 
-#[cfg(test)]
 impl<C: Curve> PointAddCommitments<C> {
+    fn points(&self) -> PointAddCommitmentPoints<C> {
+        PointAddCommitmentPoints {
+            px: self.px.commitment().clone(),
+            py: self.py.commitment().clone(),
+            qx: self.qx.commitment().clone(),
+            qy: self.qy.commitment().clone(),
+            rx: self.rx.commitment().clone(),
+            ry: self.ry.commitment().clone(),
+        }
+    }
+
+    #[cfg(test)]
     pub fn into_commitments(self) -> PointAddCommitmentPoints<C> {
         PointAddCommitmentPoints {
             px: self.px.into_commitment(),
@@ -122,30 +136,115 @@ impl<C: Curve> PointAddCommitmentPoints<C> {
     }
 }
 
-#[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
-pub struct MultCommitProof<C: Curve> {
-    commitment: Point<C>,
-    proof: MultiplicationProof<C>,
+type Pair<T> = (T, T);
+
+type PointAddStatement<'a, C> = And<
+    And<
+        And<MultiplicationStatement<'a, C>, MultiplicationStatement<'a, C>>,
+        And<MultiplicationStatement<'a, C>, MultiplicationStatement<'a, C>>,
+    >,
+    And<EqualityStatement<'a, C>, EqualityStatement<'a, C>>,
+>;
+
+/// Commitments the relations of the point addition are stated on.
+struct AuxCommitmentPoints<C: Curve> {
+    commitment_7: Point<C>,
+    commitment_8: Point<C>,
+    commitment_9: Point<C>,
+    commitment_10: Point<C>,
+    commitment_11: Point<C>,
+    commitment_12: Point<C>,
+    commitment_13: Point<C>,
+    // commitment to one with zero randomness
+    commitment_14: Point<C>,
+    sum_x: Point<C>,
+    sum_y: Point<C>,
 }
 
-impl<C: Curve> MultCommitProof<C> {
-    pub fn new(commitment: Point<C>, proof: MultiplicationProof<C>) -> Self {
-        Self { commitment, proof }
+impl<C: Curve> AuxCommitmentPoints<C> {
+    fn new(commitments: &PointAddCommitmentPoints<C>, aux: [&Point<C>; 4]) -> Self {
+        Self {
+            commitment_7: &commitments.qx - &commitments.px,
+            commitment_8: aux[0].clone(),
+            commitment_9: &commitments.qy - &commitments.py,
+            commitment_10: aux[1].clone(),
+            commitment_11: aux[2].clone(),
+            commitment_12: &commitments.px - &commitments.rx,
+            commitment_13: aux[3].clone(),
+            commitment_14: Point::<C>::GENERATOR,
+            sum_x: &(&commitments.rx + &commitments.px) + &commitments.qx,
+            sum_y: &commitments.ry + &commitments.py,
+        }
+    }
+
+    fn statement<'a>(
+        &'a self,
+        pedersen_generator: &'a PedersenGenerator<C>,
+    ) -> PointAddStatement<'a, C> {
+        let multiplication = |x, y, z| MultiplicationStatement {
+            pedersen_generator,
+            commitment_to_x: x,
+            commitment_to_y: y,
+            commitment_to_z: z,
+        };
+        let equality = |commitment_1, commitment_2| EqualityStatement {
+            pedersen_generator_1: pedersen_generator,
+            commitment_1,
+            pedersen_generator_2: pedersen_generator,
+            commitment_2,
+        };
+        And(
+            And(
+                And(
+                    multiplication(&self.commitment_7, &self.commitment_8, &self.commitment_14),
+                    multiplication(&self.commitment_8, &self.commitment_9, &self.commitment_10),
+                ),
+                And(
+                    multiplication(
+                        &self.commitment_10,
+                        &self.commitment_10,
+                        &self.commitment_11,
+                    ),
+                    multiplication(
+                        &self.commitment_10,
+                        &self.commitment_12,
+                        &self.commitment_13,
+                    ),
+                ),
+            ),
+            And(
+                equality(&self.commitment_11, &self.sum_x),
+                equality(&self.commitment_13, &self.sum_y),
+            ),
+        )
     }
 }
 
+/// Proof that the committed points satisfy `P + Q = R`.
+///
+/// The relations of the affine addition formula are proven by multiplication
+/// and equality proofs on auxiliary commitments, composed under a single
+/// challenge.
 #[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
 pub struct PointAddProof<CC: Cycle<C>, C: Curve> {
-    mult_proof_8: MultCommitProof<CC>,
-    mult_proof_10: MultCommitProof<CC>,
-    mult_proof_11: MultCommitProof<CC>,
-    mult_proof_13: MultCommitProof<CC>,
-    equality_proof_x: EqualityProof<CC>,
-    equality_proof_y: EqualityProof<CC>,
+    commitment_8: Point<CC>,
+    commitment_10: Point<CC>,
+    commitment_11: Point<CC>,
+    commitment_13: Point<CC>,
+    commitment: (
+        Pair<Pair<MultiplicationCommitment<CC>>>,
+        Pair<EqualityCommitment<CC>>,
+    ),
+    response: (
+        Pair<Pair<MultiplicationResponse<CC>>>,
+        Pair<EqualityResponse<CC>>,
+    ),
     base_curve: PhantomData<C>,
 }
 
 impl<CC: Cycle<C>, C: Curve> PointAddProof<CC, C> {
+    const HASH_ID: &'static [u8] = b"point-add-proof";
+
     pub fn construct<R: CryptoCoreRng>(
         rng: &mut R,
         pedersen_generator: &PedersenGenerator<CC>,
@@ -172,73 +271,82 @@ impl<CC: Cycle<C>, C: Curve> PointAddProof<CC, C> {
         let commitment_12 = &commitments.px - &commitments.rx;
         let commitment_13 = pedersen_generator.commit(rng, aux_13.to_cycle_scalar());
         let commitment_14 = PedersenCommitment::new(Point::<CC>::GENERATOR, Scalar::<CC>::ZERO);
-
-        let mult_proof_8 = MultiplicationProof::construct(
-            rng,
-            pedersen_generator,
-            &commitment_7,
-            &commitment_8,
-            &commitment_14,
-            aux_7.to_cycle_scalar(),
-            aux_8.to_cycle_scalar(),
-            Scalar::<CC>::ONE,
-        );
-        let mult_proof_10 = MultiplicationProof::construct(
-            rng,
-            pedersen_generator,
-            &commitment_8,
-            &commitment_9,
-            &commitment_10,
-            aux_8.to_cycle_scalar(),
-            aux_9.to_cycle_scalar(),
-            aux_10.to_cycle_scalar(),
-        );
-        let mult_proof_11 = MultiplicationProof::construct(
-            rng,
-            pedersen_generator,
-            &commitment_10,
-            &commitment_10,
-            &commitment_11,
-            aux_10.to_cycle_scalar(),
-            aux_10.to_cycle_scalar(),
-            aux_11.to_cycle_scalar(),
+        let sum_x = &(&commitments.rx + &commitments.px) + &commitments.qx;
+        let sum_y = &commitments.ry + &commitments.py;
+
+        let multiplication = |x: &PedersenCommitment<CC>,
+                              y: &PedersenCommitment<CC>,
+                              z: &PedersenCommitment<CC>,
+                              values: [&FieldElement<C>; 3]| {
+            MultiplicationWitness::new(
+                x,
+                y,
+                z,
+                values[0].to_cycle_scalar(),
+                values[1].to_cycle_scalar(),
+                values[2].to_cycle_scalar(),
+            )
+        };
+        let one = FieldElement::<C>::ONE;
+        let witness = (
+            (
+                (
+                    multiplication(
+                        &commitment_7,
+                        &commitment_8,
+                        &commitment_14,
+                        [&aux_7, &aux_8, &one],
+                    ),
+                    multiplication(
+                        &commitment_8,
+                        &commitment_9,
+                        &commitment_10,
+                        [&aux_8, &aux_9, &aux_10],
+                    ),
+                ),
+                (
+                    multiplication(
+                        &commitment_10,
+                        &commitment_10,
+                        &commitment_11,
+                        [&aux_10, &aux_10, &aux_11],
+                    ),
+                    multiplication(
+                        &commitment_10,
+                        &commitment_12,
+                        &commitment_13,
+                        [&aux_10, &aux_12, &aux_13],
+                    ),
+                ),
+            ),
+            (
+                EqualityWitness::new(aux_11.to_cycle_scalar(), &commitment_11, &sum_x),
+                EqualityWitness::new(aux_13.to_cycle_scalar(), &commitment_13, &sum_y),
+            ),
         );
 
-        let aux_commitment = &(&commitments.rx + &commitments.px) + &commitments.qx;
-        let equality_proof_x = EqualityProof::construct(
-            rng,
-            pedersen_generator,
-            &commitment_11,
-            &aux_commitment,
-            aux_11.to_cycle_scalar(),
+        let aux_points = AuxCommitmentPoints::new(
+            &commitments.points(),
+            [
+                commitment_8.commitment(),
+                commitment_10.commitment(),
+                commitment_11.commitment(),
+                commitment_13.commitment(),
+            ],
         );
-        let mult_proof_13 = MultiplicationProof::construct(
+        let (commitment, response) = aux_points.statement(pedersen_generator).prove(
             rng,
-            pedersen_generator,
-            &commitment_10,
-            &commitment_12,
-            &commitment_13,
-            aux_10.to_cycle_scalar(),
-            aux_12.to_cycle_scalar(),
-            aux_13.to_cycle_scalar(),
-        );
-
-        let aux_commitment = &commitments.ry + &commitments.py;
-        let equality_proof_y = EqualityProof::construct(
-            rng,
-            pedersen_generator,
-            &commitment_13,
-            &aux_commitment,
-            aux_13.to_cycle_scalar(),
+            &witness,
+            Transcript::new(Self::HASH_ID),
         );
 
         Self {
-            mult_proof_8: MultCommitProof::new(commitment_8.into_commitment(), mult_proof_8),
-            mult_proof_10: MultCommitProof::new(commitment_10.into_commitment(), mult_proof_10),
-            mult_proof_11: MultCommitProof::new(commitment_11.into_commitment(), mult_proof_11),
-            mult_proof_13: MultCommitProof::new(commitment_13.into_commitment(), mult_proof_13),
-            equality_proof_x,
-            equality_proof_y,
+            commitment_8: commitment_8.into_commitment(),
+            commitment_10: commitment_10.into_commitment(),
+            commitment_11: commitment_11.into_commitment(),
+            commitment_13: commitment_13.into_commitment(),
+            commitment,
+            response,
             base_curve: PhantomData,
         }
     }
@@ -249,65 +357,23 @@ impl<CC: Cycle<C>, C: Curve> PointAddProof<CC, C> {
         pedersen_generator: &PedersenGenerator<CC>,
         commitments: &PointAddCommitmentPoints<CC>,
         multimult: &mut MultiMult<CC>,
-    ) {
-        let commitment_7 = &commitments.qx - &commitments.px;
-        let commitment_9 = &commitments.qy - &commitments.py;
-        let commitment_12 = &commitments.px - &commitments.rx;
-
-        // aggregate multiplication proofs
-        self.mult_proof_8.proof.aggregate(
-            rng,
-            pedersen_generator,
-            &commitment_7,
-            &self.mult_proof_8.commitment,
-            &Point::<CC>::GENERATOR,
-            multimult,
+    ) -> Result<(), String> {
+        let aux_points = AuxCommitmentPoints::new(
+            commitments,
+            [
+                &self.commitment_8,
+                &self.commitment_10,
+                &self.commitment_11,
+                &self.commitment_13,
+            ],
         );
-
-        self.mult_proof_10.proof.aggregate(
+        aux_points.statement(pedersen_generator).verify(
             rng,
-            pedersen_generator,
-            &self.mult_proof_8.commitment,
-            &commitment_9,
-            &self.mult_proof_10.commitment,
+            &self.commitment,
+            &self.response,
+            Transcript::new(Self::HASH_ID),
             multimult,
-        );
-
-        self.mult_proof_11.proof.aggregate(
-            rng,
-            pedersen_generator,
-            &self.mult_proof_10.commitment,
-            &self.mult_proof_10.commitment,
-            &self.mult_proof_11.commitment,
-            multimult,
-        );
-
-        self.mult_proof_13.proof.aggregate(
-            rng,
-            pedersen_generator,
-            &self.mult_proof_10.commitment,
-            &commitment_12,
-            &self.mult_proof_13.commitment,
-            multimult,
-        );
-        // aggregate equality proofs
-        let aux_commitment = &(&commitments.rx + &commitments.px) + &commitments.qx;
-        self.equality_proof_x.aggregate(
-            rng,
-            pedersen_generator,
-            &self.mult_proof_11.commitment,
-            &aux_commitment,
-            multimult,
-        );
-
-        let aux_commitment = &commitments.py + &commitments.ry;
-        self.equality_proof_y.aggregate(
-            rng,
-            pedersen_generator,
-            &self.mult_proof_13.commitment,
-            &aux_commitment,
-            multimult,
-        );
+        )
     }
 
     #[cfg(test)]
@@ -318,8 +384,9 @@ impl<CC: Cycle<C>, C: Curve> PointAddProof<CC, C> {
         commitments: &PointAddCommitmentPoints<CC>,
     ) -> bool {
         let mut multimult = MultiMult::new();
-        self.aggregate(rng, pedersen_generator, commitments, &mut multimult);
-        multimult.evaluate().is_identity()
+        self.aggregate(rng, pedersen_generator, commitments, &mut multimult)
+            .is_ok()
+            && multimult.evaluate().is_identity()
     }
 }
 
@@ -386,12 +453,14 @@ mod test {
 
             let proof =
                 PointAddProof::construct(&mut rng, &pedersen_generator, &commitments, &secret);
-            proof.aggregate(
-                &mut rng,
-                &pedersen_generator,
-                &commitments.into_commitments(),
-                &mut multimult,
-            );
+            proof
+                .aggregate(
+                    &mut rng,
+                    &pedersen_generator,
+                    &commitments.into_commitments(),
+                    &mut multimult,
+                )
+                .unwrap();
         }
         assert!(multimult.evaluate().is_identity());
     }
@@ -0,0 +1,603 @@
+use super::inner_product::{inner_product, multiexp, InnerProductProof};
+use super::sigma::Transcript;
+use crate::arithmetic::multimult::{MultiMult, Relation};
+use crate::arithmetic::{AffinePoint, Modular, Point, Scalar};
+use crate::curve::Curve;
+use crate::pedersen::*;
+use crate::rng::CryptoCoreRng;
+use crate::U256;
+
+use bigint::Encoding;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Vector generators of the range proofs.
+///
+/// The generators are derived by hash-to-curve, thus nobody knows their
+/// discrete logarithms with respect to each other or to the curve generator.
+#[derive(Debug, Clone)]
+pub struct BulletproofGenerators<C: Curve> {
+    g_vec: Vec<Point<C>>,
+    h_vec: Vec<Point<C>>,
+}
+
+impl<C: Curve> BulletproofGenerators<C> {
+    /// Derives generators for proofs of at most `capacity` bits in total,
+    /// e.g. four 64 bit values need a capacity of 256.
+    pub fn new(capacity: usize) -> Self {
+        let derive = |label: &[u8]| {
+            (0..capacity as u64)
+                .map(|i| Point::from(AffinePoint::<C>::hash_to_curve(label, i)))
+                .collect()
+        };
+        Self {
+            g_vec: derive(b"bulletproof-generator-g"),
+            h_vec: derive(b"bulletproof-generator-h"),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.g_vec.len()
+    }
+}
+
+/// Aggregated Bulletproofs range proof that each committed value lies in
+/// `[0, 2^bits)`.
+///
+/// The values are committed with a [`PedersenGenerator`] in the usual way,
+/// i.e. `V = v * G + r * H`. The proof is only sound if the prover does not
+/// know the discrete logarithm of `H`, thus the verifier has to derive it
+/// itself, e.g. with [`PedersenGenerator::hash_to_curve`], it must not be
+/// taken from the prover. The bits of all values are proven at once with
+/// an [`InnerProductProof`], thus the proof size is logarithmic in the total
+/// number of bits.
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
+pub struct RangeProof<C: Curve> {
+    a: Point<C>,
+    s: Point<C>,
+    t_1: Point<C>,
+    t_2: Point<C>,
+    t_x: Scalar<C>,
+    t_x_blinding: Scalar<C>,
+    e_blinding: Scalar<C>,
+    ipp_proof: InnerProductProof<C>,
+}
+
+impl<C: Curve> RangeProof<C> {
+    const HASH_ID: &'static [u8] = b"range-proof";
+
+    /// Generates the proof for the committed values.
+    ///
+    /// The number of values has to be a power of two and `bits` one of 8, 16,
+    /// 32 or 64.
+    pub fn construct<R: CryptoCoreRng>(
+        rng: &mut R,
+        pedersen_generator: &PedersenGenerator<C>,
+        generators: &BulletproofGenerators<C>,
+        commitments: &[PedersenCommitment<C>],
+        values: &[Scalar<C>],
+        bits: usize,
+    ) -> Result<Self, String> {
+        if commitments.len() != values.len() {
+            return Err("invalid number of values".to_string());
+        }
+        let n = Self::check_dimensions(generators, values.len(), bits)?;
+        if values
+            .iter()
+            .any(|value| value.inner().bits_vartime() > bits)
+        {
+            return Err("value out of range".to_string());
+        }
+        let g = Point::<C>::GENERATOR;
+        let h = pedersen_generator.generator();
+        let g_vec = &generators.g_vec[..n];
+        let h_vec = &generators.h_vec[..n];
+
+        // a_L holds the bits of the values and a_R = a_L - 1
+        let a_l = values
+            .iter()
+            .flat_map(|value| {
+                let bytes = value.inner().to_le_bytes();
+                (0..bits).map(move |i| (bytes[i / 8] >> (i % 8)) & 1 == 1)
+            })
+            .collect::<Vec<bool>>();
+        let alpha = Scalar::<C>::random(rng);
+        let mut a = h * alpha;
+        for (i, bit) in a_l.iter().enumerate() {
+            if *bit {
+                a += &g_vec[i];
+            } else {
+                a = &a - &h_vec[i];
+            }
+        }
+
+        let s_l = (0..n).map(|_| Scalar::random(rng)).collect::<Vec<_>>();
+        let s_r = (0..n).map(|_| Scalar::random(rng)).collect::<Vec<_>>();
+        let rho = Scalar::<C>::random(rng);
+        let s = multiexp(
+            s_l.iter()
+                .zip(g_vec)
+                .chain(s_r.iter().zip(h_vec))
+                .chain(std::iter::once((&rho, h))),
+        );
+
+        let mut transcript = Self::transcript(commitments.iter().map(|c| c.commitment()), bits);
+        transcript.append_point(&a);
+        transcript.append_point(&s);
+        let y = transcript.next_challenge::<C>();
+        let z = transcript.next_challenge::<C>();
+
+        // l(X) = l_0 + l_1 * X and r(X) = r_0 + r_1 * X
+        let powers_of_y = powers(y, n);
+        let offsets = Self::offsets(z, values.len(), bits);
+        let l_0 = a_l
+            .iter()
+            .map(|bit| bit_scalar::<C>(*bit) - z)
+            .collect::<Vec<_>>();
+        let r_0 = (0..n)
+            .map(|i| powers_of_y[i] * (bit_scalar::<C>(a_l[i]) - Scalar::ONE + z) + offsets[i])
+            .collect::<Vec<_>>();
+        let r_1 = (0..n).map(|i| powers_of_y[i] * s_r[i]).collect::<Vec<_>>();
+
+        // coefficients of t(X) = <l(X), r(X)>
+        let t_1_coeff = inner_product(&l_0, &r_1) + inner_product(&s_l, &r_0);
+        let t_2_coeff = inner_product(&s_l, &r_1);
+        let tau_1 = Scalar::<C>::random(rng);
+        let tau_2 = Scalar::<C>::random(rng);
+        let t_1 = pedersen_generator
+            .commit_with_randomness(t_1_coeff, tau_1)
+            .into_commitment();
+        let t_2 = pedersen_generator
+            .commit_with_randomness(t_2_coeff, tau_2)
+            .into_commitment();
+
+        transcript.append_point(&t_1);
+        transcript.append_point(&t_2);
+        let x = transcript.next_challenge::<C>();
+
+        let l = (0..n).map(|i| l_0[i] + s_l[i] * x).collect::<Vec<_>>();
+        let r = (0..n).map(|i| r_0[i] + r_1[i] * x).collect::<Vec<_>>();
+        let t_x = inner_product(&l, &r);
+        let mut t_x_blinding = tau_2 * x * x + tau_1 * x;
+        let mut z_power = z * z;
+        for commitment in commitments {
+            t_x_blinding += z_power * *commitment.randomness();
+            z_power *= z;
+        }
+        let e_blinding = alpha + rho * x;
+
+        Self::append_scalars(&mut transcript, &[&t_x, &t_x_blinding, &e_blinding]);
+        let w = transcript.next_challenge::<C>();
+
+        // H'_i = y^-i * H_i
+        let y_inv = y.inverse();
+        let h_prime = powers(y_inv, n)
+            .iter()
+            .zip(h_vec)
+            .map(|(y_inv_i, h_i)| h_i * y_inv_i)
+            .collect();
+        let ipp_proof =
+            InnerProductProof::construct(&mut transcript, &(&g * w), g_vec.to_vec(), h_prime, l, r);
+
+        Ok(Self {
+            a,
+            s,
+            t_1,
+            t_2,
+            t_x,
+            t_x_blinding,
+            e_blinding,
+            ipp_proof,
+        })
+    }
+
+    /// Inserts the verification relations into the multimult.
+    ///
+    /// The proof is valid if the multimult evaluates to the identity and no
+    /// error is returned.
+    pub fn aggregate<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        pedersen_generator: &PedersenGenerator<C>,
+        generators: &BulletproofGenerators<C>,
+        commitments: &[&Point<C>],
+        bits: usize,
+        multimult: &mut MultiMult<C>,
+    ) -> Result<(), String> {
+        let m = commitments.len();
+        let n = Self::check_dimensions(generators, m, bits)?;
+        let g = Point::<C>::GENERATOR;
+        let h = pedersen_generator.generator();
+
+        let mut transcript = Self::transcript(commitments.iter().copied(), bits);
+        transcript.append_point(&self.a);
+        transcript.append_point(&self.s);
+        let y = transcript.next_challenge::<C>();
+        let z = transcript.next_challenge::<C>();
+        transcript.append_point(&self.t_1);
+        transcript.append_point(&self.t_2);
+        let x = transcript.next_challenge::<C>();
+        Self::append_scalars(
+            &mut transcript,
+            &[&self.t_x, &self.t_x_blinding, &self.e_blinding],
+        );
+        let w = transcript.next_challenge::<C>();
+        let scalars = self.ipp_proof.verification_scalars(n, &mut transcript)?;
+
+        // t_x * G + t_x_blinding * H = sum(z^(2+j) * V_j) + delta(y, z) * G
+        //     + x * T_1 + x^2 * T_2
+        let powers_of_y = powers(y, n);
+        let sum_of_y = powers_of_y
+            .iter()
+            .fold(Scalar::<C>::ZERO, |acc, y_i| acc + *y_i);
+        let sum_of_two = (0..bits).fold(Scalar::<C>::ZERO, |acc, _| acc + acc + Scalar::ONE);
+        let z_sq = z * z;
+        let mut delta = (z - z_sq) * sum_of_y;
+        let mut relation_t = Relation::new();
+        let mut z_power = z_sq;
+        for commitment in commitments {
+            delta -= z_power * z * sum_of_two;
+            relation_t.insert((*commitment).clone(), -z_power);
+            z_power *= z;
+        }
+        relation_t.insert(g.clone(), self.t_x - delta);
+        relation_t.insert(h.clone(), self.t_x_blinding);
+        relation_t.insert(self.t_1.clone(), -x);
+        relation_t.insert(self.t_2.clone(), -(x * x));
+        relation_t.drain(rng, multimult);
+
+        // A + x * S - e_blinding * H - z * <1, G> + <z * y^n + offsets, H'>
+        //     + t_x * w * G is the commitment of the inner product argument
+        let a = *self.ipp_proof.a();
+        let b = *self.ipp_proof.b();
+        let y_inv = y.inverse();
+        let offsets = Self::offsets(z, m, bits);
+        let mut relation_ipp = Relation::new();
+        relation_ipp.insert(self.a.clone(), Scalar::ONE);
+        relation_ipp.insert(self.s.clone(), x);
+        relation_ipp.insert(h.clone(), -self.e_blinding);
+        relation_ipp.insert(g, w * (self.t_x - a * b));
+        let mut y_inv_i = Scalar::<C>::ONE;
+        for (i, offset) in offsets.iter().enumerate() {
+            relation_ipp.insert(generators.g_vec[i].clone(), -z - a * scalars.s[i]);
+            relation_ipp.insert(
+                generators.h_vec[i].clone(),
+                z + y_inv_i * (*offset - b * scalars.s[n - 1 - i]),
+            );
+            y_inv_i *= y_inv;
+        }
+        for (k, (l, r)) in self
+            .ipp_proof
+            .l_vec()
+            .iter()
+            .zip(self.ipp_proof.r_vec())
+            .enumerate()
+        {
+            relation_ipp.insert(l.clone(), scalars.challenges_sq[k]);
+            relation_ipp.insert(r.clone(), scalars.challenges_inv_sq[k]);
+        }
+        relation_ipp.drain(rng, multimult);
+
+        Ok(())
+    }
+
+    pub fn verify<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        pedersen_generator: &PedersenGenerator<C>,
+        generators: &BulletproofGenerators<C>,
+        commitments: &[&Point<C>],
+        bits: usize,
+    ) -> Result<(), String> {
+        let mut multimult = MultiMult::new();
+        self.aggregate(
+            rng,
+            pedersen_generator,
+            generators,
+            commitments,
+            bits,
+            &mut multimult,
+        )?;
+
+        if multimult.evaluate().is_identity() {
+            Ok(())
+        } else {
+            Err("failed to verify range proof".to_owned())
+        }
+    }
+
+    // returns the total number of bits
+    fn check_dimensions(
+        generators: &BulletproofGenerators<C>,
+        m: usize,
+        bits: usize,
+    ) -> Result<usize, String> {
+        if ![8, 16, 32, 64].contains(&bits) {
+            return Err("invalid bit size".to_string());
+        }
+        if !m.is_power_of_two() {
+            return Err("invalid number of values".to_string());
+        }
+        if generators.capacity() < m * bits {
+            return Err("not enough generators".to_string());
+        }
+        Ok(m * bits)
+    }
+
+    // z^(2+j) * 2^i at index j * bits + i
+    fn offsets(z: Scalar<C>, m: usize, bits: usize) -> Vec<Scalar<C>> {
+        let powers_of_two = powers(Scalar::new(U256::from_u8(2)), bits);
+        let mut offsets = Vec::with_capacity(m * bits);
+        let mut z_power = z * z;
+        for _ in 0..m {
+            offsets.extend(powers_of_two.iter().map(|two_i| z_power * *two_i));
+            z_power *= z;
+        }
+        offsets
+    }
+
+    fn transcript<'a>(commitments: impl Iterator<Item = &'a Point<C>>, bits: usize) -> Transcript
+    where
+        C: 'a,
+    {
+        let mut transcript = Transcript::new(Self::HASH_ID);
+        transcript.append_bytes(&(bits as u64).to_be_bytes());
+        // NOTE commitments are hashed in affine form, thus the verifier may
+        // compute them differently from the prover, e.g. as a sum
+        for commitment in commitments {
+            transcript.append_point(&Point::from(commitment.to_affine()));
+        }
+        transcript
+    }
+
+    fn append_scalars(transcript: &mut Transcript, scalars: &[&Scalar<C>]) {
+        for scalar in scalars {
+            transcript.append_bytes(&scalar.inner().to_be_bytes());
+        }
+    }
+}
+
+fn bit_scalar<C: Curve>(bit: bool) -> Scalar<C> {
+    if bit {
+        Scalar::ONE
+    } else {
+        Scalar::ZERO
+    }
+}
+
+// 1, x, x^2, ..., x^(n-1)
+fn powers<C: Curve>(x: Scalar<C>, n: usize) -> Vec<Scalar<C>> {
+    let mut powers = Vec::with_capacity(n);
+    let mut power = Scalar::ONE;
+    for _ in 0..n {
+        powers.push(power);
+        power *= x;
+    }
+    powers
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curve::Tom256k1;
+    use rand::rngs::StdRng;
+    use rand_core::SeedableRng;
+
+    fn commit_values(
+        rng: &mut StdRng,
+        pedersen_generator: &PedersenGenerator<Tom256k1>,
+        values: &[u64],
+    ) -> (Vec<Scalar<Tom256k1>>, Vec<PedersenCommitment<Tom256k1>>) {
+        let values = values
+            .iter()
+            .map(|value| Scalar::new(U256::from_u64(*value)))
+            .collect::<Vec<_>>();
+        let commitments = values
+            .iter()
+            .map(|value| pedersen_generator.commit(rng, *value))
+            .collect();
+        (values, commitments)
+    }
+
+    #[test]
+    fn range_proof_32_bits() {
+        let mut rng = StdRng::from_seed([45; 32]);
+        let pedersen_generator = PedersenGenerator::<Tom256k1>::hash_to_curve(b"range-proof-test");
+        let generators = BulletproofGenerators::new(64);
+        let (values, commitments) = commit_values(&mut rng, &pedersen_generator, &[3_141_592_653]);
+        let points = [commitments[0].commitment()];
+
+        let proof = RangeProof::construct(
+            &mut rng,
+            &pedersen_generator,
+            &generators,
+            &commitments,
+            &values,
+            32,
+        )
+        .unwrap();
+        assert_eq!(
+            proof.verify(&mut rng, &pedersen_generator, &generators, &points, 32),
+            Ok(())
+        );
+        assert_eq!(
+            proof.verify(&mut rng, &pedersen_generator, &generators, &points, 64),
+            Err("invalid inner product proof length".to_string())
+        );
+
+        // the proof is bound to the commitment
+        let other_commitment = pedersen_generator.commit(&mut rng, values[0]);
+        assert_eq!(
+            proof.verify(
+                &mut rng,
+                &pedersen_generator,
+                &generators,
+                &[other_commitment.commitment()],
+                32,
+            ),
+            Err("failed to verify range proof".to_string())
+        );
+
+        // serialized proof verifies
+        let bytes = proof.try_to_vec().unwrap();
+        let deserialized = RangeProof::<Tom256k1>::try_from_slice(&bytes).unwrap();
+        assert!(deserialized
+            .verify(&mut rng, &pedersen_generator, &generators, &points, 32)
+            .is_ok());
+
+        let (values, commitments) = commit_values(&mut rng, &pedersen_generator, &[1 << 32]);
+        assert_eq!(
+            RangeProof::construct(
+                &mut rng,
+                &pedersen_generator,
+                &generators,
+                &commitments,
+                &values,
+                32,
+            )
+            .err(),
+            Some("value out of range".to_string())
+        );
+    }
+
+    #[test]
+    fn range_proof_64_bits() {
+        let mut rng = StdRng::from_seed([46; 32]);
+        let pedersen_generator = PedersenGenerator::<Tom256k1>::hash_to_curve(b"range-proof-test");
+        let generators = BulletproofGenerators::new(64);
+        let (values, commitments) = commit_values(&mut rng, &pedersen_generator, &[u64::MAX]);
+        let points = [commitments[0].commitment()];
+
+        let proof = RangeProof::construct(
+            &mut rng,
+            &pedersen_generator,
+            &generators,
+            &commitments,
+            &values,
+            64,
+        )
+        .unwrap();
+        assert!(proof
+            .verify(&mut rng, &pedersen_generator, &generators, &points, 64)
+            .is_ok());
+
+        // a value just above the range cannot be proven
+        let value = Scalar::new(U256::from_u64(u64::MAX).wrapping_add(&U256::ONE));
+        let commitment = pedersen_generator.commit(&mut rng, value);
+        assert_eq!(
+            RangeProof::construct(
+                &mut rng,
+                &pedersen_generator,
+                &generators,
+                &[commitment],
+                &[value],
+                64,
+            )
+            .err(),
+            Some("value out of range".to_string())
+        );
+        assert_eq!(
+            RangeProof::construct(
+                &mut rng,
+                &pedersen_generator,
+                &BulletproofGenerators::new(32),
+                &commitments,
+                &values,
+                64,
+            )
+            .err(),
+            Some("not enough generators".to_string())
+        );
+    }
+
+    #[test]
+    fn aggregated_range_proof() {
+        let mut rng = StdRng::from_seed([47; 32]);
+        let pedersen_generator = PedersenGenerator::<Tom256k1>::hash_to_curve(b"range-proof-test");
+        let generators = BulletproofGenerators::new(64);
+        let (values, commitments) =
+            commit_values(&mut rng, &pedersen_generator, &[0, 100, 65_535, 4_242]);
+        let points = commitments
+            .iter()
+            .map(PedersenCommitment::commitment)
+            .collect::<Vec<_>>();
+
+        let proof = RangeProof::construct(
+            &mut rng,
+            &pedersen_generator,
+            &generators,
+            &commitments,
+            &values,
+            16,
+        )
+        .unwrap();
+        assert!(proof
+            .verify(&mut rng, &pedersen_generator, &generators, &points, 16)
+            .is_ok());
+
+        // the proof is bound to the order of the commitments
+        let mut other_points = points.clone();
+        other_points.swap(1, 2);
+        assert_eq!(
+            proof.verify(
+                &mut rng,
+                &pedersen_generator,
+                &generators,
+                &other_points,
+                16
+            ),
+            Err("failed to verify range proof".to_string())
+        );
+        assert_eq!(
+            proof.verify(&mut rng, &pedersen_generator, &generators, &points[..3], 16),
+            Err("invalid number of values".to_string())
+        );
+
+        // proofs are checked together in a single multimult
+        let (single_value, single_commitment) = commit_values(&mut rng, &pedersen_generator, &[7]);
+        let single_proof = RangeProof::construct(
+            &mut rng,
+            &pedersen_generator,
+            &generators,
+            &single_commitment,
+            &single_value,
+            8,
+        )
+        .unwrap();
+        let mut multimult = MultiMult::new();
+        proof
+            .aggregate(
+                &mut rng,
+                &pedersen_generator,
+                &generators,
+                &points,
+                16,
+                &mut multimult,
+            )
+            .unwrap();
+        single_proof
+            .aggregate(
+                &mut rng,
+                &pedersen_generator,
+                &generators,
+                &[single_commitment[0].commitment()],
+                8,
+                &mut multimult,
+            )
+            .unwrap();
+        assert!(multimult.evaluate().is_identity());
+
+        let (values, commitments) = commit_values(&mut rng, &pedersen_generator, &[1, 2, 3]);
+        assert_eq!(
+            RangeProof::construct(
+                &mut rng,
+                &pedersen_generator,
+                &generators,
+                &commitments,
+                &values,
+                16,
+            )
+            .err(),
+            Some("invalid number of values".to_string())
+        );
+    }
+}
@@ -0,0 +1,385 @@
+use crate::arithmetic::multimult::{MultiMult, Relation};
+use crate::arithmetic::{Modular, Point, Scalar};
+use crate::curve::{Curve, Cycle};
+use crate::hasher::PointHasher;
+use crate::pedersen::{PedersenCommitment, PedersenCycle, PedersenGenerator};
+use crate::rng::CryptoCoreRng;
+use crate::U256;
+
+use bigint::Encoding;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use std::ops::Neg;
+
+const BITS: usize = 256;
+
+/// Commitments to the same bit on both curves of the cycle with an OR proof
+/// that they open to either zero or one.
+#[derive(BorshDeserialize, BorshSerialize)]
+struct BitProof<C: Curve, CC: Cycle<C>> {
+    commitment_cycle: Point<CC>,
+    commitment_base: Point<C>,
+    announcement_cycle_0: Point<CC>,
+    announcement_base_0: Point<C>,
+    announcement_cycle_1: Point<CC>,
+    announcement_base_1: Point<C>,
+    challenge_0: [u8; 32],
+    response_cycle_0: Scalar<CC>,
+    response_base_0: Scalar<C>,
+    response_cycle_1: Scalar<CC>,
+    response_base_1: Scalar<C>,
+}
+
+/// Proof that a commitment on the base curve opens to the value of a
+/// commitment on the cycle curve reduced modulo the base curve order.
+///
+/// The value is decomposed into bits, each committed on both curves. The
+/// commitments to the value and to its reduction are the weighted sums of the
+/// bit commitments, thus they are computed by the verifier. The integer
+/// represented by the bits is less than `2^256`, which is the value itself
+/// unless it has a second representative below `2^256`, i.e. it is less than
+/// `2^256 - p`.
+///
+/// Challenges are 255 bits long, thus they are the same integer modulo the
+/// order of both curves.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ReductionProof<C: Curve, CC: Cycle<C>> {
+    // from the most significant bit
+    bits: Vec<BitProof<C, CC>>,
+}
+
+struct PendingBit<C: Curve, CC: Cycle<C>> {
+    bit: bool,
+    commitment_cycle: PedersenCommitment<CC>,
+    commitment_base: PedersenCommitment<C>,
+    announcements: [(Point<CC>, Point<C>); 2],
+    nonce_cycle: Scalar<CC>,
+    nonce_base: Scalar<C>,
+    challenge: [u8; 32],
+    response_cycle: Scalar<CC>,
+    response_base: Scalar<C>,
+}
+
+impl<C: Curve, CC: Cycle<C>> ReductionProof<C, CC> {
+    const HASH_ID: &'static [u8] = b"reduction-proof";
+
+    /// Generates the proof and returns it with the commitments to `x` on the
+    /// cycle curve and to `x mod n` on the base curve.
+    pub fn construct<R: CryptoCoreRng>(
+        rng: &mut R,
+        pedersen: &PedersenCycle<C, CC>,
+        x: Scalar<CC>,
+    ) -> (Self, PedersenCommitment<CC>, PedersenCommitment<C>) {
+        let bytes = x.inner().to_be_bytes();
+        let mut hasher = PointHasher::new(Self::HASH_ID);
+        let mut pending = Vec::<PendingBit<C, CC>>::with_capacity(BITS);
+        let mut randomness_cycle = Scalar::<CC>::ZERO;
+        let mut randomness_base = Scalar::<C>::ZERO;
+
+        for i in (0..BITS).rev() {
+            let bit = (bytes[31 - i / 8] >> (i % 8)) & 1 == 1;
+            let (value_cycle, value_base) = if bit {
+                (Scalar::<CC>::ONE, Scalar::<C>::ONE)
+            } else {
+                (Scalar::<CC>::ZERO, Scalar::<C>::ZERO)
+            };
+            let commitment_cycle = pedersen.cycle().commit(rng, value_cycle);
+            let commitment_base = pedersen.base().commit(rng, value_base);
+            randomness_cycle = randomness_cycle + randomness_cycle + *commitment_cycle.randomness();
+            randomness_base = randomness_base + randomness_base + *commitment_base.randomness();
+
+            // simulate the branch of the other bit value
+            let challenge = random_challenge(rng);
+            let response_cycle = Scalar::<CC>::random(rng);
+            let response_base = Scalar::<C>::random(rng);
+            let simulated = (
+                announcement(
+                    pedersen.cycle(),
+                    commitment_cycle.commitment(),
+                    !bit,
+                    &challenge,
+                    response_cycle,
+                ),
+                announcement(
+                    pedersen.base(),
+                    commitment_base.commitment(),
+                    !bit,
+                    &challenge,
+                    response_base,
+                ),
+            );
+
+            let nonce_cycle = Scalar::<CC>::random(rng);
+            let nonce_base = Scalar::<C>::random(rng);
+            let honest = (
+                pedersen.cycle().generator() * nonce_cycle,
+                pedersen.base().generator() * nonce_base,
+            );
+
+            let announcements = if bit {
+                [simulated, honest]
+            } else {
+                [honest, simulated]
+            };
+            hasher.insert_point(commitment_cycle.commitment());
+            hasher.insert_point(commitment_base.commitment());
+            for (cycle, base) in &announcements {
+                hasher.insert_point(cycle);
+                hasher.insert_point(base);
+            }
+
+            pending.push(PendingBit {
+                bit,
+                commitment_cycle,
+                commitment_base,
+                announcements,
+                nonce_cycle,
+                nonce_base,
+                challenge,
+                response_cycle,
+                response_base,
+            });
+        }
+
+        let challenge = truncated_challenge(hasher.finalize());
+        let bits = pending
+            .into_iter()
+            .map(|p| {
+                let honest_challenge = xor(&challenge, &p.challenge);
+                let response_cycle = p.nonce_cycle
+                    - challenge_scalar::<CC>(&honest_challenge) * *p.commitment_cycle.randomness();
+                let response_base = p.nonce_base
+                    - challenge_scalar::<C>(&honest_challenge) * *p.commitment_base.randomness();
+                let simulated = (p.challenge, p.response_cycle, p.response_base);
+                let honest = (honest_challenge, response_cycle, response_base);
+                let (branch_0, branch_1) = if p.bit {
+                    (simulated, honest)
+                } else {
+                    (honest, simulated)
+                };
+                let [(announcement_cycle_0, announcement_base_0), (announcement_cycle_1, announcement_base_1)] =
+                    p.announcements;
+                BitProof {
+                    commitment_cycle: p.commitment_cycle.into_commitment(),
+                    commitment_base: p.commitment_base.into_commitment(),
+                    announcement_cycle_0,
+                    announcement_base_0,
+                    announcement_cycle_1,
+                    announcement_base_1,
+                    challenge_0: branch_0.0,
+                    response_cycle_0: branch_0.1,
+                    response_base_0: branch_0.2,
+                    response_cycle_1: branch_1.1,
+                    response_base_1: branch_1.2,
+                }
+            })
+            .collect();
+
+        // NOTE the commitments are computed just like by the verifier, thus
+        // they have the same projective representation in transcripts
+        let proof = Self { bits };
+        let (sum_cycle, sum_base) = proof.weighted_sums();
+        (
+            proof,
+            PedersenCommitment::new(sum_cycle, randomness_cycle),
+            PedersenCommitment::new(sum_base, randomness_base),
+        )
+    }
+
+    /// Verifies the proof against the commitment on the cycle curve and
+    /// returns the commitment to the reduced value on the base curve.
+    pub fn verify<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        pedersen: &PedersenCycle<C, CC>,
+        commitment: &Point<CC>,
+    ) -> Result<Point<C>, String> {
+        if self.bits.len() != BITS {
+            return Err("invalid number of bit proofs".to_string());
+        }
+
+        let mut hasher = PointHasher::new(Self::HASH_ID);
+        for bit in &self.bits {
+            hasher.insert_point(&bit.commitment_cycle);
+            hasher.insert_point(&bit.commitment_base);
+            hasher.insert_point(&bit.announcement_cycle_0);
+            hasher.insert_point(&bit.announcement_base_0);
+            hasher.insert_point(&bit.announcement_cycle_1);
+            hasher.insert_point(&bit.announcement_base_1);
+        }
+        let challenge = truncated_challenge(hasher.finalize());
+
+        let mut cycle_multimult = MultiMult::<CC>::new();
+        let mut base_multimult = MultiMult::<C>::new();
+        cycle_multimult.add_known(Point::<CC>::GENERATOR);
+        cycle_multimult.add_known(pedersen.cycle().generator().clone());
+        base_multimult.add_known(Point::<C>::GENERATOR);
+        base_multimult.add_known(pedersen.base().generator().clone());
+
+        for bit in &self.bits {
+            if bit.challenge_0[0] & 0x80 != 0 {
+                return Err("bit challenge out of range".to_string());
+            }
+            let challenge_1 = xor(&challenge, &bit.challenge_0);
+            aggregate_branches(
+                rng,
+                pedersen.cycle(),
+                &bit.commitment_cycle,
+                [&bit.announcement_cycle_0, &bit.announcement_cycle_1],
+                [&bit.challenge_0, &challenge_1],
+                [bit.response_cycle_0, bit.response_cycle_1],
+                &mut cycle_multimult,
+            );
+            aggregate_branches(
+                rng,
+                pedersen.base(),
+                &bit.commitment_base,
+                [&bit.announcement_base_0, &bit.announcement_base_1],
+                [&bit.challenge_0, &challenge_1],
+                [bit.response_base_0, bit.response_base_1],
+                &mut base_multimult,
+            );
+        }
+        if !cycle_multimult.evaluate().is_identity() || !base_multimult.evaluate().is_identity() {
+            return Err("bit proof failed".to_string());
+        }
+
+        let (sum_cycle, sum_base) = self.weighted_sums();
+        if &sum_cycle != commitment {
+            return Err("reduced value mismatch".to_string());
+        }
+        Ok(sum_base)
+    }
+
+    /// Returns the sums of the bit commitments weighted by powers of two.
+    fn weighted_sums(&self) -> (Point<CC>, Point<C>) {
+        let mut sum_cycle = self.bits[0].commitment_cycle.clone();
+        let mut sum_base = self.bits[0].commitment_base.clone();
+        for bit in &self.bits[1..] {
+            sum_cycle = &sum_cycle.double() + &bit.commitment_cycle;
+            sum_base = &sum_base.double() + &bit.commitment_base;
+        }
+        (sum_cycle, sum_base)
+    }
+}
+
+/// Returns `u·H + c·(A - b·G)`, the announcement for which the response `u`
+/// is accepted under the challenge `c`, given that `A` commits to `b`.
+fn announcement<C: Curve>(
+    pedersen_generator: &PedersenGenerator<C>,
+    commitment: &Point<C>,
+    bit: bool,
+    challenge: &[u8; 32],
+    response: Scalar<C>,
+) -> Point<C> {
+    let shifted = if bit {
+        commitment - &Point::<C>::GENERATOR
+    } else {
+        commitment.clone()
+    };
+    pedersen_generator
+        .generator()
+        .double_mul(&response, &shifted, &challenge_scalar(challenge))
+}
+
+/// Adds the relations `u_b·H + c_b·(A - b·G) - T_b = 0` of both bit values to
+/// the multimult.
+fn aggregate_branches<R: CryptoCoreRng, C: Curve>(
+    rng: &mut R,
+    pedersen_generator: &PedersenGenerator<C>,
+    commitment: &Point<C>,
+    announcements: [&Point<C>; 2],
+    challenges: [&[u8; 32]; 2],
+    responses: [Scalar<C>; 2],
+    multimult: &mut MultiMult<C>,
+) {
+    for (bit, ((announcement, challenge), response)) in announcements
+        .into_iter()
+        .zip(challenges)
+        .zip(responses)
+        .enumerate()
+    {
+        let challenge = challenge_scalar::<C>(challenge);
+        let mut relation = Relation::new();
+        relation.insert(pedersen_generator.generator().clone(), response);
+        relation.insert(commitment.clone(), challenge);
+        if bit == 1 {
+            relation.insert(Point::<C>::GENERATOR, -challenge);
+        }
+        relation.insert(announcement.neg(), Scalar::<C>::ONE);
+        relation.drain(rng, multimult);
+    }
+}
+
+fn challenge_scalar<C: Curve>(challenge: &[u8; 32]) -> Scalar<C> {
+    Scalar::new(U256::from_be_bytes(*challenge))
+}
+
+fn truncated_challenge(hash: U256) -> [u8; 32] {
+    let mut challenge = hash.to_be_bytes();
+    challenge[0] &= 0x7f;
+    challenge
+}
+
+fn random_challenge<R: CryptoCoreRng>(rng: &mut R) -> [u8; 32] {
+    let mut challenge = [0u8; 32];
+    rng.fill_bytes(&mut challenge);
+    challenge[0] &= 0x7f;
+    challenge
+}
+
+fn xor(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let mut result = [0u8; 32];
+    for (r, (x, y)) in result.iter_mut().zip(a.iter().zip(b.iter())) {
+        *r = x ^ y;
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curve::{Secp256k1, Tom256k1};
+    use rand::rngs::StdRng;
+    use rand_core::SeedableRng;
+
+    #[test]
+    fn reduction_proof() {
+        let mut rng = StdRng::from_seed([23; 32]);
+        let pedersen = PedersenCycle::<Secp256k1, Tom256k1>::new(&mut rng);
+        // greater than the order of secp256k1
+        let x = Scalar::<Tom256k1>::new(U256::from_be_hex(
+            "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd036414f",
+        ));
+        let reduced = Scalar::<Secp256k1>::new(U256::from_u8(0x0e));
+
+        let (proof, commitment_cycle, commitment_base) =
+            ReductionProof::construct(&mut rng, &pedersen, x);
+        assert_eq!(
+            commitment_base.commitment(),
+            pedersen
+                .base()
+                .commit_with_randomness(reduced, *commitment_base.randomness())
+                .commitment()
+        );
+        assert_eq!(
+            proof
+                .verify(&mut rng, &pedersen, commitment_cycle.commitment())
+                .as_ref(),
+            Ok(commitment_base.commitment())
+        );
+
+        let other = pedersen.cycle().commit(&mut rng, x);
+        assert_eq!(
+            proof.verify(&mut rng, &pedersen, other.commitment()),
+            Err("reduced value mismatch".to_string())
+        );
+
+        let mut tampered = proof;
+        tampered.bits.swap(0, 1);
+        assert_eq!(
+            tampered.verify(&mut rng, &pedersen, commitment_cycle.commitment()),
+            Err("bit proof failed".to_string())
+        );
+    }
+}
@@ -0,0 +1,236 @@
+use crate::curve::Curve;
+use crate::rng::CryptoCoreRng;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use std::marker::PhantomData;
+
+/// Three-move proof run interactively between a [`ProverSession`] and a
+/// [`VerifierSession`].
+///
+/// The implementing type is the public statement, the messages are the same
+/// as in the non-interactive proof. The challenge of a
+/// [`MembershipStatement`](super::MembershipStatement) is a random scalar
+/// chosen by the verifier, while the challenge of an
+/// [`ExpStatement`](super::ExpStatement) selects the checked repetitions and
+/// their challenge bits. An interactive proof is not transferable, the
+/// transcript convinces no one but the verifier who chose the challenge.
+pub trait Interactive<C: Curve> {
+    type Witness;
+    type Commitment: BorshSerialize + BorshDeserialize;
+    type Challenge: Clone + BorshSerialize + BorshDeserialize;
+    type Response: BorshSerialize + BorshDeserialize;
+    /// Secret state of the prover between the commitment and the response.
+    type State;
+
+    fn commit_message<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        witness: &Self::Witness,
+    ) -> Result<(Self::Commitment, Self::State), String>;
+
+    fn challenge_message<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        commitment: &Self::Commitment,
+    ) -> Self::Challenge;
+
+    fn respond_message<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        witness: &Self::Witness,
+        state: Self::State,
+        challenge: &Self::Challenge,
+    ) -> Result<Self::Response, String>;
+
+    /// Checks the messages of a session, the proof is valid if no error is
+    /// returned.
+    fn check<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        commitment: &Self::Commitment,
+        challenge: &Self::Challenge,
+        response: &Self::Response,
+    ) -> Result<(), String>;
+}
+
+/// Prover side of an interactive proof.
+///
+/// The session only exists after the commitment is sent and answering the
+/// challenge consumes it, thus the secret state is never reused for a second
+/// challenge.
+pub struct ProverSession<'a, C: Curve, P: Interactive<C>> {
+    statement: &'a P,
+    witness: &'a P::Witness,
+    state: P::State,
+    _curve: PhantomData<C>,
+}
+
+impl<'a, C: Curve, P: Interactive<C>> ProverSession<'a, C, P> {
+    /// Starts the session and returns the commitment to send to the
+    /// verifier.
+    pub fn commit<R: CryptoCoreRng>(
+        rng: &mut R,
+        statement: &'a P,
+        witness: &'a P::Witness,
+    ) -> Result<(Self, P::Commitment), String> {
+        let (commitment, state) = statement.commit_message(rng, witness)?;
+        let session = Self {
+            statement,
+            witness,
+            state,
+            _curve: PhantomData,
+        };
+        Ok((session, commitment))
+    }
+
+    /// Returns the response to the verifier's challenge.
+    pub fn respond<R: CryptoCoreRng>(
+        self,
+        rng: &mut R,
+        challenge: &P::Challenge,
+    ) -> Result<P::Response, String> {
+        self.statement
+            .respond_message(rng, self.witness, self.state, challenge)
+    }
+}
+
+/// Verifier side of an interactive proof.
+pub struct VerifierSession<'a, C: Curve, P: Interactive<C>> {
+    statement: &'a P,
+    commitment: P::Commitment,
+    challenge: P::Challenge,
+    _curve: PhantomData<C>,
+}
+
+impl<'a, C: Curve, P: Interactive<C>> VerifierSession<'a, C, P> {
+    /// Starts the session on the prover's commitment and returns the
+    /// challenge to send back.
+    pub fn challenge<R: CryptoCoreRng>(
+        rng: &mut R,
+        statement: &'a P,
+        commitment: P::Commitment,
+    ) -> (Self, P::Challenge) {
+        let challenge = statement.challenge_message(rng, &commitment);
+        let session = Self {
+            statement,
+            commitment,
+            challenge: challenge.clone(),
+            _curve: PhantomData,
+        };
+        (session, challenge)
+    }
+
+    /// Checks the prover's response and closes the session.
+    pub fn finish<R: CryptoCoreRng>(
+        self,
+        rng: &mut R,
+        response: &P::Response,
+    ) -> Result<(), String> {
+        self.statement
+            .check(rng, &self.commitment, &self.challenge, response)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arithmetic::{Modular, Point, Scalar};
+    use crate::curve::{Secp256k1, Tom256k1};
+    use crate::pedersen::{PedersenCycle, PedersenGenerator};
+    use crate::proofs::membership::{MembershipStatement, MembershipWitness};
+    use crate::proofs::{ExpChallenge, ExpSecrets, ExpStatement, ExpWitness};
+    use crate::U256;
+    use rand::rngs::StdRng;
+    use rand_core::SeedableRng;
+
+    // sends every message through its serialized form
+    fn transmit<T: BorshSerialize + BorshDeserialize>(message: T) -> T {
+        T::try_from_slice(&message.try_to_vec().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn membership_session() {
+        let mut rng = StdRng::from_seed([52; 32]);
+        let pedersen_generator = PedersenGenerator::<Tom256k1>::new(&mut rng);
+        let ring = (0..6u8)
+            .map(|i| Scalar::<Tom256k1>::new(U256::from_u8(i + 10)))
+            .collect::<Vec<_>>();
+        let commitment_to_key = pedersen_generator.commit(&mut rng, ring[4]);
+        let statement =
+            MembershipStatement::new(&pedersen_generator, commitment_to_key.commitment(), &ring)
+                .unwrap();
+        let witness = MembershipWitness::new(4, &commitment_to_key);
+
+        let (prover, commitment) = ProverSession::commit(&mut rng, &statement, &witness).unwrap();
+        let (verifier, challenge) =
+            VerifierSession::challenge(&mut rng, &statement, transmit(commitment));
+        let response = prover.respond(&mut rng, &transmit(challenge)).unwrap();
+        assert_eq!(verifier.finish(&mut rng, &transmit(response)), Ok(()));
+
+        // the response to another challenge is rejected
+        let (prover, commitment) = ProverSession::commit(&mut rng, &statement, &witness).unwrap();
+        let (verifier, _) = VerifierSession::challenge(&mut rng, &statement, commitment);
+        let other_challenge = Scalar::random(&mut rng);
+        let response = prover.respond(&mut rng, &other_challenge).unwrap();
+        assert_eq!(
+            verifier.finish(&mut rng, &response),
+            Err("failed to verify membership".to_string())
+        );
+    }
+
+    #[test]
+    fn exp_session() {
+        let mut rng = StdRng::from_seed([53; 32]);
+        let pedersen = PedersenCycle::<Secp256k1, Tom256k1>::new(&mut rng);
+        let base_gen = Point::<Secp256k1>::GENERATOR;
+        let q_point = Point::<Secp256k1>::GENERATOR.double();
+        let exponent = Scalar::<Secp256k1>::random(&mut rng);
+        let result = &base_gen.scalar_mul(&exponent) - &q_point;
+
+        let secrets = ExpSecrets::new(exponent, result.into());
+        let commitments = secrets.commit(&mut rng, &pedersen);
+        let points = commitments.clone().into_commitments();
+        let witness = ExpWitness {
+            secrets,
+            commitments,
+        };
+        let statement = ExpStatement {
+            base_gen: &base_gen,
+            pedersen: &pedersen,
+            commitments: &points,
+            q_point: Some(&q_point),
+            repetitions: 12,
+            security_param: 8,
+        };
+
+        let (prover, commitment) = ProverSession::commit(&mut rng, &statement, &witness).unwrap();
+        let (verifier, challenge) =
+            VerifierSession::challenge(&mut rng, &statement, transmit(commitment));
+        assert_eq!(challenge.indices.len(), 8);
+        let response = prover.respond(&mut rng, &transmit(challenge)).unwrap();
+        assert_eq!(verifier.finish(&mut rng, &transmit(response)), Ok(()));
+
+        // answers are bound to the challenge bits chosen by the verifier
+        let (prover, commitment) = ProverSession::commit(&mut rng, &statement, &witness).unwrap();
+        let (verifier, mut challenge) =
+            VerifierSession::challenge(&mut rng, &statement, commitment);
+        challenge.bits[0] = !challenge.bits[0];
+        let response = prover.respond(&mut rng, &challenge).unwrap();
+        assert_eq!(
+            verifier.finish(&mut rng, &response),
+            Err("challenge bit mismatch".to_string())
+        );
+
+        // a repetition is never answered twice
+        let (prover, _) = ProverSession::commit(&mut rng, &statement, &witness).unwrap();
+        let challenge = ExpChallenge {
+            indices: vec![3, 3],
+            bits: vec![true, false],
+        };
+        assert_eq!(
+            prover.respond(&mut rng, &challenge).err(),
+            Some("invalid challenge".to_string())
+        );
+    }
+}
@@ -0,0 +1,726 @@
+use crate::arithmetic::multimult::MultiMult;
+use crate::arithmetic::{Modular, Point, Scalar};
+use crate::curve::Curve;
+use crate::hasher::PointHasher;
+use crate::rng::CryptoCoreRng;
+
+use bigint::Encoding;
+
+/// Fiat-Shamir transcript shared by the components of a composed proof.
+pub struct Transcript {
+    hasher: PointHasher,
+}
+
+impl Transcript {
+    pub fn new(label: &[u8]) -> Self {
+        Self {
+            hasher: PointHasher::new(label),
+        }
+    }
+
+    pub fn append_bytes(&mut self, bytes: &[u8]) {
+        self.hasher.insert_bytes(bytes);
+    }
+
+    pub fn append_point<C: Curve>(&mut self, point: &Point<C>) {
+        self.hasher.insert_point(point);
+    }
+
+    pub fn append_points<C: Curve>(&mut self, points: &[&Point<C>]) {
+        self.hasher.insert_points(points);
+    }
+
+    pub fn challenge<C: Curve>(self) -> Scalar<C> {
+        Scalar::new(self.hasher.finalize())
+    }
+
+    /// Returns a challenge without closing the transcript, for protocols
+    /// with more than three moves.
+    ///
+    /// The challenge is appended to the transcript, thus the following ones
+    /// depend on it.
+    pub fn next_challenge<C: Curve>(&mut self) -> Scalar<C> {
+        let challenge = Scalar::<C>::new(self.hasher.clone().finalize());
+        self.hasher.insert_bytes(&challenge.inner().to_be_bytes());
+        challenge
+    }
+}
+
+/// Three-move proof of knowledge made non-interactive with a [`Transcript`].
+///
+/// The implementing type is the public statement, a proof consists of a
+/// commitment and a response. Verification inserts the relations into a
+/// [`MultiMult`], thus any number of proofs over the same curve are checked
+/// with a single multi-scalar multiplication. Protocols are composed with
+/// [`And`] and [`Or`], which derive a single challenge from a transcript
+/// absorbing every component.
+pub trait SigmaProtocol<C: Curve> {
+    type Witness;
+    type Commitment;
+    type Response;
+    /// Secret state of the prover between the commitment and the response.
+    type Nonce;
+
+    fn commit<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        witness: &Self::Witness,
+    ) -> (Self::Commitment, Self::Nonce);
+
+    /// Appends the statement and the commitment to the transcript.
+    fn absorb(&self, commitment: &Self::Commitment, transcript: &mut Transcript);
+
+    fn respond(
+        &self,
+        witness: &Self::Witness,
+        nonce: Self::Nonce,
+        challenge: Scalar<C>,
+    ) -> Self::Response;
+
+    /// Returns an accepting commitment and response for the given challenge
+    /// without knowing the witness.
+    fn simulate<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        challenge: Scalar<C>,
+    ) -> (Self::Commitment, Self::Response);
+
+    /// Inserts the verification relations into the multimult.
+    ///
+    /// The proof is valid if the multimult evaluates to the identity and no
+    /// error is returned.
+    fn verify_into<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        commitment: &Self::Commitment,
+        challenge: Scalar<C>,
+        response: &Self::Response,
+        multimult: &mut MultiMult<C>,
+    ) -> Result<(), String>;
+
+    fn prove<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        witness: &Self::Witness,
+        mut transcript: Transcript,
+    ) -> (Self::Commitment, Self::Response) {
+        let (commitment, nonce) = self.commit(rng, witness);
+        self.absorb(&commitment, &mut transcript);
+        let response = self.respond(witness, nonce, transcript.challenge());
+        (commitment, response)
+    }
+
+    fn verify<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        commitment: &Self::Commitment,
+        response: &Self::Response,
+        mut transcript: Transcript,
+        multimult: &mut MultiMult<C>,
+    ) -> Result<(), String> {
+        self.absorb(commitment, &mut transcript);
+        let challenge = transcript.challenge();
+        self.verify_into(rng, commitment, challenge, response, multimult)
+    }
+}
+
+/// Proof of both statements under the same challenge.
+pub struct And<A, B>(pub A, pub B);
+
+impl<C: Curve, A: SigmaProtocol<C>, B: SigmaProtocol<C>> SigmaProtocol<C> for And<A, B> {
+    type Witness = (A::Witness, B::Witness);
+    type Commitment = (A::Commitment, B::Commitment);
+    type Response = (A::Response, B::Response);
+    type Nonce = (A::Nonce, B::Nonce);
+
+    fn commit<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        witness: &Self::Witness,
+    ) -> (Self::Commitment, Self::Nonce) {
+        let (commitment_0, nonce_0) = self.0.commit(rng, &witness.0);
+        let (commitment_1, nonce_1) = self.1.commit(rng, &witness.1);
+        ((commitment_0, commitment_1), (nonce_0, nonce_1))
+    }
+
+    fn absorb(&self, commitment: &Self::Commitment, transcript: &mut Transcript) {
+        self.0.absorb(&commitment.0, transcript);
+        self.1.absorb(&commitment.1, transcript);
+    }
+
+    fn respond(
+        &self,
+        witness: &Self::Witness,
+        nonce: Self::Nonce,
+        challenge: Scalar<C>,
+    ) -> Self::Response {
+        (
+            self.0.respond(&witness.0, nonce.0, challenge),
+            self.1.respond(&witness.1, nonce.1, challenge),
+        )
+    }
+
+    fn simulate<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        challenge: Scalar<C>,
+    ) -> (Self::Commitment, Self::Response) {
+        let (commitment_0, response_0) = self.0.simulate(rng, challenge);
+        let (commitment_1, response_1) = self.1.simulate(rng, challenge);
+        ((commitment_0, commitment_1), (response_0, response_1))
+    }
+
+    fn verify_into<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        commitment: &Self::Commitment,
+        challenge: Scalar<C>,
+        response: &Self::Response,
+        multimult: &mut MultiMult<C>,
+    ) -> Result<(), String> {
+        self.0
+            .verify_into(rng, &commitment.0, challenge, &response.0, multimult)?;
+        self.1
+            .verify_into(rng, &commitment.1, challenge, &response.1, multimult)
+    }
+}
+
+/// Proof of any number of statements of the same type under the same
+/// challenge.
+///
+/// Generalizes [`And`] to a number of statements known at runtime.
+pub struct AllOf<S>(pub Vec<S>);
+
+impl<C: Curve, S: SigmaProtocol<C>> SigmaProtocol<C> for AllOf<S> {
+    type Witness = Vec<S::Witness>;
+    type Commitment = Vec<S::Commitment>;
+    type Response = Vec<S::Response>;
+    type Nonce = Vec<S::Nonce>;
+
+    /// Panics if the number of witnesses differs from the number of
+    /// statements.
+    fn commit<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        witness: &Self::Witness,
+    ) -> (Self::Commitment, Self::Nonce) {
+        assert_eq!(witness.len(), self.0.len(), "invalid number of witnesses");
+        self.0
+            .iter()
+            .zip(witness)
+            .map(|(statement, witness)| statement.commit(rng, witness))
+            .unzip()
+    }
+
+    fn absorb(&self, commitment: &Self::Commitment, transcript: &mut Transcript) {
+        for (statement, commitment) in self.0.iter().zip(commitment) {
+            statement.absorb(commitment, transcript);
+        }
+    }
+
+    fn respond(
+        &self,
+        witness: &Self::Witness,
+        nonce: Self::Nonce,
+        challenge: Scalar<C>,
+    ) -> Self::Response {
+        self.0
+            .iter()
+            .zip(witness)
+            .zip(nonce)
+            .map(|((statement, witness), nonce)| statement.respond(witness, nonce, challenge))
+            .collect()
+    }
+
+    fn simulate<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        challenge: Scalar<C>,
+    ) -> (Self::Commitment, Self::Response) {
+        self.0
+            .iter()
+            .map(|statement| statement.simulate(rng, challenge))
+            .unzip()
+    }
+
+    fn verify_into<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        commitment: &Self::Commitment,
+        challenge: Scalar<C>,
+        response: &Self::Response,
+        multimult: &mut MultiMult<C>,
+    ) -> Result<(), String> {
+        if commitment.len() != self.0.len() || response.len() != self.0.len() {
+            return Err("invalid number of proofs".to_string());
+        }
+        for ((statement, commitment), response) in self.0.iter().zip(commitment).zip(response) {
+            statement.verify_into(rng, commitment, challenge, response, multimult)?;
+        }
+        Ok(())
+    }
+}
+
+/// Proof of at least one of the statements without revealing which one.
+///
+/// The challenge is split into the challenges of the two branches, the prover
+/// chooses the challenge of the branch it has no witness for and simulates
+/// it.
+pub struct Or<A, B>(pub A, pub B);
+
+pub enum OrWitness<A, B> {
+    Left(A),
+    Right(B),
+}
+
+pub enum OrNonce<C: Curve, A: SigmaProtocol<C>, B: SigmaProtocol<C>> {
+    Left {
+        nonce: A::Nonce,
+        challenge: Scalar<C>,
+        response: B::Response,
+    },
+    Right {
+        nonce: B::Nonce,
+        challenge: Scalar<C>,
+        response: A::Response,
+    },
+}
+
+impl<C: Curve, A: SigmaProtocol<C>, B: SigmaProtocol<C>> SigmaProtocol<C> for Or<A, B> {
+    type Witness = OrWitness<A::Witness, B::Witness>;
+    type Commitment = (A::Commitment, B::Commitment);
+    // challenge of the left branch
+    type Response = (Scalar<C>, A::Response, B::Response);
+    type Nonce = OrNonce<C, A, B>;
+
+    fn commit<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        witness: &Self::Witness,
+    ) -> (Self::Commitment, Self::Nonce) {
+        let challenge = Scalar::random(rng);
+        match witness {
+            OrWitness::Left(witness) => {
+                let (commitment_0, nonce) = self.0.commit(rng, witness);
+                let (commitment_1, response) = self.1.simulate(rng, challenge);
+                (
+                    (commitment_0, commitment_1),
+                    OrNonce::Left {
+                        nonce,
+                        challenge,
+                        response,
+                    },
+                )
+            }
+            OrWitness::Right(witness) => {
+                let (commitment_0, response) = self.0.simulate(rng, challenge);
+                let (commitment_1, nonce) = self.1.commit(rng, witness);
+                (
+                    (commitment_0, commitment_1),
+                    OrNonce::Right {
+                        nonce,
+                        challenge,
+                        response,
+                    },
+                )
+            }
+        }
+    }
+
+    fn absorb(&self, commitment: &Self::Commitment, transcript: &mut Transcript) {
+        self.0.absorb(&commitment.0, transcript);
+        self.1.absorb(&commitment.1, transcript);
+    }
+
+    fn respond(
+        &self,
+        witness: &Self::Witness,
+        nonce: Self::Nonce,
+        challenge: Scalar<C>,
+    ) -> Self::Response {
+        match (witness, nonce) {
+            (
+                OrWitness::Left(witness),
+                OrNonce::Left {
+                    nonce,
+                    challenge: challenge_1,
+                    response,
+                },
+            ) => {
+                let challenge_0 = challenge - challenge_1;
+                (
+                    challenge_0,
+                    self.0.respond(witness, nonce, challenge_0),
+                    response,
+                )
+            }
+            (
+                OrWitness::Right(witness),
+                OrNonce::Right {
+                    nonce,
+                    challenge: challenge_0,
+                    response,
+                },
+            ) => (
+                challenge_0,
+                response,
+                self.1.respond(witness, nonce, challenge - challenge_0),
+            ),
+            _ => unreachable!("nonce is generated for the given witness"),
+        }
+    }
+
+    fn simulate<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        challenge: Scalar<C>,
+    ) -> (Self::Commitment, Self::Response) {
+        let challenge_0 = Scalar::random(rng);
+        let (commitment_0, response_0) = self.0.simulate(rng, challenge_0);
+        let (commitment_1, response_1) = self.1.simulate(rng, challenge - challenge_0);
+        (
+            (commitment_0, commitment_1),
+            (challenge_0, response_0, response_1),
+        )
+    }
+
+    fn verify_into<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        commitment: &Self::Commitment,
+        challenge: Scalar<C>,
+        response: &Self::Response,
+        multimult: &mut MultiMult<C>,
+    ) -> Result<(), String> {
+        let challenge_0 = response.0;
+        self.0
+            .verify_into(rng, &commitment.0, challenge_0, &response.1, multimult)?;
+        self.1.verify_into(
+            rng,
+            &commitment.1,
+            challenge - challenge_0,
+            &response.2,
+            multimult,
+        )
+    }
+}
+
+/// Proof of at least one of any number of statements of the same type
+/// without revealing which one.
+///
+/// Generalizes [`Or`], the challenges of the branches sum up to the
+/// challenge. The response contains the challenges of all but the last
+/// branch.
+pub struct AnyOf<S>(pub Vec<S>);
+
+pub struct AnyOfWitness<W> {
+    pub index: usize,
+    pub witness: W,
+}
+
+pub struct AnyOfNonce<C: Curve, S: SigmaProtocol<C>> {
+    nonce: S::Nonce,
+    // simulated challenges and responses, none at the index of the witness
+    simulated: Vec<Option<(Scalar<C>, S::Response)>>,
+}
+
+impl<C: Curve, S: SigmaProtocol<C>> SigmaProtocol<C> for AnyOf<S> {
+    type Witness = AnyOfWitness<S::Witness>;
+    type Commitment = Vec<S::Commitment>;
+    type Response = (Vec<Scalar<C>>, Vec<S::Response>);
+    type Nonce = AnyOfNonce<C, S>;
+
+    /// Panics if the index of the witness is out of bounds.
+    fn commit<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        witness: &Self::Witness,
+    ) -> (Self::Commitment, Self::Nonce) {
+        assert!(witness.index < self.0.len(), "witness index out of bounds");
+        let mut commitments = Vec::with_capacity(self.0.len());
+        let mut simulated = Vec::with_capacity(self.0.len());
+        let mut nonce = None;
+        for (i, statement) in self.0.iter().enumerate() {
+            if i == witness.index {
+                let (commitment, branch_nonce) = statement.commit(rng, &witness.witness);
+                commitments.push(commitment);
+                simulated.push(None);
+                nonce = Some(branch_nonce);
+            } else {
+                let challenge = Scalar::random(rng);
+                let (commitment, response) = statement.simulate(rng, challenge);
+                commitments.push(commitment);
+                simulated.push(Some((challenge, response)));
+            }
+        }
+        let nonce = AnyOfNonce {
+            // NOTE the index is in bounds, thus the nonce is set
+            nonce: nonce.unwrap(),
+            simulated,
+        };
+        (commitments, nonce)
+    }
+
+    fn absorb(&self, commitment: &Self::Commitment, transcript: &mut Transcript) {
+        for (statement, commitment) in self.0.iter().zip(commitment) {
+            statement.absorb(commitment, transcript);
+        }
+    }
+
+    fn respond(
+        &self,
+        witness: &Self::Witness,
+        nonce: Self::Nonce,
+        challenge: Scalar<C>,
+    ) -> Self::Response {
+        let branch_challenge = nonce
+            .simulated
+            .iter()
+            .flatten()
+            .fold(challenge, |acc, (simulated, _)| acc - *simulated);
+        let mut branch_nonce = Some(nonce.nonce);
+        let (mut challenges, responses): (Vec<_>, Vec<_>) = nonce
+            .simulated
+            .into_iter()
+            .map(|simulated| match simulated {
+                Some(simulated) => simulated,
+                None => (
+                    branch_challenge,
+                    self.0[witness.index].respond(
+                        &witness.witness,
+                        // NOTE there is a single branch without simulation
+                        branch_nonce.take().unwrap(),
+                        branch_challenge,
+                    ),
+                ),
+            })
+            .unzip();
+        challenges.pop();
+        (challenges, responses)
+    }
+
+    fn simulate<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        challenge: Scalar<C>,
+    ) -> (Self::Commitment, Self::Response) {
+        let mut last_challenge = challenge;
+        let mut challenges = Vec::with_capacity(self.0.len());
+        let mut commitments = Vec::with_capacity(self.0.len());
+        let mut responses = Vec::with_capacity(self.0.len());
+        for (i, statement) in self.0.iter().enumerate() {
+            let branch_challenge = if i + 1 == self.0.len() {
+                last_challenge
+            } else {
+                let branch_challenge = Scalar::random(rng);
+                last_challenge -= branch_challenge;
+                challenges.push(branch_challenge);
+                branch_challenge
+            };
+            let (commitment, response) = statement.simulate(rng, branch_challenge);
+            commitments.push(commitment);
+            responses.push(response);
+        }
+        (commitments, (challenges, responses))
+    }
+
+    fn verify_into<R: CryptoCoreRng>(
+        &self,
+        rng: &mut R,
+        commitment: &Self::Commitment,
+        challenge: Scalar<C>,
+        response: &Self::Response,
+        multimult: &mut MultiMult<C>,
+    ) -> Result<(), String> {
+        let (challenges, responses) = response;
+        if self.0.is_empty()
+            || commitment.len() != self.0.len()
+            || responses.len() != self.0.len()
+            || challenges.len() + 1 != self.0.len()
+        {
+            return Err("invalid number of branches".to_string());
+        }
+        let last_challenge = challenges
+            .iter()
+            .fold(challenge, |acc, branch_challenge| acc - *branch_challenge);
+        for (i, statement) in self.0.iter().enumerate() {
+            let branch_challenge = challenges.get(i).copied().unwrap_or(last_challenge);
+            statement.verify_into(
+                rng,
+                &commitment[i],
+                branch_challenge,
+                &responses[i],
+                multimult,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curve::Tom256k1;
+    use crate::pedersen::PedersenGenerator;
+    use crate::proofs::equality::{EqualityStatement, EqualityWitness};
+    use rand::rngs::StdRng;
+    use rand_core::SeedableRng;
+
+    #[test]
+    fn composed_proofs() {
+        let mut rng = StdRng::from_seed([26; 32]);
+        let pedersen_generator = PedersenGenerator::<Tom256k1>::new(&mut rng);
+        let secret = Scalar::<Tom256k1>::random(&mut rng);
+        let other = Scalar::<Tom256k1>::random(&mut rng);
+        let commitment_1 = pedersen_generator.commit(&mut rng, secret);
+        let commitment_2 = pedersen_generator.commit(&mut rng, secret);
+        let commitment_3 = pedersen_generator.commit(&mut rng, other);
+
+        let equal = EqualityStatement {
+            pedersen_generator_1: &pedersen_generator,
+            commitment_1: commitment_1.commitment(),
+            pedersen_generator_2: &pedersen_generator,
+            commitment_2: commitment_2.commitment(),
+        };
+        let different = EqualityStatement {
+            pedersen_generator_1: &pedersen_generator,
+            commitment_1: commitment_1.commitment(),
+            pedersen_generator_2: &pedersen_generator,
+            commitment_2: commitment_3.commitment(),
+        };
+        let witness = || EqualityWitness::new(secret, &commitment_1, &commitment_2);
+
+        let and = And(equal, equal);
+        let (commitment, response) =
+            and.prove(&mut rng, &(witness(), witness()), Transcript::new(b"test"));
+        let mut multimult = MultiMult::new();
+        assert!(and
+            .verify(
+                &mut rng,
+                &commitment,
+                &response,
+                Transcript::new(b"test"),
+                &mut multimult
+            )
+            .is_ok());
+        assert!(multimult.evaluate().is_identity());
+
+        // the transcripts have to match
+        let mut multimult = MultiMult::new();
+        and.verify(
+            &mut rng,
+            &commitment,
+            &response,
+            Transcript::new(b"other"),
+            &mut multimult,
+        )
+        .unwrap();
+        assert!(!multimult.evaluate().is_identity());
+
+        // either branch may hold
+        for or_witness in [OrWitness::Left(witness()), OrWitness::Right(witness())] {
+            let or = match or_witness {
+                OrWitness::Left(_) => Or(equal, different),
+                OrWitness::Right(_) => Or(different, equal),
+            };
+            let (commitment, response) = or.prove(&mut rng, &or_witness, Transcript::new(b"test"));
+            let mut multimult = MultiMult::new();
+            or.verify(
+                &mut rng,
+                &commitment,
+                &response,
+                Transcript::new(b"test"),
+                &mut multimult,
+            )
+            .unwrap();
+            assert!(multimult.evaluate().is_identity());
+        }
+
+        // a simulated proof does not verify if the challenge is derived from
+        // the transcript
+        let or = Or(different, different);
+        let challenge = Scalar::random(&mut rng);
+        let (commitment, response) = or.simulate(&mut rng, challenge);
+        let mut multimult = MultiMult::new();
+        or.verify(
+            &mut rng,
+            &commitment,
+            &response,
+            Transcript::new(b"test"),
+            &mut multimult,
+        )
+        .unwrap();
+        assert!(!multimult.evaluate().is_identity());
+
+        let all = AllOf(vec![equal, equal, equal]);
+        let (commitment, response) = all.prove(
+            &mut rng,
+            &vec![witness(), witness(), witness()],
+            Transcript::new(b"test"),
+        );
+        let mut multimult = MultiMult::new();
+        all.verify(
+            &mut rng,
+            &commitment,
+            &response,
+            Transcript::new(b"test"),
+            &mut multimult,
+        )
+        .unwrap();
+        assert!(multimult.evaluate().is_identity());
+
+        let all = AllOf(vec![equal, different, equal]);
+        let mut multimult = MultiMult::new();
+        all.verify(
+            &mut rng,
+            &commitment,
+            &response,
+            Transcript::new(b"test"),
+            &mut multimult,
+        )
+        .unwrap();
+        assert!(!multimult.evaluate().is_identity());
+
+        // any branch may hold
+        let any = AnyOf(vec![different, different, equal, different]);
+        let any_witness = AnyOfWitness {
+            index: 2,
+            witness: witness(),
+        };
+        let (commitment, response) = any.prove(&mut rng, &any_witness, Transcript::new(b"test"));
+        let mut multimult = MultiMult::new();
+        any.verify(
+            &mut rng,
+            &commitment,
+            &response,
+            Transcript::new(b"test"),
+            &mut multimult,
+        )
+        .unwrap();
+        assert!(multimult.evaluate().is_identity());
+
+        let any = AnyOf(vec![different, different, different, different]);
+        let mut multimult = MultiMult::new();
+        any.verify(
+            &mut rng,
+            &commitment,
+            &response,
+            Transcript::new(b"test"),
+            &mut multimult,
+        )
+        .unwrap();
+        assert!(!multimult.evaluate().is_identity());
+
+        let any = AnyOf(vec![different, equal]);
+        assert_eq!(
+            any.verify(
+                &mut rng,
+                &commitment,
+                &response,
+                Transcript::new(b"test"),
+                &mut MultiMult::new(),
+            ),
+            Err("invalid number of branches".to_string())
+        );
+    }
+}
@@ -0,0 +1,294 @@
+use super::{
+    signer_index, verify_message, DistinctnessProof, ExpCommitmentPoints, MembershipProof,
+    OwnershipProof,
+};
+use crate::arithmetic::{Point, Scalar};
+use crate::challenge::JoinChallenge;
+use crate::claims::ProofClaims;
+use crate::curve::{Curve, Cycle};
+use crate::message::MessageScheme;
+use crate::parse::{ParsedProofInput, ParsedRing};
+use crate::pedersen::PedersenCycle;
+use crate::ring::{ring_digest, RingView};
+use crate::rng::CryptoCoreRng;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Committed public key of one of the signers of a [`ThresholdAttestProof`]
+/// with its ownership and membership proofs.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ThresholdMember<C: Curve, CC: Cycle<C>> {
+    pub exp_commitments: ExpCommitmentPoints<C, CC>, // exp, pkx, pky
+    pub ownership_proof: OwnershipProof<C, CC>,
+    pub membership_proof: MembershipProof<CC>,
+}
+
+/// Zero-knowledge proof that the prover controls at least `k` distinct keys
+/// of the ring.
+///
+/// Every signer proves the ownership of its committed public key over the
+/// same message, like in a [`ZkAttestProof`](super::ZkAttestProof), and its
+/// membership in the ring. A [`DistinctnessProof`] on the commitments to the
+/// x coordinates shows that the keys are pairwise distinct, thus the same key
+/// cannot be counted twice.
+///
+/// The commitments are made with the generators fixed by the protocol, see
+/// [`PedersenCycle::fixed`].
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ThresholdAttestProof<C: Curve, CC: Cycle<C>> {
+    pub msg_hash: Scalar<C>,
+    pub ring_digest: [u8; 32],
+    pub members: Vec<ThresholdMember<C, CC>>,
+    pub distinctness_proof: DistinctnessProof<CC>,
+    pub challenge: JoinChallenge,
+    pub message_scheme: MessageScheme,
+}
+
+impl<C: Curve, CC: Cycle<C>> ThresholdAttestProof<C, CC> {
+    /// Generates the proof from the inputs of the signers.
+    ///
+    /// The inputs have to sign the same message, the challenge and the message
+    /// scheme of the proof are taken from the first one.
+    pub fn construct<R: CryptoCoreRng>(
+        rng: &mut R,
+        inputs: Vec<ParsedProofInput<C>>,
+        ring: &ParsedRing<CC>,
+    ) -> Result<Self, String> {
+        let first = inputs
+            .first()
+            .ok_or_else(|| "no signers given".to_string())?;
+        let msg_hash = first.msg_hash;
+        if inputs.iter().any(|input| input.msg_hash != msg_hash) {
+            return Err("signers sign different messages".to_string());
+        }
+        if inputs.iter().any(|input| input.nullifier) {
+            return Err("nullifiers are not supported for threshold proofs".to_string());
+        }
+        if inputs.iter().any(|input| input.siwe.is_some()) {
+            return Err("SIWE messages are not supported for threshold proofs".to_string());
+        }
+
+        let challenge = first.challenge.clone();
+        let message_scheme = first.message_scheme;
+        let pedersen = PedersenCycle::fixed();
+
+        let mut members = Vec::with_capacity(inputs.len());
+        let mut commitments_to_pk_x = Vec::with_capacity(inputs.len());
+        let mut pubkeys_x = Vec::with_capacity(inputs.len());
+        for input in inputs {
+            let pubkey_x = input.pubkey.x().to_cycle_scalar::<CC>();
+            let commitment_to_pk_x = pedersen.cycle().commit(rng, pubkey_x);
+            let commitment_to_pk_y = pedersen
+                .cycle()
+                .commit(rng, input.pubkey.y().to_cycle_scalar());
+
+            let membership_proof = MembershipProof::construct(
+                rng,
+                pedersen.cycle(),
+                &commitment_to_pk_x,
+                signer_index(ring, &input)?,
+                ring,
+            )?;
+            let (ownership_proof, exp_commitments) = OwnershipProof::construct(
+                rng,
+                &pedersen,
+                msg_hash,
+                input.witness,
+                input.pubkey,
+                input.hide_r_point,
+                commitment_to_pk_x.clone(),
+                commitment_to_pk_y,
+            )?;
+
+            members.push(ThresholdMember {
+                exp_commitments,
+                ownership_proof,
+                membership_proof,
+            });
+            commitments_to_pk_x.push(commitment_to_pk_x);
+            pubkeys_x.push(pubkey_x);
+        }
+
+        let distinctness_proof =
+            DistinctnessProof::construct(rng, pedersen.cycle(), &commitments_to_pk_x, &pubkeys_x)
+                .map_err(|_| "signers are not distinct".to_string())?;
+
+        Ok(Self {
+            msg_hash,
+            ring_digest: ring_digest(ring)?,
+            members,
+            distinctness_proof,
+            challenge,
+            message_scheme,
+        })
+    }
+
+    /// Verifies that the proof contains at least `k` distinct members of the
+    /// ring and returns the claims bound to it, `k` must be positive.
+    pub fn verify<R: CryptoCoreRng, V: RingView<CC> + ?Sized>(
+        &self,
+        rng: &mut R,
+        ring: &V,
+        k: usize,
+    ) -> Result<&ProofClaims, String> {
+        if k == 0 {
+            return Err("threshold must be positive".to_string());
+        }
+        if self.members.is_empty() {
+            return Err("no signers given".to_string());
+        }
+        if self.members.len() < k {
+            return Err("not enough signers".to_string());
+        }
+        if ring_digest(ring)? != self.ring_digest {
+            return Err("ring digest mismatch".to_string());
+        }
+        verify_message(
+            self.msg_hash,
+            &self.challenge,
+            self.message_scheme,
+            None,
+            None,
+        )?;
+
+        // NOTE the distinctness proof is only sound if the prover does not
+        // know the discrete logarithm of the blinding generator
+        let pedersen = PedersenCycle::fixed();
+        for member in self.members.iter() {
+            member.ownership_proof.verify(
+                rng,
+                &pedersen,
+                self.msg_hash,
+                &member.exp_commitments,
+            )?;
+            member.membership_proof.verify(
+                rng,
+                pedersen.cycle(),
+                &member.exp_commitments.px,
+                ring,
+            )?;
+        }
+
+        let commitments_to_pk_x = self
+            .members
+            .iter()
+            .map(|member| &member.exp_commitments.px)
+            .collect::<Vec<&Point<CC>>>();
+        self.distinctness_proof
+            .verify(rng, pedersen.cycle(), &commitments_to_pk_x)?;
+
+        Ok(&self.challenge.claims)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arithmetic::Modular;
+    use crate::curve::{Secp256k1, Tom256k1};
+    use crate::parse::{Signature, Witness};
+    use crate::U256;
+    use rand::rngs::StdRng;
+    use rand_core::SeedableRng;
+
+    #[test]
+    fn threshold_attest() {
+        let mut rng = StdRng::from_seed([44; 32]);
+
+        let mut claims = ProofClaims::new("almafa".to_string());
+        claims.role = Some("signer".to_string());
+        let challenge = JoinChallenge {
+            claims,
+            nonce: "5c8e21f07a3b9d46".to_string(),
+            expires_at: 1_700_000_000,
+            domain: None,
+        };
+        let msg_hash = MessageScheme::default().hash(&challenge.message());
+        let secrets = (0..3)
+            .map(|_| Scalar::<Secp256k1>::random(&mut rng))
+            .collect::<Vec<_>>();
+        let pubkeys = secrets
+            .iter()
+            .map(|secret| (&Point::<Secp256k1>::GENERATOR * *secret).to_affine())
+            .collect::<Vec<_>>();
+        let mut ring = (0..6u8)
+            .map(|i| Scalar::<Tom256k1>::new(U256::from_u8(i)))
+            .collect::<Vec<_>>();
+        for (i, pubkey) in pubkeys.iter().enumerate() {
+            ring[2 * i + 1] = pubkey.x().to_cycle_scalar();
+        }
+
+        let inputs = |signers: &[usize]| {
+            signers
+                .iter()
+                .map(|&i| ParsedProofInput {
+                    msg_hash,
+                    pubkey: pubkeys[i].clone(),
+                    witness: Witness::Signature(Signature::sign(&secrets[i], &msg_hash)),
+                    index: None,
+                    challenge: challenge.clone(),
+                    nullifier: false,
+                    message_scheme: MessageScheme::default(),
+                    siwe: None,
+                    hide_r_point: false,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            ThresholdAttestProof::<Secp256k1, Tom256k1>::construct(
+                &mut rng,
+                inputs(&[2, 0, 2]),
+                &ring,
+            )
+            .err(),
+            Some("signers are not distinct".to_string())
+        );
+
+        let proof = ThresholdAttestProof::<Secp256k1, Tom256k1>::construct(
+            &mut rng,
+            inputs(&[2, 0]),
+            &ring,
+        )
+        .unwrap();
+        assert_eq!(proof.verify(&mut rng, &ring, 2), Ok(&challenge.claims));
+        assert!(proof.verify(&mut rng, &ring, 1).is_ok());
+        assert_eq!(
+            proof.verify(&mut rng, &ring, 3),
+            Err("not enough signers".to_string())
+        );
+        assert_eq!(
+            proof.verify(&mut rng, &ring[..5], 2),
+            Err("ring digest mismatch".to_string())
+        );
+        assert_eq!(
+            proof.verify(&mut rng, &ring, 0),
+            Err("threshold must be positive".to_string())
+        );
+
+        // a member cannot be counted twice
+        let bytes = proof.try_to_vec().unwrap();
+        let mut duplicated =
+            ThresholdAttestProof::<Secp256k1, Tom256k1>::try_from_slice(&bytes).unwrap();
+        duplicated.members[1] = ThresholdAttestProof::<Secp256k1, Tom256k1>::try_from_slice(&bytes)
+            .unwrap()
+            .members
+            .remove(0);
+        assert_eq!(
+            duplicated.verify(&mut rng, &ring, 2),
+            Err("failed to verify distinctness".to_string())
+        );
+
+        // nor can an empty proof pass any threshold
+        let mut empty = duplicated;
+        empty.members.clear();
+        assert_eq!(
+            empty.verify(&mut rng, &ring, 0),
+            Err("threshold must be positive".to_string())
+        );
+        assert_eq!(
+            empty.verify(&mut rng, &ring, 1),
+            Err("no signers given".to_string())
+        );
+    }
+}
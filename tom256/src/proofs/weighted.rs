@@ -0,0 +1,603 @@
+use super::membership::{
+    MembershipCommitment, MembershipResponse, MembershipStatement, MembershipWitness,
+};
+use super::sigma::{SigmaProtocol, Transcript};
+use super::{
+    signer_index, verify_message, BulletproofGenerators, ExpCommitmentPoints, OwnershipProof,
+    RangeProof,
+};
+use crate::arithmetic::multimult::MultiMult;
+use crate::arithmetic::{Point, Scalar};
+use crate::challenge::JoinChallenge;
+use crate::claims::ProofClaims;
+use crate::curve::{Curve, Cycle};
+use crate::message::MessageScheme;
+use crate::parse::{ParsedProofInput, ParsedRing};
+use crate::pedersen::*;
+use crate::ring::{ring_digest, RingView};
+use crate::rng::CryptoCoreRng;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Number of bits of the range proof on `balance - threshold`.
+///
+/// Balances of the weighted rings are expected in units where the surplus
+/// over any threshold fits in 64 bits, e.g. whole tokens instead of wei.
+pub const BALANCE_BITS: usize = 64;
+
+// view of the weighted ring as the single column `key + weight * balance`
+struct CombinedRing<'a, C: Curve, K: ?Sized, B: ?Sized> {
+    keys: &'a K,
+    balances: &'a B,
+    weight: Scalar<C>,
+}
+
+impl<'a, C, K, B> RingView<C> for CombinedRing<'a, C, K, B>
+where
+    C: Curve,
+    K: RingView<C> + ?Sized,
+    B: RingView<C> + ?Sized,
+{
+    fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    fn member(&self, index: usize) -> Scalar<C> {
+        self.keys.member(index) + self.weight * self.balances.member(index)
+    }
+}
+
+/// Proof that the committed key and the committed balance form an entry of
+/// a weighted ring of `(pubkey_x, balance)` pairs.
+///
+/// After both commitments are fixed a weight is derived from the transcript
+/// and the one-of-many argument of [`MembershipProof`](super::MembershipProof)
+/// is run on the column `key + weight * balance` with the accordingly
+/// combined commitment. A prover that opens the combination to an entry
+/// opens both columns to the same entry, unless it guessed the weight in
+/// advance.
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
+pub struct WeightedMembershipProof<C: Curve> {
+    commitment: MembershipCommitment<C>,
+    response: MembershipResponse<C>,
+}
+
+impl<C: Curve> WeightedMembershipProof<C> {
+    const HASH_ID: &'static [u8] = b"weighted-membership-proof";
+
+    pub fn construct<R: CryptoCoreRng>(
+        rng: &mut R,
+        pedersen_generator: &PedersenGenerator<C>,
+        commitment_to_key: &PedersenCommitment<C>,
+        commitment_to_balance: &PedersenCommitment<C>,
+        index: usize,
+        keys: &[Scalar<C>],
+        balances: &[Scalar<C>],
+    ) -> Result<Self, String> {
+        if keys.len() != balances.len() {
+            return Err("ring column length mismatch".to_string());
+        }
+        if index >= keys.len() {
+            return Err("invalid index".to_string());
+        }
+
+        let (transcript, weight) = Self::transcript(
+            commitment_to_key.commitment(),
+            commitment_to_balance.commitment(),
+            keys,
+            balances,
+        )?;
+        let combined_commitment = PedersenCommitment::new(
+            Self::combine(
+                commitment_to_key.commitment(),
+                commitment_to_balance.commitment(),
+                weight,
+            ),
+            *commitment_to_key.randomness() + weight * *commitment_to_balance.randomness(),
+        );
+        let ring = CombinedRing {
+            keys,
+            balances,
+            weight,
+        };
+        let statement =
+            MembershipStatement::new(pedersen_generator, combined_commitment.commitment(), &ring)?;
+        let (commitment, response) = statement.prove(
+            rng,
+            &MembershipWitness::new(index, &combined_commitment),
+            transcript,
+        );
+
+        Ok(Self {
+            commitment,
+            response,
+        })
+    }
+
+    pub fn verify<R, K, B>(
+        &self,
+        rng: &mut R,
+        pedersen_generator: &PedersenGenerator<C>,
+        commitment_to_key: &Point<C>,
+        commitment_to_balance: &Point<C>,
+        keys: &K,
+        balances: &B,
+    ) -> Result<(), String>
+    where
+        R: CryptoCoreRng,
+        K: RingView<C> + ?Sized,
+        B: RingView<C> + ?Sized,
+    {
+        if keys.len() != balances.len() {
+            return Err("ring column length mismatch".to_string());
+        }
+
+        let (transcript, weight) =
+            Self::transcript(commitment_to_key, commitment_to_balance, keys, balances)?;
+        let combined_commitment = Self::combine(commitment_to_key, commitment_to_balance, weight);
+        let ring = CombinedRing {
+            keys,
+            balances,
+            weight,
+        };
+        let statement = MembershipStatement::new(pedersen_generator, &combined_commitment, &ring)?;
+        let mut multimult = MultiMult::new();
+        statement.verify(
+            rng,
+            &self.commitment,
+            &self.response,
+            transcript,
+            &mut multimult,
+        )?;
+
+        if multimult.evaluate().is_identity() {
+            Ok(())
+        } else {
+            Err("failed to verify weighted membership".to_owned())
+        }
+    }
+
+    fn combine(
+        commitment_to_key: &Point<C>,
+        commitment_to_balance: &Point<C>,
+        weight: Scalar<C>,
+    ) -> Point<C> {
+        commitment_to_key + &(commitment_to_balance * weight)
+    }
+
+    // returns the transcript of the membership proof and the weight of the
+    // balance column
+    fn transcript<K, B>(
+        commitment_to_key: &Point<C>,
+        commitment_to_balance: &Point<C>,
+        keys: &K,
+        balances: &B,
+    ) -> Result<(Transcript, Scalar<C>), String>
+    where
+        K: RingView<C> + ?Sized,
+        B: RingView<C> + ?Sized,
+    {
+        let mut transcript = Transcript::new(Self::HASH_ID);
+        transcript.append_bytes(&ring_digest(keys)?);
+        transcript.append_bytes(&ring_digest(balances)?);
+        transcript.append_points(&[commitment_to_key, commitment_to_balance]);
+        let weight = transcript.next_challenge();
+        Ok((transcript, weight))
+    }
+}
+
+/// Proof that the committed key is in a weighted ring and its balance is at
+/// least a public threshold, without revealing the entry or the balance.
+///
+/// The balance is committed with fresh randomness, the proof contains a
+/// [`WeightedMembershipProof`] of the key and this commitment and a
+/// [`RangeProof`] that `balance - threshold` is in `[0, 2^BALANCE_BITS)`.
+/// The proven threshold is public, the proof is accepted for any threshold
+/// not above it.
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
+pub struct BalanceThresholdProof<C: Curve> {
+    pub threshold: Scalar<C>,
+    commitment_to_balance: Point<C>,
+    membership_proof: WeightedMembershipProof<C>,
+    range_proof: RangeProof<C>,
+}
+
+impl<C: Curve> BalanceThresholdProof<C> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn construct<R: CryptoCoreRng>(
+        rng: &mut R,
+        pedersen_generator: &PedersenGenerator<C>,
+        generators: &BulletproofGenerators<C>,
+        commitment_to_key: &PedersenCommitment<C>,
+        index: usize,
+        keys: &[Scalar<C>],
+        balances: &[Scalar<C>],
+        threshold: Scalar<C>,
+    ) -> Result<Self, String> {
+        let balance = *balances
+            .get(index)
+            .ok_or_else(|| "invalid index".to_string())?;
+        if balance < threshold {
+            return Err("balance is below the threshold".to_string());
+        }
+        let commitment_to_balance = pedersen_generator.commit(rng, balance);
+        let membership_proof = WeightedMembershipProof::construct(
+            rng,
+            pedersen_generator,
+            commitment_to_key,
+            &commitment_to_balance,
+            index,
+            keys,
+            balances,
+        )?;
+        let range_proof = RangeProof::construct(
+            rng,
+            pedersen_generator,
+            generators,
+            &[surplus(&commitment_to_balance, threshold)],
+            &[balance - threshold],
+            BALANCE_BITS,
+        )?;
+
+        Ok(Self {
+            threshold,
+            commitment_to_balance: commitment_to_balance.into_commitment(),
+            membership_proof,
+            range_proof,
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify<R, K, B>(
+        &self,
+        rng: &mut R,
+        pedersen_generator: &PedersenGenerator<C>,
+        generators: &BulletproofGenerators<C>,
+        commitment_to_key: &Point<C>,
+        keys: &K,
+        balances: &B,
+        threshold: Scalar<C>,
+    ) -> Result<(), String>
+    where
+        R: CryptoCoreRng,
+        K: RingView<C> + ?Sized,
+        B: RingView<C> + ?Sized,
+    {
+        if self.threshold < threshold {
+            return Err("balance threshold not met".to_string());
+        }
+        self.membership_proof.verify(
+            rng,
+            pedersen_generator,
+            commitment_to_key,
+            &self.commitment_to_balance,
+            keys,
+            balances,
+        )?;
+        let commitment_to_surplus =
+            &self.commitment_to_balance - &(&Point::GENERATOR * self.threshold);
+        self.range_proof.verify(
+            rng,
+            pedersen_generator,
+            generators,
+            &[&commitment_to_surplus],
+            BALANCE_BITS,
+        )
+    }
+}
+
+// commitment to `balance - threshold` with the randomness of the balance
+pub(super) fn surplus<C: Curve>(
+    commitment_to_balance: &PedersenCommitment<C>,
+    threshold: Scalar<C>,
+) -> PedersenCommitment<C> {
+    PedersenCommitment::new(
+        commitment_to_balance.commitment() - &(&Point::<C>::GENERATOR * threshold),
+        *commitment_to_balance.randomness(),
+    )
+}
+
+/// Zero-knowledge proof that the prover controls a key of a weighted ring
+/// whose balance is at least a public threshold.
+///
+/// It is a [`ZkAttestProof`](super::ZkAttestProof) where the membership proof
+/// is replaced by a [`BalanceThresholdProof`] on the committed x coordinate
+/// of the public key.
+///
+/// The commitments are made with the generators fixed by the protocol, see
+/// [`PedersenCycle::fixed`].
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct WeightedAttestProof<C: Curve, CC: Cycle<C>> {
+    pub msg_hash: Scalar<C>,
+    pub ring_digest: [u8; 32],
+    pub balances_digest: [u8; 32],
+    pub exp_commitments: ExpCommitmentPoints<C, CC>, // exp, pkx, pky
+    pub ownership_proof: OwnershipProof<C, CC>,
+    pub balance_proof: BalanceThresholdProof<CC>,
+    pub challenge: JoinChallenge,
+    pub message_scheme: MessageScheme,
+}
+
+impl<C: Curve, CC: Cycle<C>> WeightedAttestProof<C, CC> {
+    /// Generates the proof on the weighted ring given by its key and balance
+    /// columns.
+    pub fn construct<R: CryptoCoreRng>(
+        rng: &mut R,
+        generators: &BulletproofGenerators<CC>,
+        input: ParsedProofInput<C>,
+        ring: &ParsedRing<CC>,
+        balances: &[Scalar<CC>],
+        threshold: Scalar<CC>,
+    ) -> Result<Self, String> {
+        if input.nullifier {
+            return Err("nullifiers are not supported for weighted proofs".to_string());
+        }
+        if input.siwe.is_some() {
+            return Err("SIWE messages are not supported for weighted proofs".to_string());
+        }
+        let index = signer_index(ring, &input)?;
+        let pedersen = PedersenCycle::fixed();
+
+        let commitment_to_pk_x = pedersen
+            .cycle()
+            .commit(rng, input.pubkey.x().to_cycle_scalar());
+        let commitment_to_pk_y = pedersen
+            .cycle()
+            .commit(rng, input.pubkey.y().to_cycle_scalar());
+
+        let balance_proof = BalanceThresholdProof::construct(
+            rng,
+            pedersen.cycle(),
+            generators,
+            &commitment_to_pk_x,
+            index,
+            ring,
+            balances,
+            threshold,
+        )?;
+        let (ownership_proof, exp_commitments) = OwnershipProof::construct(
+            rng,
+            &pedersen,
+            input.msg_hash,
+            input.witness,
+            input.pubkey,
+            input.hide_r_point,
+            commitment_to_pk_x,
+            commitment_to_pk_y,
+        )?;
+
+        Ok(Self {
+            msg_hash: input.msg_hash,
+            ring_digest: ring_digest(ring)?,
+            balances_digest: ring_digest(balances)?,
+            exp_commitments,
+            ownership_proof,
+            balance_proof,
+            challenge: input.challenge,
+            message_scheme: input.message_scheme,
+        })
+    }
+
+    /// Verifies that the signer's balance in the weighted ring is at least
+    /// `threshold` and returns the claims bound to the proof.
+    ///
+    /// A proof generated for a higher threshold is accepted as well.
+    pub fn verify<R, K, B>(
+        &self,
+        rng: &mut R,
+        generators: &BulletproofGenerators<CC>,
+        ring: &K,
+        balances: &B,
+        threshold: Scalar<CC>,
+    ) -> Result<&ProofClaims, String>
+    where
+        R: CryptoCoreRng,
+        K: RingView<CC> + ?Sized,
+        B: RingView<CC> + ?Sized,
+    {
+        if ring_digest(ring)? != self.ring_digest || ring_digest(balances)? != self.balances_digest
+        {
+            return Err("ring digest mismatch".to_string());
+        }
+        verify_message(
+            self.msg_hash,
+            &self.challenge,
+            self.message_scheme,
+            None,
+            None,
+        )?;
+
+        let pedersen = PedersenCycle::fixed();
+        self.ownership_proof
+            .verify(rng, &pedersen, self.msg_hash, &self.exp_commitments)?;
+        self.balance_proof.verify(
+            rng,
+            pedersen.cycle(),
+            generators,
+            &self.exp_commitments.px,
+            ring,
+            balances,
+            threshold,
+        )?;
+
+        Ok(&self.challenge.claims)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arithmetic::Modular;
+    use crate::curve::{Secp256k1, Tom256k1};
+    use crate::parse::{Signature, Witness};
+    use crate::U256;
+    use rand::rngs::StdRng;
+    use rand_core::SeedableRng;
+
+    fn scalars(values: &[u64]) -> Vec<Scalar<Tom256k1>> {
+        values
+            .iter()
+            .map(|value| Scalar::new(U256::from_u64(*value)))
+            .collect()
+    }
+
+    #[test]
+    fn weighted_membership_proof() {
+        let mut rng = StdRng::from_seed([46; 32]);
+        let pedersen_generator = PedersenGenerator::<Tom256k1>::new(&mut rng);
+        let keys = scalars(&[11, 12, 13, 14, 15]);
+        let balances = scalars(&[100, 250, 30, 250, 0]);
+
+        let commitment_to_key = pedersen_generator.commit(&mut rng, keys[1]);
+        let commitment_to_balance = pedersen_generator.commit(&mut rng, balances[1]);
+        let proof = WeightedMembershipProof::construct(
+            &mut rng,
+            &pedersen_generator,
+            &commitment_to_key,
+            &commitment_to_balance,
+            1,
+            &keys,
+            &balances,
+        )
+        .unwrap();
+        assert_eq!(
+            proof.verify(
+                &mut rng,
+                &pedersen_generator,
+                commitment_to_key.commitment(),
+                commitment_to_balance.commitment(),
+                &keys,
+                &balances,
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            proof.verify(
+                &mut rng,
+                &pedersen_generator,
+                commitment_to_key.commitment(),
+                commitment_to_balance.commitment(),
+                &keys,
+                &balances[1..],
+            ),
+            Err("ring column length mismatch".to_string())
+        );
+
+        // the balance of another entry cannot be claimed
+        let commitment_to_other_balance = pedersen_generator.commit(&mut rng, balances[0]);
+        let proof = WeightedMembershipProof::construct(
+            &mut rng,
+            &pedersen_generator,
+            &commitment_to_key,
+            &commitment_to_other_balance,
+            1,
+            &keys,
+            &balances,
+        )
+        .unwrap();
+        assert_eq!(
+            proof.verify(
+                &mut rng,
+                &pedersen_generator,
+                commitment_to_key.commitment(),
+                commitment_to_other_balance.commitment(),
+                &keys,
+                &balances,
+            ),
+            Err("failed to verify weighted membership".to_string())
+        );
+    }
+
+    #[test]
+    fn weighted_attest() {
+        let mut rng = StdRng::from_seed([48; 32]);
+        let generators = BulletproofGenerators::new(BALANCE_BITS);
+
+        let challenge = JoinChallenge {
+            claims: ProofClaims::new("almafa".to_string()),
+            nonce: "0d4a7e19c2b8f356".to_string(),
+            expires_at: 1_700_000_000,
+            domain: None,
+        };
+        let msg_hash = MessageScheme::default().hash(&challenge.message());
+        let secret = Scalar::<Secp256k1>::random(&mut rng);
+        let pubkey = (&Point::<Secp256k1>::GENERATOR * secret).to_affine();
+        let mut ring = scalars(&[1, 2, 3, 4]);
+        ring[2] = pubkey.x().to_cycle_scalar();
+        let balances = scalars(&[500, 20, 150, 1000]);
+
+        let input = || ParsedProofInput {
+            msg_hash,
+            pubkey: pubkey.clone(),
+            witness: Witness::Signature(Signature::sign(&secret, &msg_hash)),
+            index: None,
+            challenge: challenge.clone(),
+            nullifier: false,
+            message_scheme: MessageScheme::default(),
+            siwe: None,
+            hide_r_point: false,
+        };
+        let threshold = Scalar::new(U256::from_u64(100));
+        let proof = WeightedAttestProof::construct(
+            &mut rng,
+            &generators,
+            input(),
+            &ring,
+            &balances,
+            threshold,
+        )
+        .unwrap();
+        assert_eq!(
+            proof.verify(&mut rng, &generators, &ring, &balances, threshold),
+            Ok(&challenge.claims)
+        );
+        assert!(proof
+            .verify(&mut rng, &generators, &ring, &balances, Scalar::ZERO)
+            .is_ok());
+        assert_eq!(
+            proof.verify(
+                &mut rng,
+                &generators,
+                &ring,
+                &balances,
+                Scalar::new(U256::from_u64(151)),
+            ),
+            Err("balance threshold not met".to_string())
+        );
+        // raising the proven threshold invalidates the range proof
+        let mut forged = WeightedAttestProof::<Secp256k1, Tom256k1>::try_from_slice(
+            &proof.try_to_vec().unwrap(),
+        )
+        .unwrap();
+        forged.balance_proof.threshold = Scalar::new(U256::from_u64(151));
+        assert_eq!(
+            forged.verify(
+                &mut rng,
+                &generators,
+                &ring,
+                &balances,
+                Scalar::new(U256::from_u64(151)),
+            ),
+            Err("failed to verify range proof".to_string())
+        );
+        let mut other_balances = balances.clone();
+        other_balances[2] = Scalar::new(U256::from_u64(99));
+        assert_eq!(
+            proof.verify(&mut rng, &generators, &ring, &other_balances, threshold),
+            Err("ring digest mismatch".to_string())
+        );
+
+        assert_eq!(
+            WeightedAttestProof::construct(
+                &mut rng,
+                &generators,
+                input(),
+                &ring,
+                &balances,
+                Scalar::new(U256::from_u64(200)),
+            )
+            .err(),
+            Some("balance is below the threshold".to_string())
+        );
+    }
+}
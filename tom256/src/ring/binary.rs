@@ -0,0 +1,360 @@
+//! Binary ring file format.
+//!
+//! A ring file consists of a 48 byte header followed by the ring members,
+//! each encoded as a 32 byte big-endian integer:
+//!
+//! | offset | size | content                                      |
+//! |--------|------|----------------------------------------------|
+//! | 0      | 4    | magic bytes `TRNG`                           |
+//! | 4      | 1    | format version                               |
+//! | 5      | 1    | id of the curve the members are scalars of   |
+//! | 6      | 2    | reserved, zero                               |
+//! | 8      | 8    | number of members, little-endian             |
+//! | 16     | 32   | canonical digest, see [`ring_digest`]        |
+use super::{padded_ring_log2, ring_digest, RingView};
+use crate::arithmetic::{Modular, Scalar};
+use crate::curve::{Curve, Cycle, Secp256k1, Tom256k1};
+use crate::parse::{parse_ring_entry, ParsedRing};
+use crate::U256;
+
+use bigint::Encoding;
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use sha3::{Digest, Keccak256};
+
+use std::fmt;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::marker::PhantomData;
+
+const MAGIC: [u8; 4] = *b"TRNG";
+const VERSION: u8 = 1;
+pub(super) const HEADER_LEN: usize = 48;
+const MEMBER_LEN: usize = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RingHeader {
+    pub curve_id: u8,
+    pub len: u64,
+    pub digest: [u8; 32],
+}
+
+impl RingHeader {
+    pub fn to_bytes(&self) -> [u8; HEADER_LEN] {
+        let mut bytes = [0u8; HEADER_LEN];
+        bytes[..4].copy_from_slice(&MAGIC);
+        bytes[4] = VERSION;
+        bytes[5] = self.curve_id;
+        bytes[8..16].copy_from_slice(&self.len.to_le_bytes());
+        bytes[16..].copy_from_slice(&self.digest);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < HEADER_LEN {
+            return Err("truncated ring header".to_string());
+        }
+        if bytes[..4] != MAGIC {
+            return Err("not a binary ring file".to_string());
+        }
+        if bytes[4] != VERSION {
+            return Err(format!("unsupported ring file version {}", bytes[4]));
+        }
+        if bytes[6..8] != [0, 0] {
+            return Err("nonzero reserved ring header bytes".to_string());
+        }
+        let mut len = [0u8; 8];
+        len.copy_from_slice(&bytes[8..16]);
+        let mut digest = [0u8; 32];
+        digest.copy_from_slice(&bytes[16..HEADER_LEN]);
+        Ok(Self {
+            curve_id: bytes[5],
+            len: u64::from_le_bytes(len),
+            digest,
+        })
+    }
+}
+
+/// Writes ring members one by one, thus rings may be converted without
+/// holding them in memory.
+///
+/// The header is written by [`RingWriter::finish`] once all members are
+/// known.
+pub struct RingWriter<C, W> {
+    writer: W,
+    start: u64,
+    hasher: Keccak256,
+    first: Option<[u8; 32]>,
+    len: u64,
+    _curve: PhantomData<C>,
+}
+
+impl<C: Curve, W: Write + Seek> RingWriter<C, W> {
+    pub fn new(mut writer: W) -> Result<Self, String> {
+        curve_id::<C>()?;
+        let start = writer.stream_position().map_err(|e| e.to_string())?;
+        // NOTE placeholder until the length and digest are known
+        writer
+            .write_all(&[0u8; HEADER_LEN])
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            writer,
+            start,
+            hasher: Keccak256::new(),
+            first: None,
+            len: 0,
+            _curve: PhantomData,
+        })
+    }
+
+    pub fn push(&mut self, member: &Scalar<C>) -> Result<(), String> {
+        let bytes = member.inner().to_be_bytes();
+        self.hasher.update(bytes);
+        self.writer.write_all(&bytes).map_err(|e| e.to_string())?;
+        self.first.get_or_insert(bytes);
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<RingHeader, String> {
+        // NOTE the digest covers the padding, see `ring_digest`
+        let first = self.first.ok_or_else(|| "empty ring".to_string())?;
+        let len = usize::try_from(self.len).map_err(|e| e.to_string())?;
+        for _ in len..1 << padded_ring_log2(len)? {
+            self.hasher.update(first);
+        }
+        let header = RingHeader {
+            curve_id: curve_id::<C>()?,
+            len: self.len,
+            digest: self.hasher.finalize().into(),
+        };
+        self.writer
+            .seek(SeekFrom::Start(self.start))
+            .and_then(|_| self.writer.write_all(&header.to_bytes()))
+            .and_then(|_| self.writer.seek(SeekFrom::End(0)))
+            .and_then(|_| self.writer.flush())
+            .map_err(|e| e.to_string())?;
+        Ok(header)
+    }
+}
+
+/// Writes an in-memory ring in the binary format.
+pub fn write_ring<C: Curve, W: Write + Seek>(
+    ring: &[Scalar<C>],
+    writer: W,
+) -> Result<RingHeader, String> {
+    let mut ring_writer = RingWriter::new(writer)?;
+    for member in ring {
+        ring_writer.push(member)?;
+    }
+    ring_writer.finish()
+}
+
+/// Converts a JSON ring, i.e. an array of hex encoded public keys, into the
+/// binary format.
+///
+/// The JSON array is processed entry by entry, so the converted ring is
+/// never held in memory as a whole.
+pub fn convert_json_ring<C: Cycle<Secp256k1>, R: Read, W: Write + Seek>(
+    reader: R,
+    writer: W,
+) -> Result<RingHeader, String> {
+    let mut ring_writer = RingWriter::<C, W>::new(writer)?;
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    deserializer
+        .deserialize_seq(JsonRingVisitor {
+            writer: &mut ring_writer,
+        })
+        .and_then(|_| deserializer.end())
+        .map_err(|e| e.to_string())?;
+    ring_writer.finish()
+}
+
+struct JsonRingVisitor<'a, C, W> {
+    writer: &'a mut RingWriter<C, W>,
+}
+
+impl<'de, 'a, C: Cycle<Secp256k1>, W: Write + Seek> Visitor<'de> for JsonRingVisitor<'a, C, W> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "an array of public keys")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let mut i = 0;
+        while let Some(entry) = seq.next_element::<String>()? {
+            let member = parse_ring_entry(&entry)
+                .map_err(|e| de::Error::custom(format!("invalid ring entry {}: {}", i, e)))?;
+            self.writer
+                .push(&member.to_cycle_scalar())
+                .map_err(de::Error::custom)?;
+            i += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Borrowed view of a binary ring.
+pub struct RingSlice<'a, C> {
+    header: RingHeader,
+    members: &'a [u8],
+    _curve: PhantomData<C>,
+}
+
+impl<'a, C: Curve> RingSlice<'a, C> {
+    /// Validates the header, the digest and the range of every member of a
+    /// binary ring.
+    pub fn new(bytes: &'a [u8]) -> Result<Self, String> {
+        let header = RingHeader::from_bytes(bytes)?;
+        if header.curve_id != curve_id::<C>()? {
+            return Err("ring curve mismatch".to_string());
+        }
+        let members = &bytes[HEADER_LEN..];
+        let expected_len = usize::try_from(header.len)
+            .ok()
+            .and_then(|len| len.checked_mul(MEMBER_LEN));
+        if expected_len != Some(members.len()) {
+            return Err("ring length mismatch".to_string());
+        }
+        // NOTE members are not reduced silently by `Scalar::new`
+        for (i, member) in members.chunks_exact(MEMBER_LEN).enumerate() {
+            if U256::from_be_slice(member) >= C::ORDER {
+                return Err(format!("ring member {} out of range", i));
+            }
+        }
+        let slice = Self {
+            header,
+            members,
+            _curve: PhantomData,
+        };
+        if ring_digest(&slice)? != header.digest {
+            return Err("ring digest mismatch".to_string());
+        }
+        Ok(slice)
+    }
+
+    pub fn header(&self) -> &RingHeader {
+        &self.header
+    }
+}
+
+impl<'a, C: Curve> RingView<C> for RingSlice<'a, C> {
+    fn len(&self) -> usize {
+        self.members.len() / MEMBER_LEN
+    }
+
+    fn member(&self, index: usize) -> Scalar<C> {
+        member_at(self.members, index)
+    }
+}
+
+/// Reads a binary ring into memory.
+pub fn read_ring<C: Curve>(bytes: &[u8]) -> Result<ParsedRing<C>, String> {
+    let slice = RingSlice::<C>::new(bytes)?;
+    Ok((0..slice.len()).map(|i| slice.member(i)).collect())
+}
+
+pub(super) fn member_at<C: Curve>(members: &[u8], index: usize) -> Scalar<C> {
+    Scalar::new(U256::from_be_slice(
+        &members[index * MEMBER_LEN..(index + 1) * MEMBER_LEN],
+    ))
+}
+
+fn curve_id<C: Curve>() -> Result<u8, String> {
+    if C::ORDER == Secp256k1::ORDER && C::PRIME_MODULUS == Secp256k1::PRIME_MODULUS {
+        Ok(0)
+    } else if C::ORDER == Tom256k1::ORDER && C::PRIME_MODULUS == Tom256k1::PRIME_MODULUS {
+        Ok(1)
+    } else {
+        Err("unsupported ring curve".to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Cursor;
+
+    // G, 2G, 3G in various encodings
+    const JSON_RING: &str = r#"[
+        "0479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8",
+        "02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5",
+        "f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9"
+    ]"#;
+
+    #[test]
+    fn json_conversion() {
+        let mut buffer = Cursor::new(Vec::new());
+        let header =
+            convert_json_ring::<Tom256k1, _, _>(JSON_RING.as_bytes(), &mut buffer).unwrap();
+        let bytes = buffer.into_inner();
+        assert_eq!(header.len, 3);
+        assert_eq!(bytes.len(), HEADER_LEN + 3 * MEMBER_LEN);
+        assert_eq!(RingHeader::from_bytes(&bytes), Ok(header));
+
+        let ring = read_ring::<Tom256k1>(&bytes).unwrap();
+        let expected = serde_json::from_str(JSON_RING)
+            .map_err(|e| e.to_string())
+            .and_then(crate::parse::parse_ring::<Tom256k1>)
+            .unwrap();
+        assert_eq!(ring, expected);
+        assert_eq!(header.digest, ring_digest(&ring).unwrap());
+
+        let mut written = Cursor::new(Vec::new());
+        assert_eq!(write_ring(&ring, &mut written), Ok(header));
+        assert_eq!(written.into_inner(), bytes);
+
+        assert_eq!(
+            read_ring::<Secp256k1>(&bytes),
+            Err("ring curve mismatch".to_string())
+        );
+    }
+
+    #[test]
+    fn invalid_rings() {
+        let ring = vec![Scalar::<Tom256k1>::ONE, Scalar::ZERO];
+        let mut buffer = Cursor::new(Vec::new());
+        write_ring(&ring, &mut buffer).unwrap();
+        let bytes = buffer.into_inner();
+        assert_eq!(read_ring::<Tom256k1>(&bytes), Ok(ring));
+
+        assert_eq!(
+            read_ring::<Tom256k1>(&bytes[..HEADER_LEN - 1]),
+            Err("truncated ring header".to_string())
+        );
+        assert_eq!(
+            read_ring::<Tom256k1>(&bytes[..bytes.len() - 1]),
+            Err("ring length mismatch".to_string())
+        );
+        let mut tampered = bytes.clone();
+        tampered[HEADER_LEN + 31] ^= 1;
+        assert_eq!(
+            read_ring::<Tom256k1>(&tampered),
+            Err("ring digest mismatch".to_string())
+        );
+        let mut tampered = bytes.clone();
+        tampered[HEADER_LEN + MEMBER_LEN..].fill(0xff);
+        assert_eq!(
+            read_ring::<Tom256k1>(&tampered),
+            Err("ring member 1 out of range".to_string())
+        );
+        let mut tampered = bytes.clone();
+        tampered[0] = b'X';
+        assert_eq!(
+            read_ring::<Tom256k1>(&tampered),
+            Err("not a binary ring file".to_string())
+        );
+        let mut tampered = bytes;
+        tampered[7] = 1;
+        assert_eq!(
+            read_ring::<Tom256k1>(&tampered),
+            Err("nonzero reserved ring header bytes".to_string())
+        );
+
+        let invalid_json =
+            r#"["0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798", "05"]"#;
+        let error =
+            convert_json_ring::<Tom256k1, _, _>(invalid_json.as_bytes(), Cursor::new(Vec::new()))
+                .unwrap_err();
+        assert!(error.starts_with("invalid ring entry 1: invalid pubkey encoding"));
+    }
+}
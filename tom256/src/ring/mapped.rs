@@ -0,0 +1,83 @@
+use super::binary::{member_at, HEADER_LEN};
+use super::{RingHeader, RingSlice, RingView};
+use crate::arithmetic::Scalar;
+use crate::curve::Curve;
+
+use memmap2::Mmap;
+
+use std::fs::File;
+use std::marker::PhantomData;
+use std::path::Path;
+
+/// Memory-mapped binary ring file.
+///
+/// Members are decoded on access, thus verifying against the ring does not
+/// copy the file into memory.
+pub struct MappedRing<C> {
+    mmap: Mmap,
+    header: RingHeader,
+    _curve: PhantomData<C>,
+}
+
+impl<C: Curve> MappedRing<C> {
+    /// Maps a binary ring file and validates it.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        // NOTE the mapping is only sound as long as the file is not modified
+        // by another process, ring files are written once and then only read
+        let mmap = unsafe { Mmap::map(&file) }.map_err(|e| e.to_string())?;
+        let header = *RingSlice::<C>::new(&mmap)?.header();
+        Ok(Self {
+            mmap,
+            header,
+            _curve: PhantomData,
+        })
+    }
+
+    pub fn header(&self) -> &RingHeader {
+        &self.header
+    }
+}
+
+impl<C: Curve> RingView<C> for MappedRing<C> {
+    fn len(&self) -> usize {
+        self.header.len as usize
+    }
+
+    fn member(&self, index: usize) -> Scalar<C> {
+        member_at(&self.mmap[HEADER_LEN..], index)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arithmetic::Modular;
+    use crate::curve::Tom256k1;
+    use crate::ring::write_ring;
+    use crate::U256;
+
+    use std::fs::OpenOptions;
+
+    #[test]
+    fn mapped_ring() {
+        let ring = (0..5u8)
+            .map(|i| Scalar::<Tom256k1>::new(U256::from_u8(i)))
+            .collect::<Vec<_>>();
+        let path = std::env::temp_dir().join(format!("tom256-ring-{}.bin", std::process::id()));
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .read(true)
+            .write(true)
+            .open(&path)
+            .unwrap();
+        let header = write_ring(&ring, file).unwrap();
+
+        let mapped = MappedRing::<Tom256k1>::open(&path).unwrap();
+        assert_eq!(mapped.header(), &header);
+        assert_eq!(mapped.len(), 5);
+        assert!((0..5).all(|i| mapped.member(i) == ring[i]));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,134 @@
+//! Building, storing and loading rings of public keys.
+mod binary;
+#[cfg(not(target_arch = "wasm32"))]
+mod mapped;
+mod resolve;
+
+pub use binary::{convert_json_ring, read_ring, write_ring, RingHeader, RingSlice, RingWriter};
+#[cfg(not(target_arch = "wasm32"))]
+pub use mapped::MappedRing;
+pub use resolve::{resolve_ring, RingResolution, TransactionDump};
+
+use crate::arithmetic::{Modular, Scalar};
+use crate::curve::Curve;
+
+use bigint::Encoding;
+use sha3::{Digest, Keccak256};
+
+use std::collections::HashMap;
+
+/// Read access to the members of a ring.
+///
+/// Besides in-memory rings this is implemented by views into binary ring
+/// files, thus large rings can be verified against without loading them into
+/// a [`ParsedRing`](crate::parse::ParsedRing) first.
+pub trait RingView<C: Curve> {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the ring member at the given index.
+    ///
+    /// Panics if the index is out of bounds.
+    fn member(&self, index: usize) -> Scalar<C>;
+}
+
+impl<C: Curve> RingView<C> for [Scalar<C>] {
+    fn len(&self) -> usize {
+        <[Scalar<C>]>::len(self)
+    }
+
+    fn member(&self, index: usize) -> Scalar<C> {
+        self[index]
+    }
+}
+
+impl<C: Curve> RingView<C> for Vec<Scalar<C>> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn member(&self, index: usize) -> Scalar<C> {
+        self[index]
+    }
+}
+
+/// Storage of ring snapshots indexed by their digest.
+pub trait RingStore<C: Curve> {
+    type Ring: RingView<C> + ?Sized;
+
+    /// Returns the ring snapshot with the given digest.
+    fn ring(&self, digest: &[u8; 32]) -> Option<&Self::Ring>;
+}
+
+impl<C: Curve, V: RingView<C>> RingStore<C> for HashMap<[u8; 32], V> {
+    type Ring = V;
+
+    fn ring(&self, digest: &[u8; 32]) -> Option<&V> {
+        self.get(digest)
+    }
+}
+
+/// Returns the base 2 logarithm of the ring length padded to the next power
+/// of two.
+///
+/// The padding entries are copies of the first ring member.
+pub fn padded_ring_log2(len: usize) -> Result<usize, String> {
+    if len == 0 {
+        Err("empty ring".to_string())
+    } else {
+        let log_2_ring_len = len.log2();
+        // 2^log_2_ring_len is always less than or equal to the length
+        // because log2 always rounds down
+        if len != 1 << log_2_ring_len {
+            Ok((log_2_ring_len + 1) as usize)
+        } else {
+            Ok(log_2_ring_len as usize)
+        }
+    }
+}
+
+/// Returns the canonical digest of a ring, i.e. the Keccak-256 hash of its
+/// members in order, padded to the next power of two.
+///
+/// Padding does not change the digest, thus it identifies the ring a
+/// membership proof is generated on.
+pub fn ring_digest<C: Curve, V: RingView<C> + ?Sized>(ring: &V) -> Result<[u8; 32], String> {
+    let n = padded_ring_log2(ring.len())?;
+    let mut hasher = Keccak256::new();
+    for i in 0..1 << n {
+        let member = ring.member(if i < ring.len() { i } else { 0 });
+        hasher.update(member.inner().to_be_bytes());
+    }
+    Ok(hasher.finalize().into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curve::Tom256k1;
+
+    type TomScalar = Scalar<Tom256k1>;
+
+    #[test]
+    fn padded_ring() {
+        assert!(padded_ring_log2(0).is_err());
+        assert_eq!(padded_ring_log2(1), Ok(0));
+        assert_eq!(padded_ring_log2(2), Ok(1));
+        assert_eq!(padded_ring_log2(3), Ok(2));
+        assert_eq!(padded_ring_log2(4), Ok(2));
+        assert_eq!(padded_ring_log2(9), Ok(4));
+
+        // the padding repeats the first member
+        let ring = vec![TomScalar::ONE, TomScalar::ZERO, TomScalar::ZERO];
+        let mut padded = ring.clone();
+        padded.push(TomScalar::ONE);
+        assert_eq!(ring_digest(&ring), ring_digest(&padded));
+        let mut padded = ring.clone();
+        padded.push(TomScalar::ZERO);
+        assert_ne!(ring_digest(&ring), ring_digest(&padded));
+        assert!(ring_digest(&Vec::<TomScalar>::new()).is_err());
+    }
+}
@@ -0,0 +1,114 @@
+use crate::arithmetic::AffinePoint;
+use crate::curve::Secp256k1;
+use crate::parse::{encode_pubkey, Address, Ring, SignedTransaction};
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::{HashMap, HashSet};
+
+/// Raw signed transactions in hex encoding, e.g. collected via
+/// `eth_getRawTransactionByHash`.
+pub type TransactionDump = Vec<String>;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RingResolution {
+    /// Uncompressed public keys of the resolved addresses in the order of
+    /// the address list.
+    pub ring: Ring,
+    /// Checksummed addresses that have not sent any of the transactions,
+    /// thus their public key is unknown.
+    pub unresolved: Vec<String>,
+}
+
+/// Resolves the public keys behind a list of addresses by recovering the
+/// senders of the given transactions.
+///
+/// Transactions of senders outside the address list are ignored, while
+/// repeated addresses are only added to the ring once.
+pub fn resolve_ring(
+    addresses: &[String],
+    transactions: &[String],
+) -> Result<RingResolution, String> {
+    let mut senders = HashMap::<Address, AffinePoint<Secp256k1>>::new();
+    for (i, raw) in transactions.iter().enumerate() {
+        let pubkey = SignedTransaction::parse(raw)
+            .and_then(|tx| tx.sender_pubkey())
+            .map_err(|e| format!("invalid transaction {}: {}", i, e))?;
+        senders.insert(Address::from_pubkey(&pubkey), pubkey);
+    }
+
+    let mut seen = HashSet::new();
+    let mut resolution = RingResolution {
+        ring: Vec::new(),
+        unresolved: Vec::new(),
+    };
+    for (i, address) in addresses.iter().enumerate() {
+        let address =
+            Address::parse(address).map_err(|e| format!("invalid address {}: {}", i, e))?;
+        if !seen.insert(address) {
+            continue;
+        }
+        match senders.get(&address) {
+            Some(pubkey) => resolution.ring.push(encode_pubkey(pubkey)),
+            None => resolution.unresolved.push(address.to_checksum()),
+        }
+    }
+    Ok(resolution)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse::parse_pubkey;
+
+    const ADDRESSES: &str = include_str!("../../fixtures/addresses.json");
+    const TRANSACTIONS: &str = include_str!("../../fixtures/transactions.json");
+
+    #[test]
+    fn fixture_resolution() {
+        let addresses: Vec<String> = serde_json::from_str(ADDRESSES).unwrap();
+        let transactions: TransactionDump = serde_json::from_str(TRANSACTIONS).unwrap();
+        let resolution = resolve_ring(&addresses, &transactions).unwrap();
+
+        assert_eq!(
+            resolution.unresolved,
+            vec!["0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed".to_string()]
+        );
+        // legacy EIP-155, legacy, EIP-2930 and EIP-1559 senders
+        let resolved = resolution
+            .ring
+            .iter()
+            .map(|pubkey| Address::from_pubkey(&parse_pubkey(pubkey).unwrap()).to_checksum())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            resolved,
+            vec![
+                addresses[0].clone(),
+                addresses[2].clone(),
+                addresses[3].clone(),
+                "0x1972824c88B18Dad3432250e3bAC08D6F0E5Fe4A".to_string(),
+            ]
+        );
+        // public key of the private key 0x4646..46
+        assert_eq!(
+            resolution.ring[0],
+            "044bc2a31265153f07e70e0bab08724e6b85e217f8cd628ceb62974247bb493382ce28cab79ad7119ee1ad3ebcdb98a16805211530ecc6cfefa1b88e6dff99232a"
+        );
+    }
+
+    #[test]
+    fn invalid_inputs() {
+        let transactions: TransactionDump = serde_json::from_str(TRANSACTIONS).unwrap();
+        assert_eq!(
+            resolve_ring(&["0x1234".to_string()], &transactions),
+            Err("invalid address 0: invalid address length".to_string())
+        );
+        let mut transactions = transactions;
+        transactions.push("0x03".to_string());
+        assert_eq!(
+            resolve_ring(&[], &transactions),
+            Err("invalid transaction 5: unsupported transaction type 3".to_string())
+        );
+    }
+}
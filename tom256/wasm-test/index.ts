@@ -4,19 +4,25 @@
 	try {
 		//const start = performance.now();
 		const input = {
-			msgHash: "0x9788117298a1450f6002d25f0c21d83bc6001681a2e5e31c748c0f55504b11e9",
-			signature: "0xd2943d5fa0ba2733bcbbd58853c6c1be65388d9198dcb5228e117f49409612a46394afb97a7610d16e7bea0062e71afc2a3039324c80df8ef38d3668164fad2c1c",
-			pubkey:"0454e32170dd5a0b7b641aa77daa1f3f31b8df17e51aaba6cfcb310848d26351180b6ac0399d21460443d10072700b64b454d70bfba5e93601536c740bbd099682",
+			msgHash: "0x3594f8e94a0956762506207eb47e2dc921c0aac06488b929365ab84653de9af8",
+			signature: "0x56b68718191c2563abe04486ae0c570a2d65cf01339a9de6a89f017a746ac6a87c9726c055c6e40819f633d4b33f9b842a82a4bfb615d53f84c06e4f8b47de031c",
+			pubkey:"04e68acfc0253a10620dff706b0a1b1f1f5833ea3beb3bde2250d5f271f3563606672ebc45e0b7ea2e816ecb70ca03137b1c9476eec63d4632e990020b7b6fba39",
 			index: 2,
-			guildId: "almafa",
+			challenge: {
+				claims: {
+					guildId: "almafa",
+				},
+				nonce: "9f3c2a71d04b5e86",
+				expiresAt: 1700000000,
+			},
 		};
 
 		const ring = [
             "ddd40afe39c280d2f43f05c070988dae7fbae9cdfd5fb6461acd7657e765e172",
-            "ccc50afe39c280d2f43f05c070988dae7fbae9cdfd5fb6461acd7657e765e172",
-            "54e32170dd5a0b7b641aa77daa1f3f31b8df17e51aaba6cfcb310848d2635118", // our pubkey x
-            "aaa70afe39c280d2f43f05c070988dae7fbae9cdfd5fb6461acd7657e765e172",
-            "bbb80afe39c280d2f43f05c070988dae7fbae9cdfd5fb6461acd7657e765e172",
+            "e493dbf1c10d80f3581e4904930b1404cc6c13900ee0758474fa94abe8c4cd13",
+            "e68acfc0253a10620dff706b0a1b1f1f5833ea3beb3bde2250d5f271f3563606", // our pubkey x
+            "2f8bde4d1a07209355b4a7250a5c5128e88b84bddc619ab7cba8d569b240efe4",
+            "fff97bd5755eeea420453a14355235d382f6472f8568a18b2f057a1460297556",
 		];
 
 		const proof = generateProof(input, ring);